@@ -0,0 +1,80 @@
+use cpu_time::ProcessTime;
+use encryption::el_gamal::{Curves, ElGamal};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Inf, ScalarMul};
+use num_bigint::BigUint;
+use std::time::Duration;
+
+// Times scalar multiplication on each of the built in curves. Run with `cargo bench`.
+//  pow is compared against a plain affine double and add built from group_op, which is how pow used to work
+//   before it switched to Jacobian coordinates. Then each ScalarMul method is timed, along with
+//  init_point_pow which uses the curve's precomputed table, and the constant time versions used for secret
+//   exponents.
+
+const ROUNDS: u32 = 20;
+
+fn main() {
+    for (name, curve) in [
+        ("P-256", Curves::TwoFiveSix),
+        ("P-384", Curves::ThreeEightFour),
+        ("P-521", Curves::FiveTwoOne),
+    ] {
+        let el_gamal = ElGamal::new(curve);
+        let curve = el_gamal.curve();
+        let scalar = el_gamal.private_key();
+        let point = el_gamal.public_key();
+
+        assert_eq!(point.pow(scalar, curve), affine_pow(point, scalar, curve));
+        let affine = time(|| affine_pow(point, scalar, curve));
+        let pow = time(|| point.pow(scalar, curve));
+        println!(
+            "{}: affine {:?}, pow {:?} per multiplication, {:.1}x faster",
+            name,
+            affine,
+            pow,
+            affine.as_secs_f64() / pow.as_secs_f64()
+        );
+        for method in [
+            ScalarMul::DoubleAndAdd,
+            ScalarMul::FixedWindow(4),
+            ScalarMul::Wnaf(4),
+            ScalarMul::Wnaf(5),
+        ] {
+            println!(
+                "  {:?}: {:?}",
+                method,
+                time(|| point.pow_with(scalar, curve, method))
+            );
+        }
+        println!(
+            "  init_point pow: {:?}, init_point_pow: {:?}",
+            time(|| curve.init_point().pow(scalar, curve)),
+            time(|| curve.init_point_pow(scalar))
+        );
+        println!(
+            "  pow_secret: {:?}, init_point_pow_secret: {:?}",
+            time(|| point.pow_secret(scalar, curve)),
+            time(|| curve.init_point_pow_secret(scalar))
+        );
+    }
+}
+
+fn time(mut f: impl FnMut() -> EllipticType) -> Duration {
+    let start = ProcessTime::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
+
+// Left to right double and add where every step is an affine group_op.
+fn affine_pow(base: &EllipticType, exp: &BigUint, curve: &EllipticCurve) -> EllipticType {
+    let mut result = EllipticType::Infinity(Inf::new(curve.field()));
+    for i in (0..exp.bits()).rev() {
+        result = result.group_op(&result, curve);
+        if exp.bit(i) {
+            result = result.group_op(base, curve);
+        }
+    }
+    result
+}
@@ -0,0 +1,131 @@
+use crate::clean_up::big;
+use crate::modular_numbers::ModNum;
+use num_bigint::BigUint;
+use std::fmt;
+
+// The arithmetic the point formulas in jacobian.rs and ladder.rs are written against. Elements are
+//  whatever the backend wants them to be, always fully reduced so two elements are equal exactly when the
+//   numbers they stand for are.
+//   - Montgomery<N> in montgomery.rs, fixed size arrays of words and no allocation. Used for every odd
+//     field up to 576 bits, which covers all the built in curves.
+//   - BigArithmetic, plain BigUints with a % after every operation. Used for anything else.
+// with_arithmetic! picks the backend for a field.
+// Only scalar multiplication, pow and pow_secret through jacobian.rs and ladder.rs, and Schoof's polynomials in
+//  schoof.rs run on a backend. ModNum's own add, sub and mul, and with them group_op, sqrt, and everything
+//   else written with ModNums, still work on BigUints reduced by Field::reduce.
+pub(crate) trait Arithmetic: Clone + fmt::Debug + Send + Sync {
+    type Elem: Clone + PartialEq + fmt::Debug + Send + Sync;
+
+    fn modulus(&self) -> &BigUint;
+    fn elem(&self, value: &BigUint) -> Self::Elem;
+    fn to_big(&self, elem: &Self::Elem) -> BigUint;
+    fn zero(&self) -> Self::Elem;
+    fn one(&self) -> Self::Elem;
+    fn add(&self, left: &Self::Elem, right: &Self::Elem) -> Self::Elem;
+    fn sub(&self, left: &Self::Elem, right: &Self::Elem) -> Self::Elem;
+    fn mul(&self, left: &Self::Elem, right: &Self::Elem) -> Self::Elem;
+
+    // Swaps left and right when mask is all ones and leaves them alone when it is 0, doing the same work
+    //  either way.
+    fn cswap(&self, left: &mut Self::Elem, right: &mut Self::Elem, mask: u64);
+
+    // value * times for a small, public, times. Built out of additions, which are much cheaper than a mul.
+    fn small(&self, times: u32, value: &Self::Elem) -> Self::Elem {
+        let mut result = self.zero();
+        for i in (0..u32::BITS - times.leading_zeros()).rev() {
+            result = self.add(&result, &result);
+            if times >> i & 1 == 1 {
+                result = self.add(&result, value);
+            }
+        }
+        result
+    }
+
+    // Only done once per scalar multiplication so it goes through ModNum::mul_inv.
+    fn inv(&self, elem: &Self::Elem) -> Self::Elem {
+        let value = ModNum::new(&self.to_big(elem), self.modulus()).mul_inv();
+        self.elem(value.value())
+    }
+}
+
+// Runs body with name bound to the backend for the field modulus. body is compiled once for each backend.
+macro_rules! with_arithmetic {
+    ($modulus:expr, |$name:ident| $body:expr) => {{
+        let modulus: &num_bigint::BigUint = $modulus;
+        if let Some($name) = $crate::montgomery::Montgomery::<4>::new(modulus) {
+            $body
+        } else if let Some($name) = $crate::montgomery::Montgomery::<6>::new(modulus) {
+            $body
+        } else if let Some($name) = $crate::montgomery::Montgomery::<9>::new(modulus) {
+            $body
+        } else {
+            let $name = $crate::arithmetic::BigArithmetic::new(modulus);
+            $body
+        }
+    }};
+}
+pub(crate) use with_arithmetic;
+
+// Used for fields Montgomery can't handle, even ones and ones over 576 bits.
+#[derive(Debug, Clone)]
+pub(crate) struct BigArithmetic {
+    modulus: BigUint,
+    // The number of 32 bit words needed to hold any number less than the modulus, cswap always works on
+    //  this many.
+    limbs: usize,
+}
+
+impl BigArithmetic {
+    pub(crate) fn new(modulus: &BigUint) -> BigArithmetic {
+        BigArithmetic {
+            modulus: modulus.clone(),
+            limbs: modulus.bits().div_ceil(32) as usize,
+        }
+    }
+}
+
+impl Arithmetic for BigArithmetic {
+    type Elem = BigUint;
+
+    fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+    fn elem(&self, value: &BigUint) -> BigUint {
+        value % &self.modulus
+    }
+    fn to_big(&self, elem: &BigUint) -> BigUint {
+        elem.clone()
+    }
+    fn zero(&self) -> BigUint {
+        big(0)
+    }
+    fn one(&self) -> BigUint {
+        big(1) % &self.modulus
+    }
+    fn add(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        (left + right) % &self.modulus
+    }
+    // Both values are already reduced so adding the modulus first keeps the result from going negative.
+    fn sub(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        (left + &self.modulus - right) % &self.modulus
+    }
+    fn mul(&self, left: &BigUint, right: &BigUint) -> BigUint {
+        (left * right) % &self.modulus
+    }
+
+    // The numbers are xored a word at a time, always limbs words.
+    fn cswap(&self, left: &mut BigUint, right: &mut BigUint, mask: u64) {
+        let mask = mask as u32;
+        let mut l_digits = left.to_u32_digits();
+        let mut r_digits = right.to_u32_digits();
+        l_digits.resize(self.limbs, 0);
+        r_digits.resize(self.limbs, 0);
+        for (l_digit, r_digit) in l_digits.iter_mut().zip(r_digits.iter_mut()) {
+            let difference = mask & (*l_digit ^ *r_digit);
+            *l_digit ^= difference;
+            *r_digit ^= difference;
+        }
+        *left = BigUint::new(l_digits);
+        *right = BigUint::new(r_digits);
+    }
+}
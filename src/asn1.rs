@@ -0,0 +1,224 @@
+use crate::error::{Error, Result};
+use num_bigint::BigUint;
+
+// Just enough of ASN.1 DER, https://www.itu.int/rec/T-REC-X.690, to read and write key files and signatures.
+//  Every DER value is a tag byte, a length, and then that many bytes of content. Only the handful of types
+//   that show up in PKCS#8, SEC 1 ECPrivateKey, SubjectPublicKeyInfo, and ECDSA signatures are supported, and
+//  the reader is strict about DER's rules so there is only ever one valid encoding of a value.
+
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OBJECT_IDENTIFIER: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+
+// Context specific tags, [0], [1], ..., used for optional fields. They are always constructed here since they
+//  wrap another value.
+pub fn context_tag(n: u8) -> u8 {
+    0xA0 | n
+}
+
+// Writes a complete tag, length, and content.
+pub fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        encoded.push(len as u8);
+    } else {
+        // Long form, 0x80 | number of length bytes, then the length big-endian with no leading zeros.
+        let len_bytes: Vec<u8> = len
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|&b| b == 0)
+            .collect();
+        encoded.push(0x80 | len_bytes.len() as u8);
+        encoded.extend(len_bytes);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+// A SEQUENCE is just its already encoded members one after another.
+pub fn sequence(members: &[Vec<u8>]) -> Vec<u8> {
+    encode(SEQUENCE, &members.concat())
+}
+
+// INTEGERs are two's complement, so a leading 0x00 is needed whenever the top bit would otherwise be set.
+pub fn integer(value: &BigUint) -> Vec<u8> {
+    let mut content = value.to_bytes_be();
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    encode(INTEGER, &content)
+}
+
+pub fn octet_string(bytes: &[u8]) -> Vec<u8> {
+    encode(OCTET_STRING, bytes)
+}
+
+// Only whole bytes are ever stored so the leading "unused bits" byte is always 0.
+pub fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = vec![0];
+    content.extend_from_slice(bytes);
+    encode(BIT_STRING, &content)
+}
+
+// The first two arcs are combined into one number, 40 * first + second, and then every arc is written base
+//  128 with the top bit set on every byte but the last.
+pub fn oid(arcs: &[u64]) -> Vec<u8> {
+    let mut content = Vec::new();
+    let mut numbers = vec![arcs[0] * 40 + arcs[1]];
+    numbers.extend_from_slice(&arcs[2..]);
+    for number in numbers {
+        let mut base_128 = vec![(number & 0x7F) as u8];
+        let mut rest = number >> 7;
+        while rest > 0 {
+            base_128.push(0x80 | (rest & 0x7F) as u8);
+            rest >>= 7;
+        }
+        content.extend(base_128.iter().rev());
+    }
+    encode(OBJECT_IDENTIFIER, &content)
+}
+
+// Reads DER values from the front of a slice of bytes.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    // Returns the tag of the next value without reading it, used for optional fields.
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.bytes.first().copied()
+    }
+
+    // Errors if anything is left over, DER values shouldn't have trailing data.
+    pub fn finish(&self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidEncoding("unexpected data after DER value"))
+        }
+    }
+
+    // Reads the next value, checking that it has the expected tag, and returns its content.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8]> {
+        let truncated = Error::InvalidEncoding("DER value is truncated");
+        if self.bytes.len() < 2 {
+            return Err(truncated);
+        }
+        if self.bytes[0] != tag {
+            return Err(Error::InvalidEncoding("unexpected DER tag"));
+        }
+        let first = self.bytes[1] as usize;
+        let (len, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            // 0x80 on its own is the indefinite length form, which DER doesn't allow.
+            let count = first & 0x7F;
+            if count == 0 || count > std::mem::size_of::<usize>() {
+                return Err(Error::InvalidEncoding("unsupported DER length"));
+            }
+            if self.bytes.len() < 2 + count {
+                return Err(truncated);
+            }
+            let len = self.bytes[2..2 + count]
+                .iter()
+                .fold(0usize, |len, &b| (len << 8) | b as usize);
+            // DER requires the shortest form of the length.
+            if len < 0x80 || self.bytes[2] == 0 {
+                return Err(Error::InvalidEncoding("DER length isn't minimally encoded"));
+            }
+            (len, 2 + count)
+        };
+        if self.bytes.len() - header < len {
+            return Err(truncated);
+        }
+        let content = &self.bytes[header..header + len];
+        self.bytes = &self.bytes[header + len..];
+        Ok(content)
+    }
+
+    // Reads a SEQUENCE and returns a Reader over its members.
+    pub fn read_sequence(&mut self) -> Result<Reader<'a>> {
+        Ok(Reader::new(self.read(SEQUENCE)?))
+    }
+
+    // Reads a non-negative INTEGER.
+    pub fn read_integer(&mut self) -> Result<BigUint> {
+        let content = self.read(INTEGER)?;
+        if content.is_empty() {
+            return Err(Error::InvalidEncoding("empty DER integer"));
+        }
+        if content[0] & 0x80 != 0 {
+            return Err(Error::InvalidEncoding("negative DER integer"));
+        }
+        // A leading zero is only allowed when it is needed to keep the number positive.
+        if content.len() > 1 && content[0] == 0 && content[1] & 0x80 == 0 {
+            return Err(Error::InvalidEncoding(
+                "DER integer isn't minimally encoded",
+            ));
+        }
+        Ok(BigUint::from_bytes_be(content))
+    }
+
+    pub fn read_octet_string(&mut self) -> Result<&'a [u8]> {
+        self.read(OCTET_STRING)
+    }
+
+    // Reads a BIT STRING made of whole bytes.
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8]> {
+        let content = self.read(BIT_STRING)?;
+        match content.first() {
+            Some(0) => Ok(&content[1..]),
+            _ => Err(Error::InvalidEncoding(
+                "DER bit string isn't made of whole bytes",
+            )),
+        }
+    }
+
+    pub fn read_oid(&mut self) -> Result<Vec<u64>> {
+        let content = self.read(OBJECT_IDENTIFIER)?;
+        let mut numbers = Vec::new();
+        let mut number: u64 = 0;
+        for (i, &b) in content.iter().enumerate() {
+            // Leading 0x80 bytes would be padding, which isn't allowed.
+            if number == 0 && b == 0x80 {
+                return Err(Error::InvalidEncoding("DER OID isn't minimally encoded"));
+            }
+            if number > u64::MAX >> 7 {
+                return Err(Error::InvalidEncoding("DER OID arc is too large"));
+            }
+            number = (number << 7) | u64::from(b & 0x7F);
+            if b & 0x80 == 0 {
+                numbers.push(number);
+                number = 0;
+            } else if i == content.len() - 1 {
+                return Err(Error::InvalidEncoding("DER OID is truncated"));
+            }
+        }
+        if numbers.is_empty() {
+            return Err(Error::InvalidEncoding("empty DER OID"));
+        }
+        // Undo the combining of the first two arcs.
+        let first = numbers[0];
+        let mut arcs = if first < 40 {
+            vec![0, first]
+        } else if first < 80 {
+            vec![1, first - 40]
+        } else {
+            vec![2, first - 80]
+        };
+        arcs.extend_from_slice(&numbers[1..]);
+        Ok(arcs)
+    }
+}
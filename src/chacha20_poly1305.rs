@@ -0,0 +1,213 @@
+use crate::error::{Error, Result};
+
+// The ChaCha20-Poly1305 AEAD from RFC 8439, https://www.rfc-editor.org/rfc/rfc8439
+//  ChaCha20 is a stream cipher, the plain text is xored with a keystream made from the key and nonce.
+//   Poly1305 is a one time authenticator, it produces a tag that only someone with the key could have made,
+//  so any change to the cipher text or associated data is caught before anything is decrypted.
+// A key and nonce pair must never be used for two different messages.
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+// Encrypts the plain text and returns the cipher text with the 16 byte tag on the end. The associated data
+//  isn't encrypted but is covered by the tag.
+pub fn seal(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    associated_data: &[u8],
+    plain_text: &[u8],
+) -> Vec<u8> {
+    let mut sealed = plain_text.to_vec();
+    chacha20_xor(key, 1, nonce, &mut sealed);
+    let tag = tag(key, nonce, associated_data, &sealed);
+    sealed.extend_from_slice(&tag);
+    sealed
+}
+
+// Checks the tag and decrypts a cipher text made by seal. Returns Error::DecryptionFailed if the tag doesn't
+//  match, which is all that can be said about a wrong key, a wrong nonce, or a tampered message.
+pub fn open(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    associated_data: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>> {
+    if sealed.len() < TAG_LEN {
+        return Err(Error::InvalidEncoding(
+            "cipher text is shorter than its tag",
+        ));
+    }
+    let (cipher_text, received_tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    let expected_tag = tag(key, nonce, associated_data, cipher_text);
+    // Looks at every byte no matter where the first difference is, so the time taken doesn't say how much of
+    //  a forged tag was right.
+    let difference = expected_tag
+        .iter()
+        .zip(received_tag)
+        .fold(0, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        return Err(Error::DecryptionFailed);
+    }
+    let mut plain_text = cipher_text.to_vec();
+    chacha20_xor(key, 1, nonce, &mut plain_text);
+    Ok(plain_text)
+}
+
+// RFC 8439 section 2.8. The Poly1305 key is the first 32 bytes of the block with counter 0, and the message
+//  it authenticates is the associated data and cipher text, each padded to 16 bytes, followed by their
+//   lengths.
+fn tag(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    associated_data: &[u8],
+    cipher_text: &[u8],
+) -> [u8; TAG_LEN] {
+    let poly_key: [u8; 32] = chacha20_block(key, 0, nonce)[..32].try_into().unwrap();
+    let mut mac_data = Vec::with_capacity(associated_data.len() + cipher_text.len() + 48);
+    for part in [associated_data, cipher_text] {
+        mac_data.extend_from_slice(part);
+        mac_data.resize(mac_data.len().next_multiple_of(16), 0);
+    }
+    mac_data.extend_from_slice(&(associated_data.len() as u64).to_le_bytes());
+    mac_data.extend_from_slice(&(cipher_text.len() as u64).to_le_bytes());
+    poly1305(&poly_key, &mac_data)
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+// RFC 8439 section 2.3, one 64 byte block of keystream.
+pub fn chacha20_block(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; NONCE_LEN]) -> [u8; 64] {
+    let mut initial = [0u32; 16];
+    // "expand 32-byte k"
+    initial[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for (i, word) in key.chunks(4).enumerate() {
+        initial[4 + i] = u32::from_le_bytes(word.try_into().unwrap());
+    }
+    initial[12] = counter;
+    for (i, word) in nonce.chunks(4).enumerate() {
+        initial[13 + i] = u32::from_le_bytes(word.try_into().unwrap());
+    }
+
+    let mut state = initial;
+    for _ in 0..10 {
+        // Columns and then diagonals.
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0u8; 64];
+    for (i, (word, start)) in state.iter().zip(initial).enumerate() {
+        block[4 * i..4 * i + 4].copy_from_slice(&word.wrapping_add(start).to_le_bytes());
+    }
+    block
+}
+
+// RFC 8439 section 2.4. Encrypting and decrypting are the same, xor with the keystream starting at the block
+//  numbered counter.
+pub fn chacha20_xor(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; NONCE_LEN], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(64).enumerate() {
+        let keystream = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        for (byte, key_byte) in chunk.iter_mut().zip(keystream) {
+            *byte ^= key_byte;
+        }
+    }
+}
+
+// RFC 8439 section 2.5. The accumulator is kept mod 2^130 - 5 in five 26 bit limbs so every product fits in a
+//  u64, the same layout as poly1305-donna.
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG_LEN] {
+    let le_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+
+    // r has some of its bits cleared, "clamped", as the RFC requires.
+    let r = [
+        le_u32(&key[0..4]) & 0x3ffffff,
+        (le_u32(&key[3..7]) >> 2) & 0x3ffff03,
+        (le_u32(&key[6..10]) >> 4) & 0x3ffc0ff,
+        (le_u32(&key[9..13]) >> 6) & 0x3f03fff,
+        (le_u32(&key[12..16]) >> 8) & 0x00fffff,
+    ];
+    // Multiplying by 2^130 is the same as multiplying by 5 mod 2^130 - 5, so the limbs that would overflow
+    //  are folded back in multiplied by 5.
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u32; 5];
+
+    for chunk in message.chunks(16) {
+        // Each chunk has a 1 appended just past its last byte.
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        h[0] += le_u32(&block[0..4]) & 0x3ffffff;
+        h[1] += (le_u32(&block[3..7]) >> 2) & 0x3ffffff;
+        h[2] += (le_u32(&block[6..10]) >> 4) & 0x3ffffff;
+        h[3] += (le_u32(&block[9..13]) >> 6) & 0x3ffffff;
+        h[4] += (le_u32(&block[12..16]) >> 8) | (u32::from(block[16]) << 24);
+
+        // h = h * r mod 2^130 - 5
+        let m = |a: u32, b: u32| u64::from(a) * u64::from(b);
+        let d = [
+            m(h[0], r[0]) + m(h[1], s[3]) + m(h[2], s[2]) + m(h[3], s[1]) + m(h[4], s[0]),
+            m(h[0], r[1]) + m(h[1], r[0]) + m(h[2], s[3]) + m(h[3], s[2]) + m(h[4], s[1]),
+            m(h[0], r[2]) + m(h[1], r[1]) + m(h[2], r[0]) + m(h[3], s[3]) + m(h[4], s[2]),
+            m(h[0], r[3]) + m(h[1], r[2]) + m(h[2], r[1]) + m(h[3], r[0]) + m(h[4], s[3]),
+            m(h[0], r[4]) + m(h[1], r[3]) + m(h[2], r[2]) + m(h[3], r[1]) + m(h[4], r[0]),
+        ];
+        let mut carry = 0u64;
+        for i in 0..5 {
+            let limb = d[i] + carry;
+            h[i] = (limb & 0x3ffffff) as u32;
+            carry = limb >> 26;
+        }
+        h[0] += (carry * 5) as u32;
+        h[1] += h[0] >> 26;
+        h[0] &= 0x3ffffff;
+    }
+
+    // Fully carry h and then reduce it, h - (2^130 - 5) is used if it doesn't go negative.
+    let mut carry;
+    for _ in 0..2 {
+        carry = 0;
+        for limb in h.iter_mut() {
+            *limb += carry;
+            carry = *limb >> 26;
+            *limb &= 0x3ffffff;
+        }
+        h[0] += carry * 5;
+    }
+    let mut g = [0u32; 5];
+    carry = 5;
+    for i in 0..5 {
+        g[i] = h[i] + carry;
+        carry = g[i] >> 26;
+        g[i] &= 0x3ffffff;
+    }
+    // carry is 1 only when h >= 2^130 - 5. The choice is made with a mask rather than a branch.
+    let mask = 0u32.wrapping_sub(carry);
+    for i in 0..5 {
+        h[i] = (h[i] & !mask) | (g[i] & mask);
+    }
+
+    // tag = (h + s) mod 2^128, where s here is the second half of the key.
+    let h_128 = u128::from(h[0])
+        | (u128::from(h[1]) << 26)
+        | (u128::from(h[2]) << 52)
+        | (u128::from(h[3]) << 78)
+        | (u128::from(h[4]) << 104);
+    let key_s = u128::from_le_bytes(key[16..32].try_into().unwrap());
+    h_128.wrapping_add(key_s).to_le_bytes()
+}
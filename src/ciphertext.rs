@@ -0,0 +1,157 @@
+use crate::clean_up::byte_len;
+use crate::el_gamal::{chunk_size, Curves};
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::EllipticType;
+use crate::error::{Error, Result};
+use crate::pem;
+
+// The serialized cipher text format. All numbers are big-endian.
+//   magic              4 bytes  "EGCT"
+//   version            1 byte   currently 1
+//   curve id           1 byte   see curve_id
+//   chunk count        4 bytes
+//   plain text length  8 bytes
+//  followed by chunk count (c_0, c_1) pairs, each point SEC 1 encoded. Points are written compressed but
+//   uncompressed points are accepted when reading since the first byte of each point says which it is.
+const MAGIC: &[u8; 4] = b"EGCT";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 18;
+const PEM_LABEL: &str = "EL GAMAL CIPHERTEXT";
+
+// The result of encrypting a message. Each chunk of the plain text becomes a pair of points (c_0, c_1). The
+//  length of the original plain text is kept alongside the points so the chunks can be padded back out to
+//   their original size, otherwise any trailing zero bytes in a chunk would be lost when it is turned into
+//  a number.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Ciphertext {
+    chunks: Vec<(EllipticType, EllipticType)>,
+    length: usize,
+}
+
+impl Ciphertext {
+    pub fn new(chunks: Vec<(EllipticType, EllipticType)>, length: usize) -> Ciphertext {
+        Ciphertext { chunks, length }
+    }
+
+    pub fn chunks(&self) -> &Vec<(EllipticType, EllipticType)> {
+        &self.chunks
+    }
+    // The length in bytes of the plain text that was encrypted.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    // Serializes the cipher text in the format described at the top of this file. Returns
+    //  Error::CiphertextTooLarge if the chunk count doesn't fit in 4 bytes or the length in 8, rather than
+    //   writing a header that doesn't match the chunks after it.
+    pub fn to_bytes(&self, curve: &EllipticCurve) -> Result<Vec<u8>> {
+        let (chunk_count, length) =
+            match (u32::try_from(self.chunks.len()), u64::try_from(self.length)) {
+                (Ok(chunk_count), Ok(length)) => (chunk_count, length),
+                _ => return Err(Error::CiphertextTooLarge),
+            };
+        let mut bytes =
+            Vec::with_capacity(HEADER_LEN + self.chunks.len() * 2 * (1 + byte_len(curve.field())));
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(curve_id(curve));
+        bytes.extend_from_slice(&chunk_count.to_be_bytes());
+        bytes.extend_from_slice(&length.to_be_bytes());
+        for (c_0, c_1) in &self.chunks {
+            bytes.extend(c_0.to_sec1_bytes(true));
+            bytes.extend(c_1.to_sec1_bytes(true));
+        }
+        Ok(bytes)
+    }
+
+    // Parses bytes produced by to_bytes. Returns an Error if the bytes are truncated, have anything left over
+    //  at the end, were made for a different curve, or hold points that aren't on the curve.
+    pub fn from_bytes(bytes: &[u8], curve: &EllipticCurve) -> Result<Ciphertext> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::InvalidEncoding(
+                "cipher text is shorter than its header",
+            ));
+        }
+        if &bytes[0..4] != MAGIC {
+            return Err(Error::InvalidEncoding(
+                "cipher text doesn't start with EGCT",
+            ));
+        }
+        if bytes[4] != VERSION {
+            return Err(Error::InvalidEncoding("unsupported cipher text version"));
+        }
+        if bytes[5] != curve_id(curve) {
+            return Err(Error::CurveMismatch);
+        }
+        let chunk_count = u32::from_be_bytes(bytes[6..10].try_into().unwrap()) as usize;
+        let length = u64::from_be_bytes(bytes[10..18].try_into().unwrap());
+        let length: usize = match length.try_into() {
+            Ok(length) => length,
+            Err(_) => return Err(Error::InvalidEncoding("plain text length is too large")),
+        };
+        if chunk_count != length.div_ceil(chunk_size(curve)?) {
+            return Err(Error::ChunkCountMismatch);
+        }
+
+        let field_len = byte_len(curve.field());
+        let mut rest = &bytes[HEADER_LEN..];
+        // Every point takes at least one byte, this stops a bad header from asking for a huge allocation.
+        let mut chunks = Vec::with_capacity(chunk_count.min(rest.len() / 2));
+        let next_point = |rest: &mut &[u8]| -> Result<EllipticType> {
+            let len = match rest.first() {
+                Some(0x00) => 1,
+                Some(0x02) | Some(0x03) => 1 + field_len,
+                Some(0x04) => 1 + 2 * field_len,
+                Some(_) => return Err(Error::InvalidEncoding("unknown SEC 1 point prefix")),
+                None => return Err(Error::InvalidEncoding("cipher text is truncated")),
+            };
+            if rest.len() < len {
+                return Err(Error::InvalidEncoding("cipher text is truncated"));
+            }
+            let point = EllipticType::from_sec1_bytes(&rest[..len], curve)?;
+            *rest = &rest[len..];
+            Ok(point)
+        };
+        for _ in 0..chunk_count {
+            let c_0 = next_point(&mut rest)?;
+            let c_1 = next_point(&mut rest)?;
+            chunks.push((c_0, c_1));
+        }
+        if !rest.is_empty() {
+            return Err(Error::InvalidEncoding(
+                "unexpected bytes after the last chunk",
+            ));
+        }
+        Ok(Ciphertext { chunks, length })
+    }
+
+    // Serializes the cipher text with to_bytes and wraps it in a PEM style block of base64 text.
+    pub fn to_armored(&self, curve: &EllipticCurve) -> Result<String> {
+        Ok(pem::encode(PEM_LABEL, &self.to_bytes(curve)?))
+    }
+
+    // Parses a cipher text produced by to_armored.
+    pub fn from_armored(text: &str, curve: &EllipticCurve) -> Result<Ciphertext> {
+        Self::from_bytes(&pem::decode(PEM_LABEL, text)?, curve)
+    }
+}
+
+// The id written into the header for each curve. Cipher texts for a custom curve all share 0, so reading one
+//  back relies on being given the right curve.
+fn curve_id(curve: &EllipticCurve) -> u8 {
+    match Curves::identify(curve) {
+        Curves::Custom(_) => 0,
+        Curves::TwoFiveSix => 1,
+        Curves::ThreeEightFour => 2,
+        Curves::FiveTwoOne => 3,
+        Curves::Secp256k1 => 4,
+        Curves::Secp224r1 => 5,
+        Curves::Secp192r1 => 6,
+        Curves::BrainpoolP256r1 => 7,
+        Curves::BrainpoolP384r1 => 8,
+        Curves::BrainpoolP512r1 => 9,
+    }
+}
@@ -3,3 +3,18 @@ use num_bigint::BigUint;
 pub fn big(x: u32) -> BigUint {
     BigUint::new(vec![x])
 }
+
+// The number of bytes needed to write out any number less than or equal to value.
+pub fn byte_len(value: &BigUint) -> usize {
+    value.bits().div_ceil(8) as usize
+}
+
+// Writes value as a big-endian number padded with leading zeros out to len bytes. Used anywhere a number needs
+//  to be stored with a fixed width, panics if value doesn't fit.
+pub fn to_fixed_bytes(value: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    assert!(bytes.len() <= len, "value doesn't fit in {} bytes", len);
+    let mut fixed = vec![0; len - bytes.len()];
+    fixed.extend(bytes);
+    fixed
+}
@@ -0,0 +1,343 @@
+use crate::clean_up::big;
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::{EllipticType, Point};
+use crate::error::{Error, Result};
+use crate::field::Field;
+use crate::modular_numbers::ModNum;
+use crate::point_counting::{self, random_point, PointCounting, SCHOOF_BITS};
+use crate::primes::{factor_with, is_probable_prime_with, random_prime};
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+use std::fmt;
+
+// The smallest field ElGamal can encode messages in, see el_gamal::chunk_size.
+const MIN_FIELD: u32 = 65_537;
+
+// How far the MOV check looks, p^k - 1 is checked for k up to this. SEC 1 section 3.1.1.2.1 uses 100.
+pub const MOV_BOUND: u32 = 100;
+
+// The largest cofactor EllipticCurve::generate accepts. SEC 2's curves have cofactors of 1, 2, and 4.
+pub const MAX_GENERATED_COFACTOR: u32 = 4;
+
+// Builds a custom curve after checking it's one that can safely be used, following the curve validation in
+//  SEC 1 section 3.1.1.2.1, https://www.secg.org/sec1-v2.pdf. EllipticCurve::new takes whatever it's given
+//   so a typo in a parameter gives a curve that silently doesn't work, or worse, one that works but isn't
+//  secure.
+//   p - the field, must be a prime greater than 65_537.
+//   a, b - y^2 = x^3 + a*x + b must not be singular, 4a^3 + 27b^2 != 0.
+//   init_point - must be on the curve.
+//   order - must be prime and n * init_point = Infinity. When it isn't given the points on the curve are
+//    counted with count_points, the count factored, and the order of init_point found from it with
+//   point_order. That takes a while for large fields, see PointCounting, and above SCHOOF_BITS the order
+//    has to be given.
+//   cofactor - n * h must be a possible number of points on the curve, within the Hasse bound. It's worked
+//    out from the order when it isn't given and the order is large enough for there to be only one choice,
+//   or is the count divided by the order when the points were counted.
+//  The curve is also checked for the weaknesses listed in Weakness and refused if it has any, unless
+//   allow_weak is set.
+#[derive(Debug, Clone)]
+pub struct EllipticCurveBuilder {
+    field: BigUint,
+    a: Option<BigUint>,
+    b: Option<BigUint>,
+    init_point: Option<(BigUint, BigUint)>,
+    order: Option<BigUint>,
+    cofactor: Option<BigUint>,
+    allow_weak: bool,
+}
+
+// Why the builder refused a curve.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CurveError {
+    // a, b, or init_point wasn't given. Says which.
+    Missing(&'static str),
+    // A parameter is the field or larger. Says which.
+    NotInField(&'static str),
+    // The field is 65_537 or less, too small for ElGamal to encode messages in.
+    FieldTooSmall,
+    FieldNotPrime,
+    // 4a^3 + 27b^2 = 0, the curve has a cusp or a self intersection and isn't an elliptic curve.
+    Singular,
+    InitPointNotOnCurve,
+    OrderNotPrime,
+    // n * init_point isn't Infinity.
+    WrongOrder,
+    // No order was given and the field is over SCHOOF_BITS bits, too large to count the points on.
+    OrderRequired,
+    // n * h isn't within the Hasse bound, |p + 1 - n * h| <= 2 * sqrt(p), so it can't be the number of points.
+    WrongCofactor,
+    // No cofactor was given and more than one fits the Hasse bound, which can happen when n <= 4 * sqrt(p).
+    AmbiguousCofactor,
+    // No order was given and the number of points has more than one prime factor too large to find, see
+    //  primes::factor, so the order of init_point can't be worked out.
+    CountNotFactored,
+    Weak(Weakness),
+}
+
+// Curves with these can have their discrete logarithms found far faster than by the generic attacks, so
+//  keys on them aren't safe.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Weakness {
+    // The curve has exactly p points. Smart's attack solves the discrete logarithm in polynomial time.
+    Anomalous,
+    // n divides p^k - 1 for the given k, up to MOV_BOUND. The MOV and Frey-Ruck attacks move the discrete
+    //  logarithm into the multiplicative group of the field with p^k elements, where it's much easier for
+    //   small k.
+    Mov(u32),
+}
+
+impl EllipticCurveBuilder {
+    pub fn new(field: BigUint) -> EllipticCurveBuilder {
+        EllipticCurveBuilder {
+            field,
+            a: None,
+            b: None,
+            init_point: None,
+            order: None,
+            cofactor: None,
+            allow_weak: false,
+        }
+    }
+
+    pub fn a(mut self, a: BigUint) -> EllipticCurveBuilder {
+        self.a = Some(a);
+        self
+    }
+
+    pub fn b(mut self, b: BigUint) -> EllipticCurveBuilder {
+        self.b = Some(b);
+        self
+    }
+
+    pub fn init_point(mut self, x: BigUint, y: BigUint) -> EllipticCurveBuilder {
+        self.init_point = Some((x, y));
+        self
+    }
+
+    pub fn order(mut self, order: BigUint) -> EllipticCurveBuilder {
+        self.order = Some(order);
+        self
+    }
+
+    pub fn cofactor(mut self, cofactor: BigUint) -> EllipticCurveBuilder {
+        self.cofactor = Some(cofactor);
+        self
+    }
+
+    // Builds curves with a Weakness instead of refusing them. EllipticCurve::weaknesses still lists them.
+    pub fn allow_weak(mut self, allow_weak: bool) -> EllipticCurveBuilder {
+        self.allow_weak = allow_weak;
+        self
+    }
+
+    // Checks the parameters and builds the curve, or returns Error::InvalidCurve saying what was wrong.
+    pub fn build(&self) -> Result<EllipticCurve> {
+        self.build_with(&mut rand::thread_rng())
+    }
+
+    // Same as build with the random choices made while checking primes and counting points picked by rng.
+    pub(crate) fn build_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<EllipticCurve> {
+        let invalid = |e| Err(Error::InvalidCurve(e));
+        let p = &self.field;
+        if p <= &big(MIN_FIELD) {
+            return invalid(CurveError::FieldTooSmall);
+        }
+        if !is_probable_prime_with(p, rng) {
+            return invalid(CurveError::FieldNotPrime);
+        }
+        let field = Field::new(p);
+        let in_field = |value: &Option<BigUint>, name| match value {
+            None => Err(Error::InvalidCurve(CurveError::Missing(name))),
+            Some(value) if value >= p => Err(Error::InvalidCurve(CurveError::NotInField(name))),
+            Some(value) => Ok(ModNum::in_field(value, &field)),
+        };
+        let a = in_field(&self.a, "a")?;
+        let b = in_field(&self.b, "b")?;
+        let (x, y) = match &self.init_point {
+            None => return invalid(CurveError::Missing("init_point")),
+            Some((x, y)) => (
+                in_field(&Some(x.clone()), "x")?,
+                in_field(&Some(y.clone()), "y")?,
+            ),
+        };
+
+        let number = |value: u32| ModNum::in_field(&big(value), &field);
+        if number(4) * &a * &a * &a + number(27) * &b * &b == number(0) {
+            return invalid(CurveError::Singular);
+        }
+        let init_point = match Point::try_new(x, y, &a, &b) {
+            Ok(point) => EllipticType::Point(point),
+            Err(_) => return invalid(CurveError::InitPointNotOnCurve),
+        };
+
+        // n * init_point is checked on the curve without an order, once it has one pow reduces exponents by it.
+        let curve = EllipticCurve::try_new(a.clone(), b.clone(), init_point.clone())?;
+        let (order, count) = match &self.order {
+            Some(order) => (order.clone(), None),
+            None if p.bits() > SCHOOF_BITS => return invalid(CurveError::OrderRequired),
+            None => {
+                let count = point_counting::count_points(&curve, PointCounting::for_field(p), rng)?;
+                let factors = match factor_with(&count, rng) {
+                    Some(factors) => factors,
+                    None => return invalid(CurveError::CountNotFactored),
+                };
+                (curve.try_point_order(&init_point, &factors)?, Some(count))
+            }
+        };
+        if !is_probable_prime_with(&order, rng) {
+            return invalid(CurveError::OrderNotPrime);
+        }
+        if let EllipticType::Point(_) = init_point.pow(&order, &curve) {
+            return invalid(CurveError::WrongOrder);
+        }
+        let cofactor = match count {
+            Some(count) => {
+                let cofactor = count / &order;
+                if self
+                    .cofactor
+                    .as_ref()
+                    .is_some_and(|given| given != &cofactor)
+                {
+                    return invalid(CurveError::WrongCofactor);
+                }
+                cofactor
+            }
+            None => cofactor(p, &order, self.cofactor.as_ref())?,
+        };
+        let curve = EllipticCurve::try_new_with_order(a, b, init_point, order, cofactor)?;
+        if !self.allow_weak {
+            if let Some(weakness) = curve.weaknesses().first() {
+                return invalid(CurveError::Weak(*weakness));
+            }
+        }
+        Ok(curve)
+    }
+}
+
+// Searches for a curve over a random prime field of the given size whose number of points is a prime, the
+//  order, times a cofactor up to MAX_GENERATED_COFACTOR. Random a and b are tried, counting the points on
+//   each with count_points, until one fits. The init_point is a random point times the cofactor, and the
+//  curve goes through the builder so it's checked like any other, a curve with a Weakness is passed over.
+// Every random choice is made with rng so a seeded one always gives the same curve.
+pub(crate) fn generate<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> Result<EllipticCurve> {
+    if bits <= big(MIN_FIELD).bits() {
+        return Err(Error::InvalidCurve(CurveError::FieldTooSmall));
+    }
+    if bits > SCHOOF_BITS {
+        return Err(Error::FieldTooLargeToCount);
+    }
+    let p = random_prime(bits, rng);
+    let field = Field::new(&p);
+    let number = |value: u32| ModNum::in_field(&big(value), &field);
+    loop {
+        let a = ModNum::in_field(&rng.gen_biguint_below(&p), &field);
+        let b = ModNum::in_field(&rng.gen_biguint_below(&p), &field);
+        if number(4) * &a * &a * &a + number(27) * &b * &b == number(0) {
+            continue;
+        }
+        let point = random_point(&a, &b, rng);
+        let curve = EllipticCurve::new(a.clone(), b.clone(), point);
+        let count = point_counting::count_points(&curve, PointCounting::for_field(&p), rng)?;
+        let found = (1..=MAX_GENERATED_COFACTOR).map(big).find_map(|cofactor| {
+            let order = &count / &cofactor;
+            (&order * &cofactor == count && is_probable_prime_with(&order, rng))
+                .then_some((order, cofactor))
+        });
+        let (order, cofactor) = match found {
+            Some(found) => found,
+            None => continue,
+        };
+        // Every point times the cofactor is in the subgroup of prime order, so it generates it unless it's
+        //  Infinity.
+        let init_point = loop {
+            if let EllipticType::Point(point) = random_point(&a, &b, rng).pow(&cofactor, &curve) {
+                break point;
+            }
+        };
+        let built = EllipticCurveBuilder::new(p.clone())
+            .a(a.value().clone())
+            .b(b.value().clone())
+            .init_point(
+                init_point.x().value().clone(),
+                init_point.y().value().clone(),
+            )
+            .order(order)
+            .cofactor(cofactor)
+            .build_with(rng);
+        match built {
+            Err(Error::InvalidCurve(CurveError::Weak(_))) => continue,
+            built => return built,
+        }
+    }
+}
+
+// The range of possible numbers of points on a curve over the field p, [p + 1 - 2 * sqrt(p), p + 1 +
+//  2 * sqrt(p)], from Hasse's theorem.
+pub(crate) fn hasse_bounds(p: &BigUint) -> (BigUint, BigUint) {
+    let width = (p * 4u32).sqrt();
+    (p + 1u32 - &width, p + 1u32 + width)
+}
+
+// Checks the given cofactor, or finds the only one that fits the Hasse bound.
+fn cofactor(p: &BigUint, order: &BigUint, given: Option<&BigUint>) -> Result<BigUint> {
+    let (low, high) = hasse_bounds(p);
+    if let Some(cofactor) = given {
+        let points = order * cofactor;
+        if points < low || points > high {
+            return Err(Error::InvalidCurve(CurveError::WrongCofactor));
+        }
+        return Ok(cofactor.clone());
+    }
+    let smallest = (&low + order - 1u32) / order;
+    let largest = &high / order;
+    if smallest > largest {
+        Err(Error::InvalidCurve(CurveError::WrongOrder))
+    } else if smallest < largest {
+        Err(Error::InvalidCurve(CurveError::AmbiguousCofactor))
+    } else {
+        Ok(smallest)
+    }
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveError::Missing(name) => write!(f, "{} wasn't given", name),
+            CurveError::NotInField(name) => write!(f, "{} isn't less than the field", name),
+            CurveError::FieldTooSmall => write!(f, "The field must be greater than 65537"),
+            CurveError::FieldNotPrime => write!(f, "The field isn't prime"),
+            CurveError::Singular => write!(f, "4a^3 + 27b^2 = 0, the curve is singular"),
+            CurveError::InitPointNotOnCurve => write!(f, "The init_point isn't on the curve"),
+            CurveError::OrderNotPrime => write!(f, "The order isn't prime"),
+            CurveError::WrongOrder => write!(f, "The order isn't the order of the init_point"),
+            CurveError::OrderRequired => write!(
+                f,
+                "The order wasn't given and the field is too large to count the points on"
+            ),
+            CurveError::WrongCofactor => write!(
+                f,
+                "The order times the cofactor can't be the number of points"
+            ),
+            CurveError::AmbiguousCofactor => {
+                write!(f, "The order is too small to work out the cofactor from")
+            }
+            CurveError::CountNotFactored => write!(
+                f,
+                "The number of points couldn't be factored to find the order"
+            ),
+            CurveError::Weak(Weakness::Anomalous) => {
+                write!(
+                    f,
+                    "The curve has as many points as the field, it's anomalous"
+                )
+            }
+            CurveError::Weak(Weakness::Mov(k)) => {
+                write!(
+                    f,
+                    "The order divides p^{} - 1, the curve is open to the MOV attack",
+                    k
+                )
+            }
+        }
+    }
+}
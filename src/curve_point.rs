@@ -0,0 +1,175 @@
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::{EllipticType, Inf};
+use crate::error::Result;
+use num_bigint::BigUint;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// An EllipticType tied to the curve it's on so points can be combined with operators, p + q, -p, and p * k,
+//  instead of group_op, group_inv, and pow which each need the curve passed in.
+// Multiplying by a scalar uses pow, which takes a different amount of time depending on the scalar, so use
+//  pow_secret or init_point_pow_secret on the point itself for private keys and nonces.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CurvePoint<'c> {
+    point: EllipticType,
+    curve: &'c EllipticCurve,
+}
+
+impl<'c> CurvePoint<'c> {
+    // Ties point to curve, panics if it isn't on the curve, see try_new.
+    pub fn new(point: EllipticType, curve: &'c EllipticCurve) -> CurvePoint<'c> {
+        Self::try_new(point, curve).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as new but returns an Error if the point fails EllipticCurve::validate_point.
+    pub fn try_new(point: EllipticType, curve: &'c EllipticCurve) -> Result<CurvePoint<'c>> {
+        curve.validate_point(&point)?;
+        Ok(CurvePoint { point, curve })
+    }
+
+    // The curve's init_point.
+    pub fn init_point(curve: &'c EllipticCurve) -> CurvePoint<'c> {
+        CurvePoint {
+            point: curve.init_point().clone(),
+            curve,
+        }
+    }
+
+    // Infinity on curve, the identity for +.
+    pub fn infinity(curve: &'c EllipticCurve) -> CurvePoint<'c> {
+        CurvePoint {
+            point: EllipticType::Infinity(Inf::in_field(curve.shared_field())),
+            curve,
+        }
+    }
+
+    pub fn point(&self) -> &EllipticType {
+        &self.point
+    }
+    pub fn curve(&self) -> &'c EllipticCurve {
+        self.curve
+    }
+    pub fn into_point(self) -> EllipticType {
+        self.point
+    }
+
+    // The result of an operation, which is on the same curve as self.
+    fn with_point(&self, point: EllipticType) -> CurvePoint<'c> {
+        CurvePoint {
+            point,
+            curve: self.curve,
+        }
+    }
+
+    fn add(&self, other: &Self) -> CurvePoint<'c> {
+        assert!(
+            std::ptr::eq(self.curve, other.curve) || self.curve == other.curve,
+            "Can't add points on different curves"
+        );
+        self.with_point(self.point.group_op(&other.point, self.curve))
+    }
+
+    fn sub(&self, other: &Self) -> CurvePoint<'c> {
+        self.add(&other.neg())
+    }
+
+    fn neg(&self) -> CurvePoint<'c> {
+        self.with_point(self.point.group_inv())
+    }
+
+    fn mul(&self, exp: &BigUint) -> CurvePoint<'c> {
+        self.with_point(self.point.pow(exp, self.curve))
+    }
+}
+
+// Every combination of owned and borrowed points, like the ModNum operators in modular_numbers.rs.
+macro_rules! curve_point_op {
+    ($op:ident, $method:ident, $assign:ident, $assign_method:ident) => {
+        impl<'c> $op<&CurvePoint<'c>> for &CurvePoint<'c> {
+            type Output = CurvePoint<'c>;
+            fn $method(self, other: &CurvePoint<'c>) -> CurvePoint<'c> {
+                CurvePoint::$method(self, other)
+            }
+        }
+        impl<'c> $op<CurvePoint<'c>> for &CurvePoint<'c> {
+            type Output = CurvePoint<'c>;
+            fn $method(self, other: CurvePoint<'c>) -> CurvePoint<'c> {
+                CurvePoint::$method(self, &other)
+            }
+        }
+        impl<'c> $op<&CurvePoint<'c>> for CurvePoint<'c> {
+            type Output = CurvePoint<'c>;
+            fn $method(self, other: &CurvePoint<'c>) -> CurvePoint<'c> {
+                CurvePoint::$method(&self, other)
+            }
+        }
+        impl<'c> $op<CurvePoint<'c>> for CurvePoint<'c> {
+            type Output = CurvePoint<'c>;
+            fn $method(self, other: CurvePoint<'c>) -> CurvePoint<'c> {
+                CurvePoint::$method(&self, &other)
+            }
+        }
+        impl<'c> $assign<&CurvePoint<'c>> for CurvePoint<'c> {
+            fn $assign_method(&mut self, other: &CurvePoint<'c>) {
+                *self = CurvePoint::$method(self, other);
+            }
+        }
+        impl<'c> $assign<CurvePoint<'c>> for CurvePoint<'c> {
+            fn $assign_method(&mut self, other: CurvePoint<'c>) {
+                *self = CurvePoint::$method(self, &other);
+            }
+        }
+    };
+}
+
+curve_point_op!(Add, add, AddAssign, add_assign);
+curve_point_op!(Sub, sub, SubAssign, sub_assign);
+
+impl<'c> Neg for &CurvePoint<'c> {
+    type Output = CurvePoint<'c>;
+    fn neg(self) -> CurvePoint<'c> {
+        CurvePoint::neg(self)
+    }
+}
+impl<'c> Neg for CurvePoint<'c> {
+    type Output = CurvePoint<'c>;
+    fn neg(self) -> CurvePoint<'c> {
+        CurvePoint::neg(&self)
+    }
+}
+
+impl<'c> Mul<&BigUint> for &CurvePoint<'c> {
+    type Output = CurvePoint<'c>;
+    fn mul(self, exp: &BigUint) -> CurvePoint<'c> {
+        CurvePoint::mul(self, exp)
+    }
+}
+impl<'c> Mul<&BigUint> for CurvePoint<'c> {
+    type Output = CurvePoint<'c>;
+    fn mul(self, exp: &BigUint) -> CurvePoint<'c> {
+        CurvePoint::mul(&self, exp)
+    }
+}
+impl<'c> Mul<&CurvePoint<'c>> for &BigUint {
+    type Output = CurvePoint<'c>;
+    fn mul(self, point: &CurvePoint<'c>) -> CurvePoint<'c> {
+        point.mul(self)
+    }
+}
+impl<'c> Mul<CurvePoint<'c>> for &BigUint {
+    type Output = CurvePoint<'c>;
+    fn mul(self, point: CurvePoint<'c>) -> CurvePoint<'c> {
+        point.mul(self)
+    }
+}
+impl MulAssign<&BigUint> for CurvePoint<'_> {
+    fn mul_assign(&mut self, exp: &BigUint) {
+        *self = CurvePoint::mul(self, exp);
+    }
+}
+
+impl fmt::Display for CurvePoint<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.point)
+    }
+}
@@ -0,0 +1,33 @@
+use crate::clean_up::{byte_len, to_fixed_bytes};
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::EllipticType;
+use crate::error::{Error, Result};
+use crate::keys::{PrivateKey, PublicKey};
+use num_bigint::BigUint;
+
+// Elliptic Curve Diffie-Hellman as described in SEC 1 section 3.3.1, https://www.secg.org/sec1-v2.pdf
+// Both sides raise the other's public key to their own private key. Since
+//  (init_point^a)^b = (init_point^b)^a they end up with the same point without ever sending anything secret.
+//   Only the x coordinate of that point is used as the shared secret.
+// The shared secret shouldn't be used as a key directly, it isn't uniformly random. Run it through a key
+//  derivation function first.
+
+// Works out the secret shared with the owner of their_public. The public key goes through the same checks as
+//  PublicKey::try_new, on the curve, not Infinity, and in the group generated by init_point, so a bad point
+//   can't be used to learn anything about my_private. Returns x as a fixed width big-endian number the same
+//  width as the field.
+pub fn shared_secret(
+    my_private: &BigUint,
+    their_public: &EllipticType,
+    curve: &EllipticCurve,
+) -> Result<Vec<u8>> {
+    let my_private = PrivateKey::try_new(my_private.clone(), curve)?;
+    let their_public = PublicKey::try_new(their_public.clone(), curve)?;
+    match their_public.point().pow_secret(my_private.scalar(), curve) {
+        // Only possible when the order of the curve isn't known and their_public couldn't be fully checked.
+        EllipticType::Infinity(_) => Err(Error::InvalidSharedSecret),
+        EllipticType::Point(point) => {
+            Ok(to_fixed_bytes(point.x().value(), byte_len(curve.field())))
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::asn1::{self, Reader};
+use crate::clean_up::{big, byte_len, to_fixed_bytes};
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::EllipticType;
+use crate::error::{Error, Result};
+use crate::keys::{PrivateKey, PublicKey};
+use crate::modular_numbers::ModNum;
+use crate::rfc6979::{bits2int, NonceGenerator};
+use crate::sha2::Hash;
+use num_bigint::{BigUint, RandBigInt};
+
+// The Elliptic Curve Digital Signature Algorithm as described in FIPS 186-4 section 6,
+//  https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+// Messages are hashed by the caller, sign and verify take the hash. Signing needs the order, n, of the
+//  curve's init_point since all of the signature math is done mod n.
+
+// A signature is the pair (r, s), both in [1, n).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Signature {
+    r: BigUint,
+    s: BigUint,
+}
+
+impl Signature {
+    // Creates a signature after checking r and s are in [1, n).
+    pub fn try_new(r: BigUint, s: BigUint, curve: &EllipticCurve) -> Result<Signature> {
+        let n = order(curve)?;
+        if r == big(0) || s == big(0) || &r >= n || &s >= n {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(Signature { r, s })
+    }
+
+    pub fn r(&self) -> &BigUint {
+        &self.r
+    }
+    pub fn s(&self) -> &BigUint {
+        &self.s
+    }
+
+    // Ecdsa-Sig-Value ::= SEQUENCE { r INTEGER, s INTEGER }, the format used by X.509 and TLS.
+    pub fn to_der(&self) -> Vec<u8> {
+        asn1::sequence(&[asn1::integer(&self.r), asn1::integer(&self.s)])
+    }
+
+    pub fn from_der(der: &[u8], curve: &EllipticCurve) -> Result<Signature> {
+        let mut reader = Reader::new(der);
+        let mut value = reader.read_sequence()?;
+        reader.finish()?;
+        let r = value.read_integer()?;
+        let s = value.read_integer()?;
+        value.finish()?;
+        Self::try_new(r, s, curve)
+    }
+
+    // r || s, each a fixed width big-endian number the same width as n. The format used by JWS and most
+    //  hardware.
+    pub fn to_bytes(&self, curve: &EllipticCurve) -> Vec<u8> {
+        let len = byte_len(curve.scalar_bound());
+        let mut bytes = to_fixed_bytes(&self.r, len);
+        bytes.extend(to_fixed_bytes(&self.s, len));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8], curve: &EllipticCurve) -> Result<Signature> {
+        let len = byte_len(order(curve)?);
+        if bytes.len() != 2 * len {
+            return Err(Error::InvalidEncoding(
+                "signature has the wrong number of bytes for the curve",
+            ));
+        }
+        Self::try_new(
+            BigUint::from_bytes_be(&bytes[..len]),
+            BigUint::from_bytes_be(&bytes[len..]),
+            curve,
+        )
+    }
+}
+
+// Signs the hash of a message with a random per signature nonce, k.
+pub fn sign(private_key: &BigUint, curve: &EllipticCurve, msg_hash: &[u8]) -> Result<Signature> {
+    let n = order(curve)?;
+    let mut rng = rand::thread_rng();
+    loop {
+        let k = rng.gen_biguint_range(&big(1), n);
+        // r or s being 0 is astronomically unlikely, FIPS 186-4 says to just try again with a new k.
+        match sign_with_nonce(private_key, curve, msg_hash, &k) {
+            Err(Error::InvalidSignature) => continue,
+            result => return result,
+        }
+    }
+}
+
+// Signs the hash of a message with a nonce generated from the private key and the hash, following RFC 6979.
+//  The same key and hash always give the same signature, and nothing depends on the RNG. hash should be the
+//   function msg_hash was made with.
+pub fn sign_deterministic(
+    private_key: &BigUint,
+    curve: &EllipticCurve,
+    msg_hash: &[u8],
+    hash: Hash,
+) -> Result<Signature> {
+    let n = order(curve)?;
+    let mut nonces = NonceGenerator::new(hash, private_key, n, msg_hash);
+    loop {
+        match sign_with_nonce(private_key, curve, msg_hash, &nonces.next()) {
+            Err(Error::InvalidSignature) => continue,
+            result => return result,
+        }
+    }
+}
+
+// Signs the hash of a message using the given nonce, k. This only exists to check known answer test vectors.
+//  k must be secret, uniformly random, and never reused, anyone who sees two signatures with the same k or
+//   knows k for one signature can work out the private key.
+pub fn sign_with_nonce(
+    private_key: &BigUint,
+    curve: &EllipticCurve,
+    msg_hash: &[u8],
+    k: &BigUint,
+) -> Result<Signature> {
+    let n = order(curve)?;
+    let d = PrivateKey::try_new(private_key.clone(), curve)?;
+    if k == &big(0) || k >= n {
+        return Err(Error::InvalidNonce);
+    }
+    let e = ModNum::try_new(&bits2int(msg_hash, n), n)?;
+    let k = ModNum::try_new(k, n)?;
+
+    // R = init_point^k, r = R.x mod n
+    let r = match curve.init_point_pow_secret(k.value()) {
+        EllipticType::Infinity(_) => return Err(Error::InvalidSignature),
+        EllipticType::Point(point) => ModNum::try_new(point.x().value(), n)?,
+    };
+    // s = k^-1 * (e + r * d) mod n
+    let s = k
+        .mul_inv()
+        .mul(&e.add(&r.mul(&ModNum::try_new(d.scalar(), n)?)));
+    Signature::try_new(r.value().clone(), s.value().clone(), curve)
+}
+
+// Checks the signature against the hash of a message and the signer's public key. Returns false for anything
+//  that isn't a valid signature, including an invalid public key.
+pub fn verify(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    msg_hash: &[u8],
+    signature: &Signature,
+) -> bool {
+    let n = match curve.order() {
+        Some(n) => n,
+        None => return false,
+    };
+    let public_key = match PublicKey::try_new(public_key.clone(), curve) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    // Signatures built with try_new are always in range but the fields could have come from anywhere.
+    if signature.r == big(0) || signature.s == big(0) || &signature.r >= n || &signature.s >= n {
+        return false;
+    }
+    let e = ModNum::new(&bits2int(msg_hash, n), n);
+    let w = ModNum::new(&signature.s, n).mul_inv();
+    let u_1 = e.mul(&w);
+    let u_2 = ModNum::new(&signature.r, n).mul(&w);
+
+    // X = init_point^u_1 * public_key^u_2
+    let x = curve
+        .init_point_pow(u_1.value())
+        .group_op(&public_key.point().pow(u_2.value(), curve), curve);
+    match x {
+        EllipticType::Infinity(_) => false,
+        EllipticType::Point(point) => &(point.x().value() % n) == signature.r(),
+    }
+}
+
+fn order(curve: &EllipticCurve) -> Result<&BigUint> {
+    curve.order().ok_or(Error::UnknownOrder)
+}
@@ -0,0 +1,92 @@
+use crate::chacha20_poly1305::{self, KEY_LEN, NONCE_LEN, TAG_LEN};
+use crate::clean_up::{big, byte_len};
+use crate::ecdh::shared_secret;
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::EllipticType;
+use crate::error::{Error, Result};
+use crate::sha2::Hash;
+use num_bigint::{BigUint, RandBigInt};
+
+// The Elliptic Curve Integrated Encryption Scheme, SEC 1 section 5.1, https://www.secg.org/sec1-v2.pdf
+//  Rather than turning every chunk of the message into a pair of points the way el_gamal::encrypt does, one
+//   ephemeral key pair is made per message. The ECDH secret between it and the recipient's public key is run
+//  through the ANSI X9.63 KDF to get a symmetric key, and the message is encrypted with ChaCha20-Poly1305.
+//   The cost of the elliptic curve math and the size of the overhead don't depend on the length of the
+//  message.
+// The output is
+//   R           the ephemeral public key, SEC 1 compressed
+//   cipher text the same length as the plain text
+//   tag         16 bytes
+// R is fed into the KDF as SharedInfo1 so the symmetric key is tied to it. Since every message gets a new
+//  ephemeral key, and so a new symmetric key, the AEAD nonce can be fixed at zero.
+
+const NONCE: [u8; NONCE_LEN] = [0; NONCE_LEN];
+
+// The number of bytes encrypt adds to the plain text for the given curve.
+pub fn overhead(curve: &EllipticCurve) -> usize {
+    1 + byte_len(curve.field()) + TAG_LEN
+}
+
+// Encrypts the plain text for the owner of public_key. Returns an Error if the public key isn't a valid key
+//  for the curve.
+pub fn encrypt(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &[u8],
+) -> Result<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    let ephemeral_private = rng.gen_biguint_range(&big(1), curve.scalar_bound());
+    let ephemeral_public = curve
+        .init_point_pow_secret(&ephemeral_private)
+        .to_sec1_bytes(true);
+    let secret = shared_secret(&ephemeral_private, public_key, curve)?;
+    let key = derive_key(&secret, &ephemeral_public);
+
+    let mut cipher_text = ephemeral_public;
+    cipher_text.extend(chacha20_poly1305::seal(&key, &NONCE, &[], plain_text));
+    Ok(cipher_text)
+}
+
+// Decrypts a cipher text made by encrypt. Returns Error::DecryptionFailed if the cipher text was changed or
+//  wasn't encrypted for this private key.
+pub fn decrypt(
+    private_key: &BigUint,
+    curve: &EllipticCurve,
+    cipher_text: &[u8],
+) -> Result<Vec<u8>> {
+    let point_len = 1 + byte_len(curve.field());
+    if cipher_text.len() < overhead(curve) {
+        return Err(Error::InvalidEncoding(
+            "cipher text is shorter than the ECIES overhead",
+        ));
+    }
+    let (ephemeral_public, sealed) = cipher_text.split_at(point_len);
+    // shared_secret checks the point is a valid public key, so an attacker can't pick one that leaks bits
+    //  of the private key.
+    let point = EllipticType::from_sec1_bytes(ephemeral_public, curve)?;
+    let secret = shared_secret(private_key, &point, curve)?;
+    let key = derive_key(&secret, ephemeral_public);
+    chacha20_poly1305::open(&key, &NONCE, &[], sealed)
+}
+
+fn derive_key(secret: &[u8], shared_info: &[u8]) -> [u8; KEY_LEN] {
+    x963_kdf(Hash::Sha256, secret, shared_info, KEY_LEN)
+        .try_into()
+        .unwrap()
+}
+
+// The ANSI X9.63 key derivation function, SEC 1 section 3.6.1. Hash(Z || counter || SharedInfo) with a 32 bit
+//  big-endian counter starting at 1, repeated until there are len bytes.
+pub fn x963_kdf(hash: Hash, secret: &[u8], shared_info: &[u8], len: usize) -> Vec<u8> {
+    let mut key = Vec::with_capacity(len.next_multiple_of(hash.output_len()));
+    let mut counter: u32 = 1;
+    while key.len() < len {
+        let mut input = secret.to_vec();
+        input.extend_from_slice(&counter.to_be_bytes());
+        input.extend_from_slice(shared_info);
+        key.extend(hash.digest(&input));
+        counter += 1;
+    }
+    key.truncate(len);
+    key
+}
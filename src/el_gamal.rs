@@ -1,167 +1,157 @@
+pub use crate::ciphertext::Ciphertext;
 use crate::clean_up::big;
 use crate::elliptic_curve::EllipticCurve;
 use crate::elliptic_point::{EllipticType, Point};
+use crate::error::{Error, Result};
+use crate::keys::{PrivateKey, PublicKey};
 use crate::modular_numbers::ModNum;
+use crate::named_curves::{NamedCurve, NAMED_CURVES};
+use crate::rfc6979::NonceGenerator;
+use crate::sha2::Hash;
 use num_bigint::{BigUint, RandBigInt};
+use std::mem;
 
 // This value is used to determine was sized chunks to use for message encoding.
 const W: u32 = 2_u32.pow(8);
 
-// These are the possible encodings to use.
-// These encoding were copied from http://www.secg.org/sec2-v2.pdf While is paper is 12 years old there
-//  is not yet a version 3 that I could find.
-// They are referred to as secp256r1, secp384r1, and secp521r1 respectibely. Users can also specify their own
-// curve. However the field the user selects must be greater than 65_537, 256^2 + 1. This is due to specific
-//  implementation choices.
+// The curves to choose from. Every variant but Custom is one of the named curves in named_curves.rs, where
+//  their parameters are copied from SEC 2, http://www.secg.org/sec2-v2.pdf, and RFC 5639 for the Brainpool
+//   curves. TwoFiveSix, ThreeEightFour, and FiveTwoOne are secp256r1, secp384r1, and secp521r1.
+// Users can also specify their own curve, build it with EllipticCurveBuilder, in curve_builder.rs, to have its
+//  parameters checked and its order found. The builder only takes fields over 65_537, 256^2 + 1, so there's
+//   room to encode messages in them, see chunk_size.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
 pub enum Curves {
     TwoFiveSix,
     ThreeEightFour,
     FiveTwoOne,
+    Secp256k1,
+    Secp224r1,
+    Secp192r1,
+    BrainpoolP256r1,
+    BrainpoolP384r1,
+    BrainpoolP512r1,
     Custom(EllipticCurve),
 }
 
+impl Curves {
+    // Builds the EllipticCurve described by self.
+    // panics if the curve can't be built, see try_into_curve.
+    pub fn into_curve(self) -> EllipticCurve {
+        self.try_into_curve().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as into_curve but returns an Error instead of panicking. Named curves are only built once, every
+    //  call after the first gets a copy of the saved curve.
+    pub fn try_into_curve(self) -> Result<EllipticCurve> {
+        match self {
+            Curves::Custom(curve) => Ok(curve),
+            named => named.named().unwrap().curve().clone(),
+        }
+    }
+
+    // Every named curve, in the order of the registry in named_curves.rs.
+    pub fn all() -> impl Iterator<Item = Curves> {
+        NAMED_CURVES.iter().map(|named| named.curve.clone())
+    }
+
+    // The named curve called name, like "secp256k1" or "P-256". Case doesn't matter.
+    pub fn by_name(name: &str) -> Option<Curves> {
+        NAMED_CURVES
+            .iter()
+            .find(|named| named.is_called(name))
+            .map(|named| named.curve.clone())
+    }
+
+    // The named curve with the given object identifier, as written into key files.
+    pub fn by_oid(oid: &[u64]) -> Option<Curves> {
+        NAMED_CURVES
+            .iter()
+            .find(|named| named.oid == oid)
+            .map(|named| named.curve.clone())
+    }
+
+    // The standard name of the curve, None for custom curves.
+    pub fn name(&self) -> Option<&'static str> {
+        self.named().map(|named| named.name)
+    }
+
+    // The object identifier of the curve, None for custom curves.
+    pub fn oid(&self) -> Option<&'static [u64]> {
+        self.named().map(|named| named.oid)
+    }
+
+    // Works out which of the named curves the given curve is. Returns Curves::Custom if it isn't one of
+    //  them. Only named curves with the same field are built to be compared.
+    pub fn identify(curve: &EllipticCurve) -> Curves {
+        for named in &NAMED_CURVES {
+            if &named.field() == curve.field() {
+                if let Ok(named_curve) = named.curve() {
+                    if named_curve == curve {
+                        return named.curve.clone();
+                    }
+                }
+            }
+        }
+        Curves::Custom(curve.clone())
+    }
+
+    fn named(&self) -> Option<&'static NamedCurve> {
+        NAMED_CURVES
+            .iter()
+            .find(|named| mem::discriminant(&named.curve) == mem::discriminant(self))
+    }
+}
+
+// Holds a curve and a key pair on it. An ElGamal built with from_public_key doesn't have a private key and
+//  can only be used to encrypt.
 #[derive(Debug)]
 pub struct ElGamal {
     curve: EllipticCurve,
-    public_key: EllipticType,
-    private_key: BigUint,
+    public_key: PublicKey,
+    private_key: Option<PrivateKey>,
 }
 
 impl ElGamal {
-    // Invokes the correct constructor for the ElGamal struct.
+    // Builds the curve and generates a random key pair on it.
+    // panics if the curve can't be built, see try_new.
     pub fn new(curve: Curves) -> ElGamal {
-        match curve {
-            Curves::TwoFiveSix => return Self::new_256(),
-            Curves::ThreeEightFour => return Self::new_384(),
-            Curves::FiveTwoOne => return Self::new_521(),
-            Curves::Custom(curve) => return Self::new_custom(curve),
-        }
+        Self::try_new(curve).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    fn new_256() -> ElGamal {
-        let field = BigUint::parse_bytes(
-            b"FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF",
-            16,
-        )
-        .unwrap();
-        let a = BigUint::parse_bytes(
-            b"FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFC",
-            16,
-        )
-        .unwrap();
-        let a = ModNum::new(&a, &field);
-        let b = BigUint::parse_bytes(
-            b"5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B",
-            16,
-        )
-        .unwrap();
-        let b = ModNum::new(&b, &field);
-        let init_x = BigUint::parse_bytes(
-            b"036B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296",
-            16,
-        )
-        .unwrap();
-        let init_x = ModNum::new(&init_x, &field);
-        let init_y = calc_y(&init_x, &a, &b);
-        let init_point = EllipticType::Point(Point::new(init_x, init_y, &a, &b));
-        let curve = EllipticCurve::new(a, b, init_point);
-        let mut rng = rand::thread_rng();
-        let private_key = rng.gen_biguint_range(&big(0), &field);
-        let public_key = curve.init_point().pow(&private_key, &curve);
-
-        ElGamal {
-            curve,
-            public_key,
-            private_key,
-        }
+    // Same as new but returns an Error instead of panicking.
+    pub fn try_new(curve: Curves) -> Result<ElGamal> {
+        let curve = curve.try_into_curve()?;
+        let private_key = PrivateKey::try_new(random_scalar(&curve)?, &curve)?;
+        Ok(Self::from_parts(curve, private_key))
     }
 
-    fn new_384() -> ElGamal {
-        let field = BigUint::parse_bytes(
-            b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFF0000000000000000FFFFFFFF",
-            16,
-        )
-        .unwrap();
-        let a = BigUint::parse_bytes(
-            b"FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFF0000000000000000FFFFFFFC",
-            16,
-        )
-        .unwrap();
-        let a = ModNum::new(&a, &field);
-        let b = BigUint::parse_bytes(
-            b"B3312FA7E23EE7E4988E056BE3F82D19181D9C6EFE8141120314088F5013875AC656398D8A2ED19D2A85C8EDD3EC2AEF",
-            16,
-        )
-        .unwrap();
-        let b = ModNum::new(&b, &field);
-        let init_x = BigUint::parse_bytes(
-            b"03AA87CA22BE8B05378EB1C71EF320AD746E1D3B628BA79B9859F741E082542A385502F25DBF55296C3A545E3872760AB7",
-            16,
-        )
-        .unwrap();
-        let init_x = ModNum::new(&init_x, &field);
-        let init_y = calc_y(&init_x, &a, &b);
-        let init_point = EllipticType::Point(Point::new(init_x, init_y, &a, &b));
-        let curve = EllipticCurve::new(a, b, init_point);
-        let mut rng = rand::thread_rng();
-        let private_key = rng.gen_biguint_range(&big(0), &field);
-        let public_key = curve.init_point().pow(&private_key, &curve);
-
-        ElGamal {
-            curve,
-            public_key,
-            private_key,
-        }
+    // Rebuilds a key pair from a stored private key. Returns an Error if the scalar isn't in [1, n).
+    pub fn from_private_key(curve: Curves, scalar: BigUint) -> Result<ElGamal> {
+        let curve = curve.try_into_curve()?;
+        let private_key = PrivateKey::try_new(scalar, &curve)?;
+        Ok(Self::from_parts(curve, private_key))
     }
 
-    fn new_521() -> ElGamal {
-        let field = BigUint::parse_bytes(
-            b"01FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
-            16,
-        )
-        .unwrap();
-        let a = BigUint::parse_bytes(
-            b"01FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC",
-            16,
-        )
-        .unwrap();
-        let a = ModNum::new(&a, &field);
-        let b = BigUint::parse_bytes(
-            b"0051953EB9618E1C9A1F929A21A0B68540EEA2DA725B99B315F3B8B489918EF109E156193951EC7E937B1652C0BD3BB1BF073573DF883D2C34F1EF451FD46B503F00",
-            16,
-        )
-        .unwrap();
-        let b = ModNum::new(&b, &field);
-        let init_x = BigUint::parse_bytes(
-            b"0200C6858E06B70404E9CD9E3ECB662395B4429C648139053FB521F828AF606B4D3DBAA14B5E77EFE75928FE1DC127A2FFA8DE3348B3C1856A429BF97E7E31C2E5BD66",
-            16,
-        )
-        .unwrap();
-        let init_x = ModNum::new(&init_x, &field);
-        let init_y = calc_y(&init_x, &a, &b);
-        let init_point = EllipticType::Point(Point::new(init_x, init_y, &a, &b));
-        let curve = EllipticCurve::new(a, b, init_point);
-        let mut rng = rand::thread_rng();
-        let private_key = rng.gen_biguint_range(&big(0), &field);
-        let public_key = curve.init_point().pow(&private_key, &curve);
-
-        ElGamal {
+    // Creates an encrypt only ElGamal from someone else's public key. Returns an Error if the point isn't a
+    //  valid public key for the curve.
+    pub fn from_public_key(curve: Curves, point: EllipticType) -> Result<ElGamal> {
+        let curve = curve.try_into_curve()?;
+        let public_key = PublicKey::try_new(point, &curve)?;
+        Ok(ElGamal {
             curve,
             public_key,
-            private_key,
-        }
+            private_key: None,
+        })
     }
 
-    fn new_custom(curve: EllipticCurve) -> ElGamal {
-        let field = curve.a().field();
-        let mut rng = rand::thread_rng();
-        let private_key = rng.gen_biguint_range(&big(0), &field);
-        let public_key = curve.init_point().pow(&private_key, &curve);
-
+    fn from_parts(curve: EllipticCurve, private_key: PrivateKey) -> ElGamal {
+        let public_key = private_key.public_key(&curve);
         ElGamal {
             curve,
-            private_key,
             public_key,
+            private_key: Some(private_key),
         }
     }
 
@@ -169,123 +159,248 @@ impl ElGamal {
         &self.curve
     }
     pub fn public_key(&self) -> &EllipticType {
-        &self.public_key
+        self.public_key.point()
     }
+    // panics if this ElGamal was built from only a public key, see try_private_key.
     pub fn private_key(&self) -> &BigUint {
-        &self.private_key
+        self.try_private_key().unwrap_or_else(|e| panic!("{}", e))
+    }
+    pub fn try_private_key(&self) -> Result<&BigUint> {
+        match &self.private_key {
+            Some(private_key) => Ok(private_key.scalar()),
+            None => Err(Error::MissingPrivateKey),
+        }
+    }
+    pub fn public(&self) -> &PublicKey {
+        &self.public_key
     }
+    pub fn private(&self) -> Option<&PrivateKey> {
+        self.private_key.as_ref()
+    }
+}
+
+// Decrypts the cipher text and returns it as a String. The program will panic if the decrypted bytes are not
+//  valid utf8, which is what most likely happens if the wrong private key is used. See try_decrypt.
+pub fn decrypt(private_key: &BigUint, curve: &EllipticCurve, cipher_text: &Ciphertext) -> String {
+    try_decrypt(private_key, curve, cipher_text).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Same as decrypt but returns an Error instead of panicking.
+pub fn try_decrypt(
+    private_key: &BigUint,
+    curve: &EllipticCurve,
+    cipher_text: &Ciphertext,
+) -> Result<String> {
+    let plain_bytes = try_decrypt_bytes(private_key, curve, cipher_text)?;
+    String::from_utf8(plain_bytes).map_err(Error::InvalidUtf8)
+}
+
+// Encrypts a String. This is a thin wrapper around encrypt_bytes.
+pub fn encrypt(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &String,
+) -> Ciphertext {
+    encrypt_bytes(public_key, curve, plain_text.as_bytes())
 }
 
-pub fn decrypt(
+// Same as encrypt but returns an Error instead of panicking.
+pub fn try_encrypt(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &String,
+) -> Result<Ciphertext> {
+    try_encrypt_bytes(public_key, curve, plain_text.as_bytes())
+}
+
+pub fn decrypt_bytes(
     private_key: &BigUint,
     curve: &EllipticCurve,
-    cipher_text: &Vec<(EllipticType, EllipticType)>,
-) -> String {
-    fn decode(m: &EllipticType) -> String {
+    cipher_text: &Ciphertext,
+) -> Vec<u8> {
+    try_decrypt_bytes(private_key, curve, cipher_text).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Decrypts the cipher text back into the original bytes. Returns an Error if the cipher text is malformed, has
+//  points that aren't on the curve, or doesn't decrypt to something that could have been produced by
+//   encrypt_bytes.
+pub fn try_decrypt_bytes(
+    private_key: &BigUint,
+    curve: &EllipticCurve,
+    cipher_text: &Ciphertext,
+) -> Result<Vec<u8>> {
+    // Turns the point back into the chunk of bytes it was encoded from. chunk_len is the number of bytes
+    //  the chunk originally had so any trailing zeros can be restored.
+    fn decode(m: &EllipticType, chunk_len: usize) -> Result<Vec<u8>> {
         match m {
-            EllipticType::Infinity(_) => {
-                panic!("I'm not sure how to handle this right now")
-            }
+            EllipticType::Infinity(_) => Err(Error::DecodedInfinity),
             EllipticType::Point(point) => {
                 let x = point.x().value() / &big(W);
-                let temp_m = x.to_bytes_le();
-                let message = String::from_utf8(temp_m);
-                match message {
-                    Err(e) => {
-                        panic!("Didn't return valid utf8 {:?}", e)
-                    }
-                    Ok(mes) => {
-                        return mes;
-                    }
+                let mut temp_m = x.to_bytes_le();
+                // to_bytes_le returns [0] for zero, which is the only time a chunk can have a trailing zero
+                //  in temp_m.
+                while temp_m.len() > chunk_len && temp_m.last() == Some(&0) {
+                    temp_m.pop();
                 }
+                if temp_m.len() > chunk_len {
+                    return Err(Error::ChunkTooLong);
+                }
+                temp_m.resize(chunk_len, 0);
+                Ok(temp_m)
             }
         }
     }
-    let mut plain_text: String = String::new();
-    for message_pair in cipher_text {
+    let n = chunk_size(curve)?;
+    if cipher_text.chunks().len() != cipher_text.len().div_ceil(n) {
+        return Err(Error::ChunkCountMismatch);
+    }
+    let mut plain_text: Vec<u8> = Vec::with_capacity(cipher_text.len());
+    for message_pair in cipher_text.chunks() {
         let c_0 = &message_pair.0;
         let c_1 = &message_pair.1;
+        // c_0 is raised to the private key so it gets the full public key checks, anything outside the group
+        //  generated by init_point could leak bits of the key. c_1 is only added, and with a cofactor it
+        //   needn't be in that group, so it only has to be on the curve.
+        curve.validate_public_key(c_0)?;
+        curve.validate_point(c_1)?;
 
-        let m = ((c_0.group_inv()).pow(private_key, curve)).group_op(c_1, curve);
-        let message = decode(&m);
-        plain_text.push_str(&message);
+        let m = ((c_0.group_inv()).try_pow_secret(private_key, curve)?).try_group_op(c_1, curve)?;
+        let chunk_len = n.min(cipher_text.len() - plain_text.len());
+        plain_text.extend(decode(&m, chunk_len)?);
     }
-    plain_text
+    Ok(plain_text)
 }
 
-pub fn encrypt(
+pub fn encrypt_bytes(
     public_key: &EllipticType,
     curve: &EllipticCurve,
-    plain_text: &String,
-) -> Vec<(EllipticType, EllipticType)> {
-    let field = curve.field();
-    fn encode(text: &BigUint, curve: &EllipticCurve) -> EllipticType {
-        let mut counter: u32 = 0;
-        loop {
+    plain_text: &[u8],
+) -> Ciphertext {
+    try_encrypt_bytes(public_key, curve, plain_text).unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Encrypts the bytes with the given public key. Returns an Error if the public key fails
+//  EllipticCurve::validate_public_key or if a chunk of the message couldn't be encoded as a point.
+pub fn try_encrypt_bytes(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &[u8],
+) -> Result<Ciphertext> {
+    // The specific value for s is irrelevant to decryption. So in order to increase the difficulty of the
+    // task of breaking encryption a random value for s i used.
+    encrypt_with(public_key, curve, plain_text, || random_scalar(curve))
+}
+
+pub fn encrypt_bytes_deterministic(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &[u8],
+    secret: &BigUint,
+    hash: Hash,
+) -> Ciphertext {
+    try_encrypt_bytes_deterministic(public_key, curve, plain_text, secret, hash)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+// Same as try_encrypt_bytes but each s comes from an RFC 6979 nonce generator seeded with the secret and the
+//  hash of the plain text, so encrypting the same bytes with the same secret always gives the same cipher text.
+//   Meant for reproducible test output. The secret should be something only the encrypter knows, e.g. their
+//  own ElGamal private key, anyone who knows it can work out s and decrypt.
+pub fn try_encrypt_bytes_deterministic(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &[u8],
+    secret: &BigUint,
+    hash: Hash,
+) -> Result<Ciphertext> {
+    let mut nonces =
+        NonceGenerator::new(hash, secret, curve.scalar_bound(), &hash.digest(plain_text));
+    encrypt_with(public_key, curve, plain_text, || Ok(nonces.next()))
+}
+
+// Does the work for both try_encrypt_bytes and try_encrypt_bytes_deterministic. next_s is called once per chunk
+//  to get the ephemeral s, there has to be at least one scalar in [1, n) for it to return.
+fn encrypt_with(
+    public_key: &EllipticType,
+    curve: &EllipticCurve,
+    plain_text: &[u8],
+    mut next_s: impl FnMut() -> Result<BigUint>,
+) -> Result<Ciphertext> {
+    if curve.scalar_bound() <= &big(1) {
+        return Err(Error::InvalidOrder);
+    }
+    curve.validate_public_key(public_key)?;
+    fn encode(text: &BigUint, curve: &EllipticCurve) -> Result<EllipticType> {
+        for counter in 0..W {
             let intermediate = big(W) * text + &big(counter);
-            let x = ModNum::new(&intermediate, curve.field());
-            let y = calc_y(&x, curve.a(), curve.b());
-            if y.value() != &big(0) {
-                return EllipticType::Point(Point::new(x, y, &curve.a(), &curve.b()));
-            } else {
-                counter += 1;
-            }
-            if counter > W {
-                panic!(
-                    "Something exceeding rare, 10^-78 level rare, occured. Increase the size of W"
-                );
+            let x = ModNum::in_field(&intermediate, curve.shared_field());
+            if let Some(y) = calc_y(&x, curve.a(), curve.b()) {
+                return Ok(EllipticType::Point(Point::try_new(
+                    x,
+                    y,
+                    curve.a(),
+                    curve.b(),
+                )?));
             }
         }
+        Err(Error::EncodingFailed)
     }
 
-    // Splits the full string message into chunks of appropriate size as determined by n. Then converts those
+    // Splits the full message into chunks of appropriate size as determined by n. Then converts those
     //  appropriately sized chunks into integers and combines them into a Vec.
-    fn split(message: &String, n: usize) -> Vec<BigUint> {
-        let mes_bytes = message.as_bytes();
-        let mut messages: Vec<BigUint> = Vec::new();
-        for i in (0..mes_bytes.len()).step_by(n) {
-            let mes_as_num = if (i + n) > (mes_bytes.len() - 1) {
-                BigUint::from_bytes_le(&mes_bytes[i..])
-            } else {
-                BigUint::from_bytes_le(&mes_bytes[i..i + n])
-            };
-            messages.push(mes_as_num);
-        }
-        messages
+    fn split(message: &[u8], n: usize) -> Vec<BigUint> {
+        message.chunks(n).map(BigUint::from_bytes_le).collect()
     }
-    // n - Determines the maximimum chunk size for message encoding. Allows messages 256 potentional values
-    //  This is needed because not every value is a valid point on the curve. ~50% of x values generate a
-    //   valid z such that there exists a y^2 = z. With 256 additional values of X to choose from the
-    // potentional that this algorithim doesn't find a valid point is ~10^-78. For referenc it is estimated
-    //  this is approximately the number of atoms in the universe. Aka if you repeated this encoding once
-    // for each atom in the universe it would fail ~ONE(!!!) time.
-    let n: usize = ((curve.field() / big(256)).bits() / 8).try_into().unwrap();
-    let message_list = split(&plain_text, n);
+    let n = chunk_size(curve)?;
+    let message_list = split(plain_text, n);
     let mut point_mes_list: Vec<EllipticType> = Vec::new();
     for mes_as_num in message_list {
-        point_mes_list.push(encode(&mes_as_num, &curve));
+        point_mes_list.push(encode(&mes_as_num, curve)?);
     }
     let mut encrypted_message_vec: Vec<(EllipticType, EllipticType)> = Vec::new();
-    let mut rng = rand::thread_rng();
 
     for point in &point_mes_list {
-        // The specific value for s is irrelevant to decryption. So in order to increase the difficulty of the
-        // task of breaking encryption a random value for s i used.
-        let s = rng.gen_biguint_range(&(field / (2 * W)), &(field / W));
+        let s = next_s()?;
 
         // C_0 = (initial_curve_position)^s
-        let c_0 = curve.init_point().pow(&s, curve);
+        let c_0 = curve.init_point_pow_secret(&s);
         // h_to_the_s = (public_key)^s = (initial_curve_position)^(private_key)^s
-        let h_to_the_s = public_key.pow(&s, curve);
+        let h_to_the_s = public_key.try_pow_secret(&s, curve)?;
         // C_1 = h_to_the_s * (message_chunk_as_a_number) = (initial_curve_position)^(private_key)^s * message
-        let c_1 = h_to_the_s.group_op(&point, curve);
+        let c_1 = h_to_the_s.try_group_op(point, curve)?;
         encrypted_message_vec.push((c_0, c_1));
     }
 
-    encrypted_message_vec
+    Ok(Ciphertext::new(encrypted_message_vec, plain_text.len()))
+}
+
+// n - Determines the maximimum chunk size, in bytes, for message encoding. Allows messages 256 potentional
+//  values This is needed because not every value is a valid point on the curve. ~50% of x values generate a
+//   valid z such that there exists a y^2 = z. With 256 additional values of X to choose from the
+// potentional that this algorithim doesn't find a valid point is ~10^-78. For referenc it is estimated
+//  this is approximately the number of atoms in the universe. Aka if you repeated this encoding once
+// for each atom in the universe it would fail ~ONE(!!!) time.
+//   One byte is reserved for W and the chunk is kept a full byte below the size of the field so that
+//  W * chunk + counter is always less than the field, no matter what bytes the chunk contains. Returns
+//   Error::FieldTooSmall when that leaves no room for even one byte, fields below 2^16.
+pub(crate) fn chunk_size(curve: &EllipticCurve) -> Result<usize> {
+    match (curve.field().bits().saturating_sub(1) / 8).checked_sub(1) {
+        Some(n) if n > 0 => Ok(n.try_into().unwrap()),
+        _ => Err(Error::FieldTooSmall),
+    }
+}
+
+// Picks a random secret scalar from [1, n), or [1, field) if the order of the curve isn't known. Returns
+//  Error::InvalidOrder if that range is empty, an order of 1.
+fn random_scalar(curve: &EllipticCurve) -> Result<BigUint> {
+    if curve.scalar_bound() <= &big(1) {
+        return Err(Error::InvalidOrder);
+    }
+    let mut rng = rand::thread_rng();
+    Ok(rng.gen_biguint_range(&big(1), curve.scalar_bound()))
 }
 
-// Generates one of the y values for a given x
-fn calc_y(x: &ModNum, a: &ModNum, b: &ModNum) -> ModNum {
-    x.pow(&big(3)).add(&(a.mul(&x))).add(b).sqrt()
+// Generates one of the y values for a given x, None if x isn't the x of a point on the curve.
+fn calc_y(x: &ModNum, a: &ModNum, b: &ModNum) -> Option<ModNum> {
+    (x * x * x + a * x + b).sqrt()
 }
@@ -1,46 +1,111 @@
+use crate::arithmetic::with_arithmetic;
+use crate::clean_up::big;
+use crate::curve_builder::{self, Weakness, MOV_BOUND};
 use crate::elliptic_point::EllipticType;
+use crate::error::{Error, Result};
+use crate::field::Field;
+use crate::jacobian::{BaseTable, FixedBaseTable, Params};
 use crate::modular_numbers::ModNum;
+use crate::point_counting::{self, PointCounting};
 use num_bigint::BigUint;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::fmt;
+use std::sync::{Arc, OnceLock};
 
 // Simple struct that holds the a, b, and starting point for an elliptic curve
 //  Elliptic curves have the form y^2 = x^3 + a*x + b
 //   Curves usually, always(?), come with an initial point. This isn't strictly required but if it wasn't
 //  supplied you would need to calculate, fairly simple, and publish an additional piece of information along
 //   with your public_key
-#[derive(Debug, Eq, PartialEq)]
+// The order, n, is the number of points generated by init_point, n * init_point = Infinity. The cofactor, h,
+//  is the number of points on the whole curve divided by n. Published curves come with both but they aren't
+//   easy to calculate for a custom curve so they are optional.
+// When the order is known a table of powers of init_point is built the first time init_point_pow is called.
+//  It is shared between clones of the curve and isn't part of the curve's value, two curves are equal if
+//   their a, b, init_point, order, and cofactor are.
+#[derive(Debug)]
 pub struct EllipticCurve {
     a: ModNum,
     b: ModNum,
     init_point: EllipticType,
+    order: Option<BigUint>,
+    cofactor: Option<BigUint>,
+    init_table: OnceLock<Arc<dyn BaseTable>>,
 }
 
 impl EllipticCurve {
     // Creates an elliptic curve struct. ensures that the supplied data is from the same field thus ensuring
     //  the calculations generated with the data is valid/meaningful.
     pub fn new(a: ModNum, b: ModNum, init_point: EllipticType) -> EllipticCurve {
-        assert!(a.field() == b.field(), "a & b aren't from the same field");
-        assert!(
-            a.field() == init_point.field(),
-            "a and init_x aren't from the same field"
-        );
-
-        match init_point {
-            EllipticType::Infinity(inf) => {
-                return EllipticCurve {
-                    a,
-                    b,
-                    init_point: EllipticType::Infinity(inf),
-                }
-            }
-            EllipticType::Point(point) => {
-                return EllipticCurve {
-                    a: a,
-                    b: b,
-                    init_point: EllipticType::Point(point),
-                };
-            }
+        Self::try_new(a, b, init_point).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as new but returns Error::FieldMismatch instead of panicking when a, b, and init_point aren't all
+    //  from the same field.
+    pub fn try_new(a: ModNum, b: ModNum, init_point: EllipticType) -> Result<EllipticCurve> {
+        if a.shared_field() != b.shared_field() || a.shared_field() != init_point.shared_field() {
+            return Err(Error::FieldMismatch);
         }
+
+        Ok(EllipticCurve {
+            a,
+            b,
+            init_point,
+            order: None,
+            cofactor: None,
+            init_table: OnceLock::new(),
+        })
+    }
+
+    // Creates an elliptic curve whose init_point has a known order and cofactor.
+    pub fn new_with_order(
+        a: ModNum,
+        b: ModNum,
+        init_point: EllipticType,
+        order: BigUint,
+        cofactor: BigUint,
+    ) -> EllipticCurve {
+        Self::try_new_with_order(a, b, init_point, order, cofactor)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as new_with_order but returns an Error instead of panicking. The order and cofactor are taken on
+    //  trust, no attempt is made to check n * init_point = Infinity.
+    pub fn try_new_with_order(
+        a: ModNum,
+        b: ModNum,
+        init_point: EllipticType,
+        order: BigUint,
+        cofactor: BigUint,
+    ) -> Result<EllipticCurve> {
+        if order == big(0) || cofactor == big(0) {
+            return Err(Error::InvalidOrder);
+        }
+        let mut curve = Self::try_new(a, b, init_point)?;
+        curve.order = Some(order);
+        curve.cofactor = Some(cofactor);
+        Ok(curve)
+    }
+
+    // A random curve over a random prime field with the given number of bits, whose init_point has a prime
+    //  order and a cofactor of at most curve_builder::MAX_GENERATED_COFACTOR, see curve_builder::generate.
+    //   It counts the points on dozens of curves, in a release build that takes seconds for a 48 bit field
+    //  and a minute or two for 64 or 80 bits. Panics if bits is 17 or less, too small for the builder, or over
+    //   SCHOOF_BITS, too large to count the points on.
+    pub fn generate<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> EllipticCurve {
+        Self::try_generate(bits, rng).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as generate but returns Error::InvalidCurve or Error::FieldTooLargeToCount instead of panicking.
+    pub fn try_generate<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> Result<EllipticCurve> {
+        curve_builder::generate(bits, rng)
+    }
+
+    // Same as generate with a StdRng seeded from seed, so the same seed always gives the same curve, for test
+    //  fixtures. StdRng's algorithm can change between versions of rand, and the curve along with it.
+    pub fn generate_seeded(bits: u64, seed: u64) -> EllipticCurve {
+        Self::generate(bits, &mut StdRng::seed_from_u64(seed))
     }
 
     pub fn a(&self) -> &ModNum {
@@ -50,11 +115,165 @@ impl EllipticCurve {
         &self.b
     }
     pub fn field(&self) -> &BigUint {
-        &self.a.field()
+        self.a.field()
+    }
+    pub fn shared_field(&self) -> &Arc<Field> {
+        self.a.shared_field()
     }
     pub fn init_point(&self) -> &EllipticType {
         &self.init_point
     }
+    pub fn order(&self) -> Option<&BigUint> {
+        self.order.as_ref()
+    }
+    pub fn cofactor(&self) -> Option<&BigUint> {
+        self.cofactor.as_ref()
+    }
+
+    // init_point^exp. Faster than init_point().pow when the order of the curve is known since it uses the
+    //  precomputed table, building it first if this is the first call.
+    pub fn init_point_pow(&self, exp: &BigUint) -> EllipticType {
+        let order = match &self.order {
+            Some(order) => order,
+            None => return self.init_point.pow(exp, self),
+        };
+        self.init_table().unwrap().pow(&(exp % order))
+    }
+
+    // init_point^exp for a secret exp, see EllipticType::pow_secret. Uses the table when the order is known and
+    //  odd, with every row read in full so the lookups don't depend on exp. An even order would mean the
+    //   table holds a point of order 2, which the complete formulas can't handle, so the ladder is used.
+    pub fn init_point_pow_secret(&self, exp: &BigUint) -> EllipticType {
+        let order = match &self.order {
+            Some(order) if order.bit(0) => order,
+            _ => return self.init_point.pow_secret(exp, self),
+        };
+        self.init_table().unwrap().pow_secret(&(exp % order))
+    }
+
+    // Builds the init_point table if it hasn't been built yet. Returns None when the order isn't known, the
+    //  table only covers exponents below the order.
+    pub(crate) fn init_table(&self) -> Option<&dyn BaseTable> {
+        let order = self.order.as_ref()?;
+        let table = self.init_table.get_or_init(|| {
+            with_arithmetic!(self.field(), |field| {
+                let params = Params::new(field, self);
+                Arc::new(FixedBaseTable::new(&self.init_point, order.bits(), params))
+                    as Arc<dyn BaseTable>
+            })
+        });
+        Some(table.as_ref())
+    }
+
+    // The number of points on the whole curve, h * n, if it is known. Every point on the curve, not just
+    //  the ones generated by init_point, goes to Infinity when raised to this.
+    pub fn group_order(&self) -> Option<BigUint> {
+        match (&self.order, &self.cofactor) {
+            (Some(n), Some(h)) => Some(n * h),
+            _ => None,
+        }
+    }
+
+    // The number of points on the curve, including Infinity, counted with PointCounting::for_field. This is
+    //  the order and cofactor multiplied together, for a custom curve find the order of init_point from it
+    //   with point_order. The field has to be prime. Returns Error::FieldTooLargeToCount for fields over
+    //  SCHOOF_BITS, 128 bits, and Error::PointCountFailed if the curve is singular or the field isn't prime.
+    pub fn count_points(&self) -> Result<BigUint> {
+        self.count_points_with(PointCounting::for_field(self.field()))
+    }
+
+    // Same as count_points but with the given method, see PointCounting. Each method has a limit on the size
+    //  of the field it takes, NAIVE_BITS, MESTRE_BITS or SCHOOF_BITS.
+    pub fn count_points_with(&self, method: PointCounting) -> Result<BigUint> {
+        point_counting::count_points(self, method, &mut rand::thread_rng())
+    }
+
+    // The order of point, the smallest n with n * point = Infinity. group_order is a multiple of it, usually
+    //  count_points, as a list of primes and their powers. Panics if point isn't on the curve or group_order
+    //   isn't a multiple of its order.
+    pub fn point_order(&self, point: &EllipticType, group_order: &[(BigUint, u32)]) -> BigUint {
+        self.try_point_order(point, group_order)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as point_order but returns an Error from validate_point, or Error::WrongGroupOrder, instead of
+    //  panicking.
+    pub fn try_point_order(
+        &self,
+        point: &EllipticType,
+        group_order: &[(BigUint, u32)],
+    ) -> Result<BigUint> {
+        point_counting::point_order(self, point, group_order)
+    }
+
+    // The weaknesses in curve_builder::Weakness this curve has, see EllipticCurveBuilder. Empty when the order
+    //  isn't known since both checks need it.
+    pub fn weaknesses(&self) -> Vec<Weakness> {
+        let order = match &self.order {
+            Some(order) => order,
+            None => return vec![],
+        };
+        let mut weaknesses = vec![];
+        if self.group_order().as_ref().unwrap_or(order) == self.field() {
+            weaknesses.push(Weakness::Anomalous);
+        }
+        let p = self.field() % order;
+        let mut power = p.clone();
+        for k in 1..=MOV_BOUND {
+            if power == big(1) {
+                weaknesses.push(Weakness::Mov(k));
+                break;
+            }
+            power = power * &p % order;
+        }
+        weaknesses
+    }
+
+    // Checks point can be used with this curve, that it is from the curve's field and, unless it's Infinity,
+    //  that it satisfies y^2 = x^3 + a*x + b. group_op and pow trust their arguments, so a point from
+    //   someone else should be checked first. Otherwise they can pick a point on a different curve with the
+    //  same a, whose group might have small subgroups that leak the private key a few bits at a time, an
+    //   invalid curve attack.
+    pub fn validate_point(&self, point: &EllipticType) -> Result<()> {
+        if point.shared_field() != self.shared_field() {
+            return Err(Error::FieldMismatch);
+        }
+        if let EllipticType::Point(p) = point {
+            let (x, y) = (p.x(), p.y());
+            if y * y != x * x * x + &self.a * x + &self.b {
+                return Err(Error::NotOnCurve);
+            }
+        }
+        Ok(())
+    }
+
+    // The public key validation from SEC 1 section 3.2.2.1. Along with validate_point the point can't be
+    //  Infinity and must be in the group generated by init_point, n * point = Infinity. When the cofactor is 1
+    //   every point on the curve is in that group so the multiplication is skipped, and when the order isn't
+    //  known it can't be done.
+    pub fn validate_public_key(&self, point: &EllipticType) -> Result<()> {
+        self.validate_point(point)?;
+        if let EllipticType::Infinity(_) = point {
+            return Err(Error::InvalidPublicKey);
+        }
+        if let (Some(order), cofactor) = (&self.order, &self.cofactor) {
+            if cofactor != &Some(big(1)) {
+                if let EllipticType::Point(_) = point.pow(order, self) {
+                    return Err(Error::InvalidPublicKey);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The exclusive upper bound for secret scalars, private keys and the ephemeral s in encrypt. When the
+    //  order is known scalars are taken from [1, n), otherwise the field is the best bound we have.
+    pub fn scalar_bound(&self) -> &BigUint {
+        match &self.order {
+            Some(n) => n,
+            None => self.field(),
+        }
+    }
 }
 
 impl Clone for EllipticCurve {
@@ -63,10 +282,24 @@ impl Clone for EllipticCurve {
             a: self.a.clone(),
             b: self.b.clone(),
             init_point: self.init_point.clone(),
+            order: self.order.clone(),
+            cofactor: self.cofactor.clone(),
+            init_table: self.init_table.clone(),
         }
     }
 }
 
+impl PartialEq for EllipticCurve {
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a
+            && self.b == other.b
+            && self.init_point == other.init_point
+            && self.order == other.order
+            && self.cofactor == other.cofactor
+    }
+}
+impl Eq for EllipticCurve {}
+
 impl fmt::Display for EllipticCurve {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
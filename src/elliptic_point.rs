@@ -1,24 +1,48 @@
-use crate::clean_up::big;
+use crate::arithmetic::with_arithmetic;
+use crate::clean_up::{big, byte_len, to_fixed_bytes};
 use crate::elliptic_curve::EllipticCurve;
+use crate::error::{Error, Result};
+use crate::field::Field;
+use crate::jacobian::{mul_double_and_add, mul_fixed_window, mul_wnaf, Jacobian, Params};
+use crate::ladder::{self, Projective};
 use crate::modular_numbers::ModNum;
 use num_bigint::BigUint;
 use std::fmt;
+use std::sync::Arc;
 
 //Defines the two different types of points on an elliptic curve
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum EllipticType {
     Infinity(Inf),
     Point(Point),
 }
 
+// The ways pow can do a scalar multiplication. The widths are the number of bits of the exponent handled at
+//  a time, wider windows need fewer additions but a bigger table of precomputed points.
+//   DoubleAndAdd - one bit at a time, a doubling for every bit and an addition for every 1 bit.
+//   FixedWindow(width) - width from 1 to 8, a table of base^0 to base^(2^width - 1).
+//   Wnaf(width) - width from 2 to 8, signed digits so only the odd powers up to 2^(width-1) are needed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ScalarMul {
+    DoubleAndAdd,
+    FixedWindow(u32),
+    Wnaf(u32),
+}
+
+impl Default for ScalarMul {
+    fn default() -> Self {
+        ScalarMul::Wnaf(4)
+    }
+}
+
 // stores the nessacary information for Infinity on an elliptic curve
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Inf {
-    field: BigUint,
+    field: Arc<Field>,
 }
 
 // Stores the nessacary information for a point on an elliptic curve.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Point {
     x: ModNum,
     y: ModNum,
@@ -26,28 +50,33 @@ pub struct Point {
 
 impl Point {
     // Creates a point on the curve described by y^2 = x^3 + a*x + b
+    // panics if the point isn't valid, see try_new.
     pub fn new(x: ModNum, y: ModNum, curve_a: &ModNum, curve_b: &ModNum) -> Point {
-        // These asserts ensure that the operations are on numbers from the same field and are thus valid
-        assert!(
-            x.field() == y.field(),
-            "x and y aren't from the same field."
-        );
-        assert!(
-            x.field() == curve_a.field(),
-            "x/y and curve_a aren't from the same field"
-        );
-        assert!(
-            x.field() == curve_b.field(),
-            "x/y/curve_a and curve_b aren't from the same field"
-        );
-
-        // This assert ensures that the given x and y are valid for the given a and b
-        let test_val = x.pow(&big(3)).add(&(curve_a.mul(&x))).add(curve_b);
-        assert!(
-            y.pow(&big(2)) == test_val,
-            "Not a valid point on the given curve"
-        );
+        Self::try_new(x, y, curve_a, curve_b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Creates a point on the curve described by y^2 = x^3 + a*x + b or returns an Error if the x, y, a, and b
+    //  aren't all from the same field or if (x, y) isn't on the curve.
+    pub fn try_new(x: ModNum, y: ModNum, curve_a: &ModNum, curve_b: &ModNum) -> Result<Point> {
+        // These checks ensure that the operations are on numbers from the same field and are thus valid
+        let field = x.shared_field();
+        if field != y.shared_field()
+            || field != curve_a.shared_field()
+            || field != curve_b.shared_field()
+        {
+            return Err(Error::FieldMismatch);
+        }
 
+        // This check ensures that the given x and y are valid for the given a and b
+        if &y * &y != &x * &x * &x + curve_a * &x + curve_b {
+            return Err(Error::NotOnCurve);
+        }
+
+        Ok(Point { x, y })
+    }
+
+    // Skips the on curve check, for points the crate has just calculated from other points on the curve.
+    pub(crate) fn from_trusted(x: ModNum, y: ModNum) -> Point {
         Point { x, y }
     }
 
@@ -62,20 +91,29 @@ impl Point {
 impl Inf {
     // Every curve has an infinity/0 and so the only characteristic about Infinity points is what field they belong to.
     pub fn new(field: &BigUint) -> Inf {
+        Self::in_field(&Field::new(field))
+    }
+    pub fn in_field(field: &Arc<Field>) -> Inf {
         Inf {
             field: field.clone(),
         }
     }
     pub fn field(&self) -> &BigUint {
+        self.field.modulus()
+    }
+    pub fn shared_field(&self) -> &Arc<Field> {
         &self.field
     }
 }
 
 impl EllipticType {
     pub fn field(&self) -> &BigUint {
+        self.shared_field().modulus()
+    }
+    pub fn shared_field(&self) -> &Arc<Field> {
         match self {
             EllipticType::Infinity(inf) => &inf.field,
-            EllipticType::Point(point) => point.x.field(),
+            EllipticType::Point(point) => point.x.shared_field(),
         }
     }
 
@@ -87,149 +125,269 @@ impl EllipticType {
     // point and calculating its slope to determine the new point created by combined Self and Other. Finally if neither of the above
     //  is true the slope of the line connecting Self and Other is calculated in the standard manner. This slope is used to determine
     // the new point created by the combination of Self and Other.
+    // panics if the points and curve aren't all from the same field, see try_group_op.
     pub fn group_op(&self, other: &Self, curve: &EllipticCurve) -> Self {
-        assert!(
-            self.field() == other.field(),
-            "value for field on points don't match"
-        );
-        assert!(
-            self.field() == curve.a().field(),
-            "value of the field doesn't match for points and curve"
-        );
-        match self {
+        self.try_group_op(other, curve)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as group_op but returns Error::FieldMismatch instead of panicking.
+    pub fn try_group_op(&self, other: &Self, curve: &EllipticCurve) -> Result<Self> {
+        if self.shared_field() != other.shared_field()
+            || self.shared_field() != curve.a().shared_field()
+        {
+            return Err(Error::FieldMismatch);
+        }
+        Ok(match self {
             // self = Infinity
-            EllipticType::Infinity(_) => {
-                return other.clone();
-            }
+            EllipticType::Infinity(_) => other.clone(),
             EllipticType::Point(left) => {
                 match other {
                     //Other = Infinity
                     EllipticType::Infinity(_) => {
-                        return EllipticType::Point(Point {
+                        EllipticType::Point(Point {
                             x: left.x.clone(),
                             y: left.y.clone(),
-                        });
+                        })
                         //return self.clone();
                     }
                     EllipticType::Point(right) => {
                         let slope: ModNum;
                         if left.x == right.x {
                             if left.y == right.y.add_inv() {
-                                return EllipticType::Infinity(Inf::new(left.x.field()));
+                                return Ok(EllipticType::Infinity(Inf::in_field(
+                                    left.x.shared_field(),
+                                )));
                             } else {
                                 // (3x^2+a)/(2y)
-                                slope = (ModNum::new(&big(3), left.x.field())
+                                slope = (ModNum::in_field(&big(3), left.x.shared_field())
                                     .mul(&left.x.pow(&big(2)))
                                     .add(curve.a()))
                                 .mul(
-                                    &(ModNum::new(&big(2), left.x.field()).mul(&left.y)).mul_inv(),
+                                    &(ModNum::in_field(&big(2), left.x.shared_field())
+                                        .mul(&left.y))
+                                    .mul_inv(),
                                 );
                             }
                         } else {
                             // (right.y + (-left.y)) * 1/(right.x+(-left.x)) = (right.y-left.y)/(right.x-left.x)
-                            slope = ((&right.y).add(&(&left.y).add_inv()))
-                                .mul(&((&right.x).add(&(&left.x).add_inv())).mul_inv());
+                            slope = (right.y.add(&left.y.add_inv()))
+                                .mul(&(right.x.add(&left.x.add_inv())).mul_inv());
                         }
                         // slope^2 + (-left.x) + (-right.x) = slope^2 - xleft.x - right.x
                         let new_x = slope
                             .pow(&big(2))
-                            .add(&(&left.x).add_inv())
-                            .add(&(&right.x).add_inv());
+                            .add(&left.x.add_inv())
+                            .add(&right.x.add_inv());
                         let new_y = left
                             .y
                             .add_inv()
-                            .add(&(slope.mul(&((&new_x).add(&left.x.add_inv())))).add_inv());
-                        return EllipticType::Point(Point { x: new_x, y: new_y });
+                            .add(&(slope.mul(&(new_x.add(&left.x.add_inv())))).add_inv());
+                        EllipticType::Point(Point { x: new_x, y: new_y })
                     } //return left.group_op_point(&right, curve),
                 }
             }
-        }
+        })
     }
 
     // Returns the operational inverse of self. If Self.y = 0 or Self is Infinity then Self is it's own inverse
     pub fn group_inv(&self) -> Self {
         match self {
-            EllipticType::Infinity(inf) => {
-                return EllipticType::Infinity(Inf {
-                    field: inf.field.clone(),
-                })
-            }
+            EllipticType::Infinity(inf) => EllipticType::Infinity(Inf {
+                field: inf.field.clone(),
+            }),
             EllipticType::Point(point) => {
                 if point.y.value() == &big(0) {
-                    return EllipticType::Point(Point {
+                    EllipticType::Point(Point {
                         x: point.x.clone(),
                         y: point.y.clone(),
-                    });
+                    })
                 } else {
-                    return EllipticType::Point(Point {
+                    EllipticType::Point(Point {
                         x: point.x.clone(),
                         y: point.y.add_inv(),
-                    });
+                    })
                 }
             }
         }
     }
 
-    // Performs exponentiation on elliptic type objects. Uses a recursive algorithm to do so.
+    // Performs exponentiation on elliptic type objects using ScalarMul::default().
+    //  If the number of points on the curve is known the exponent is reduced by it first, since raising any
+    //   point on the curve to that number gives Infinity.
+    // The work is done in Jacobian coordinates so there is only one division, at the very end, instead of one
+    //  for every group_op, and with the field arithmetic from arithmetic.rs, which is Montgomery multiplication
+    //   for all but unusual fields.
+    // panics if the point and curve aren't from the same field, see try_pow.
     pub fn pow(&self, exp: &BigUint, curve: &EllipticCurve) -> Self {
-        match self {
-            EllipticType::Infinity(inf) => {
-                return EllipticType::Infinity(Inf {
-                    field: inf.field.clone(),
-                })
+        self.pow_with(exp, curve, ScalarMul::default())
+    }
+
+    // Same as pow but returns Error::FieldMismatch instead of panicking.
+    pub fn try_pow(&self, exp: &BigUint, curve: &EllipticCurve) -> Result<Self> {
+        self.try_pow_with(exp, curve, ScalarMul::default())
+    }
+
+    // Same as pow but with the given method. Every method gives the same answer, they only differ in speed.
+    //  panics if the window width is out of range, see ScalarMul, or the fields don't match, see try_pow_with.
+    pub fn pow_with(&self, exp: &BigUint, curve: &EllipticCurve, method: ScalarMul) -> Self {
+        self.try_pow_with(exp, curve, method)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as pow_with but returns Error::FieldMismatch or Error::InvalidWindowWidth instead of panicking.
+    pub fn try_pow_with(
+        &self,
+        exp: &BigUint,
+        curve: &EllipticCurve,
+        method: ScalarMul,
+    ) -> Result<Self> {
+        if self.shared_field() != curve.shared_field() {
+            return Err(Error::FieldMismatch);
+        }
+        let width_range = match method {
+            ScalarMul::DoubleAndAdd => None,
+            ScalarMul::FixedWindow(width) => Some((width, 1..=8)),
+            ScalarMul::Wnaf(width) => Some((width, 2..=8)),
+        };
+        if let Some((width, range)) = width_range {
+            if !range.contains(&width) {
+                return Err(Error::InvalidWindowWidth);
             }
-            EllipticType::Point(point) => {
-                if exp == &big(0) {
-                    return EllipticType::Infinity(Inf {
-                        field: point.x.field().clone(),
-                    });
-                } else if exp == &big(1) {
-                    return EllipticType::Point(Point {
-                        x: point.x.clone(),
-                        y: point.y.clone(),
-                    });
-                }
-                if exp % &big(2) == big(0) {
-                    let temp = self.pow(&(exp / &big(2)), curve);
-                    return temp.group_op(&temp, curve);
-                } else {
-                    let temp = self.pow(&((exp - &big(1)) / &big(2)), curve);
-                    return temp.group_op(&temp, curve).group_op(self, curve);
-                }
+        }
+        let reduced;
+        let exp = match curve.group_order() {
+            Some(group_order) => {
+                reduced = exp % group_order;
+                &reduced
             }
+            None => exp,
+        };
+        with_arithmetic!(curve.field(), |field| {
+            let params = Params::new(field, curve);
+            let base = Jacobian::from_affine(self, &params);
+            let result = match method {
+                ScalarMul::DoubleAndAdd => mul_double_and_add(&base, exp, &params),
+                ScalarMul::FixedWindow(width) => mul_fixed_window(&base, exp, width, &params),
+                ScalarMul::Wnaf(width) => mul_wnaf(&base, exp, width, &params),
+            };
+            Ok(result.to_affine(&params))
+        })
+    }
+
+    // Same as pow but for secret exponents, private keys and nonces. Uses the Montgomery ladder in ladder.rs,
+    //  which does the same operations whatever exp is, where pow skips work for 0 bits and group_op has
+    //   special cases for equal and inverse points. The number of steps depends on the curve, the bits of
+    //  the group order or one more than the bits of the field when it isn't known, never on exp. Slower than
+    //   pow so only use it when exp has to be kept secret. panics if the fields don't match, see try_pow_secret.
+    pub fn pow_secret(&self, exp: &BigUint, curve: &EllipticCurve) -> Self {
+        self.try_pow_secret(exp, curve)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as pow_secret but returns Error::FieldMismatch instead of panicking.
+    pub fn try_pow_secret(&self, exp: &BigUint, curve: &EllipticCurve) -> Result<Self> {
+        if self.shared_field() != curve.shared_field() {
+            return Err(Error::FieldMismatch);
         }
+        // The complete formulas can't add two points whose difference has order 2, and the ladder's points
+        //  always differ by self. Points of order 2 have y = 0 and self isn't secret, only exp is.
+        if let EllipticType::Point(point) = self {
+            if point.y.value() == &big(0) {
+                return self.try_pow(exp, curve);
+            }
+        }
+        let (exp, bits) = match curve.group_order() {
+            Some(group_order) => (exp % &group_order, group_order.bits()),
+            None => (exp.clone(), exp.bits().max(curve.field().bits() + 1)),
+        };
+        with_arithmetic!(curve.field(), |field| {
+            let params = Params::new(field, curve);
+            let base = Projective::from_affine(self, &params);
+            Ok(ladder::mul_ladder(&base, &exp, bits, &params).to_affine(&params))
+        })
     }
-}
 
-impl Clone for EllipticType {
-    fn clone(&self) -> Self {
+    // Encodes the point as bytes following SEC 1 section 2.3.3, https://www.secg.org/sec1-v2.pdf
+    //  Infinity is the single byte 0x00. Uncompressed points are 0x04 followed by x and y as fixed width
+    //   big-endian numbers. Compressed points are 0x02 or 0x03, for even or odd y respectively, followed by
+    //  just x. y can be recalculated from x and the parity of y, see from_sec1_bytes.
+    pub fn to_sec1_bytes(&self, compressed: bool) -> Vec<u8> {
         match self {
-            EllipticType::Infinity(inf) => {
-                return EllipticType::Infinity(Inf {
-                    field: inf.field().clone(),
-                })
-            }
+            EllipticType::Infinity(_) => vec![0x00],
             EllipticType::Point(point) => {
-                return EllipticType::Point(Point {
-                    x: point.x.clone(),
-                    y: point.y.clone(),
-                })
+                let len = byte_len(self.field());
+                let mut bytes = if compressed {
+                    if point.y.value().bit(0) {
+                        vec![0x03]
+                    } else {
+                        vec![0x02]
+                    }
+                } else {
+                    vec![0x04]
+                };
+                bytes.extend(to_fixed_bytes(point.x.value(), len));
+                if !compressed {
+                    bytes.extend(to_fixed_bytes(point.y.value(), len));
+                }
+                bytes
             }
         }
     }
-}
-impl Clone for Inf {
-    fn clone(&self) -> Self {
-        Inf {
-            field: self.field.clone(),
-        }
-    }
-}
-impl Clone for Point {
-    fn clone(&self) -> Self {
-        Point {
-            x: self.x.clone(),
-            y: self.y.clone(),
+
+    // Decodes a point encoded with to_sec1_bytes following SEC 1 section 2.3.4. Both compressed and
+    //  uncompressed points are accepted. The point is checked to be on the given curve, for compressed points
+    //   this happens when y is recovered from x^3 + a*x + b.
+    pub fn from_sec1_bytes(bytes: &[u8], curve: &EllipticCurve) -> Result<EllipticType> {
+        let field = curve.field();
+        let len = byte_len(field);
+        // Values greater than the field would be silently reduced by ModNum so they are rejected here.
+        let coordinate = |coord_bytes: &[u8]| -> Result<ModNum> {
+            let value = BigUint::from_bytes_be(coord_bytes);
+            if &value >= field {
+                return Err(Error::InvalidEncoding(
+                    "point coordinate is larger than the field",
+                ));
+            }
+            Ok(ModNum::in_field(&value, curve.shared_field()))
+        };
+        match bytes.first() {
+            Some(0x00) if bytes.len() == 1 => {
+                Ok(EllipticType::Infinity(Inf::in_field(curve.shared_field())))
+            }
+            Some(0x02) | Some(0x03) if bytes.len() == 1 + len => {
+                let x = coordinate(&bytes[1..])?;
+                let y_is_odd = bytes[0] == 0x03;
+                let test_val = &x * &x * &x + curve.a() * &x + curve.b();
+                let y = test_val.sqrt().ok_or(Error::NotOnCurve)?;
+                let y = if y.value().bit(0) == y_is_odd {
+                    y
+                } else if y.value() == &big(0) {
+                    // 0 is its own inverse so there isn't an odd y to choose.
+                    return Err(Error::InvalidEncoding("y is 0 but was marked as odd"));
+                } else {
+                    y.add_inv()
+                };
+                Ok(EllipticType::Point(Point::try_new(
+                    x,
+                    y,
+                    curve.a(),
+                    curve.b(),
+                )?))
+            }
+            Some(0x04) if bytes.len() == 1 + 2 * len => {
+                let x = coordinate(&bytes[1..1 + len])?;
+                let y = coordinate(&bytes[1 + len..])?;
+                Ok(EllipticType::Point(Point::try_new(
+                    x,
+                    y,
+                    curve.a(),
+                    curve.b(),
+                )?))
+            }
+            _ => Err(Error::InvalidEncoding(
+                "not a SEC 1 encoded point for this curve",
+            )),
         }
     }
 }
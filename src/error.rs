@@ -0,0 +1,147 @@
+use crate::curve_builder::CurveError;
+use std::fmt;
+use std::string::FromUtf8Error;
+
+// Every way an operation in this crate can fail. The panicking functions (ModNum::new, Point::new, encrypt,
+//  decrypt, ...) are thin wrappers around their try_ counterparts which return one of these instead.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    // A modular number was given a field of 0.
+    ZeroField,
+    // Two values that need to be from the same field aren't. Operations between them are undefined.
+    FieldMismatch,
+    // A curve was given an order or cofactor of 0, or its order is 1 so there's no scalar in [1, n) to use as
+    //  a key or ephemeral s.
+    InvalidOrder,
+    // Something made for one curve was used with another.
+    CurveMismatch,
+    // The curve isn't one of the named curves so it has no OID to write into a key file, or a key file named
+    //  a curve we don't know.
+    UnsupportedCurve,
+    // Something needed the order of the curve's init_point but the curve was created without one.
+    UnknownOrder,
+    // A signature's r or s isn't in [1, n).
+    InvalidSignature,
+    // The nonce given for a signature isn't in [1, n).
+    InvalidNonce,
+    // The x and y given don't satisfy y^2 = x^3 + a*x + b.
+    NotOnCurve,
+    // The private key isn't in [1, n).
+    InvalidPrivateKey,
+    // The public key is Infinity or isn't in the group generated by the curve's init_point.
+    InvalidPublicKey,
+    // An ECDH shared secret came out as Infinity, so the other side's public key was a bad one.
+    InvalidSharedSecret,
+    // An ElGamal that was only given a public key was asked for its private key.
+    MissingPrivateKey,
+    // Bytes being decoded aren't in the expected format. The message says what was wrong with them.
+    InvalidEncoding(&'static str),
+    // An authenticated cipher text's tag didn't match. It was changed, or the wrong key was used.
+    DecryptionFailed,
+    // A chunk of cipher text decrypted to Infinity, which no message chunk is ever encoded as.
+    DecodedInfinity,
+    // The decrypted bytes weren't valid utf8. Most likely the wrong private key was used.
+    InvalidUtf8(FromUtf8Error),
+    // None of the W candidate x values for a message chunk were on the curve.
+    EncodingFailed,
+    // The number of chunks in the cipher text doesn't match the length of the message it claims to hold.
+    ChunkCountMismatch,
+    // A decrypted chunk is larger than the chunk it was encrypted from. Most likely the wrong private key
+    //  was used.
+    ChunkTooLong,
+    // A ScalarMul window width is outside the range given for it in elliptic_point.rs.
+    InvalidWindowWidth,
+    // The curve's field is too small for a chunk of a message to be encoded in it, see el_gamal::chunk_size.
+    FieldTooSmall,
+    // A cipher text has more chunks, or a longer plain text, than the header written by Ciphertext::to_bytes
+    //  can hold.
+    CiphertextTooLarge,
+    // The factored group order given to EllipticCurve::point_order doesn't send the point to Infinity, so it
+    //  isn't the number of points on the curve.
+    WrongGroupOrder,
+    // The field is over the size the point counting method handles, see PointCounting.
+    FieldTooLargeToCount,
+    // No number of points within the Hasse bound fits the curve, it's singular or its field isn't prime.
+    PointCountFailed,
+    // EllipticCurveBuilder refused the curve's parameters. The CurveError says why.
+    InvalidCurve(CurveError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ZeroField => write!(f, "Cannot create a modular number with field of 0."),
+            Error::FieldMismatch => write!(f, "Values aren't from the same field"),
+            Error::InvalidOrder => write!(
+                f,
+                "The order and cofactor of a curve can't be 0, and the order must be over 1"
+            ),
+            Error::CurveMismatch => write!(f, "This was made for a different curve"),
+            Error::UnsupportedCurve => write!(f, "The curve isn't one of the named curves"),
+            Error::UnknownOrder => write!(f, "The order of the curve isn't known"),
+            Error::InvalidSignature => write!(f, "The signature's r and s must be in [1, n)"),
+            Error::InvalidNonce => write!(f, "The nonce must be in [1, n)"),
+            Error::NotOnCurve => write!(f, "Not a valid point on the given curve"),
+            Error::InvalidPrivateKey => write!(f, "The private key must be in the range [1, n)"),
+            Error::InvalidPublicKey => write!(
+                f,
+                "The public key is Infinity or isn't generated by the curve's init_point"
+            ),
+            Error::InvalidSharedSecret => write!(f, "The shared secret is Infinity"),
+            Error::MissingPrivateKey => write!(f, "This ElGamal only has a public key"),
+            Error::InvalidEncoding(message) => write!(f, "Invalid encoding: {}", message),
+            Error::DecryptionFailed => write!(f, "The cipher text failed authentication"),
+            Error::DecodedInfinity => write!(f, "Decrypted a chunk to Infinity"),
+            Error::InvalidUtf8(e) => write!(f, "Didn't return valid utf8 {:?}", e),
+            Error::EncodingFailed => write!(
+                f,
+                "Something exceeding rare, 10^-78 level rare, occured. Increase the size of W"
+            ),
+            Error::ChunkCountMismatch => {
+                write!(
+                    f,
+                    "The number of chunks doesn't match the length of the message"
+                )
+            }
+            Error::ChunkTooLong => {
+                write!(
+                    f,
+                    "Decrypted chunk is longer than the original message chunk"
+                )
+            }
+            Error::InvalidWindowWidth => {
+                write!(f, "The scalar multiplication window width is out of range")
+            }
+            Error::FieldTooSmall => {
+                write!(f, "The field is too small to encode messages in")
+            }
+            Error::CiphertextTooLarge => {
+                write!(f, "The cipher text is too large to serialize")
+            }
+            Error::WrongGroupOrder => {
+                write!(f, "The group order isn't a multiple of the point's order")
+            }
+            Error::FieldTooLargeToCount => {
+                write!(f, "The field is too large to count the points on this way")
+            }
+            Error::PointCountFailed => {
+                write!(
+                    f,
+                    "No number of points fits, the curve is singular or its field isn't prime"
+                )
+            }
+            Error::InvalidCurve(e) => write!(f, "Invalid curve: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::clean_up::big;
+use crate::error::{Error, Result};
+use crate::nist_primes::Reduction;
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+// The modulus of a field and everything worked out from it, shared by every ModNum, Point, and Inf in that
+//  field. They hold an Arc<Field> so making a new number is a reference count bump instead of a copy of the
+//   modulus.
+// Fields are interned, Field::new hands back the Arc that already exists for a modulus if there is one, so
+//  there's only ever one Field for each modulus and two numbers are in the same field exactly when they point
+//   at the same Field. Comparing fields is comparing pointers.
+#[derive(Debug)]
+pub struct Field {
+    modulus: BigUint,
+    reduction: Reduction,
+}
+
+// Every live field by modulus. Weak so a field is dropped once nothing uses it.
+fn fields() -> &'static Mutex<HashMap<BigUint, Weak<Field>>> {
+    static FIELDS: OnceLock<Mutex<HashMap<BigUint, Weak<Field>>>> = OnceLock::new();
+    FIELDS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl Field {
+    // The field for modulus, panics if modulus is zero.
+    pub fn new(modulus: &BigUint) -> Arc<Field> {
+        Self::try_new(modulus).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as new but returns Error::ZeroField instead of panicking.
+    pub fn try_new(modulus: &BigUint) -> Result<Arc<Field>> {
+        if modulus == &big(0) {
+            return Err(Error::ZeroField);
+        }
+        let mut fields = fields().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(field) = fields.get(modulus).and_then(Weak::upgrade) {
+            return Ok(field);
+        }
+        // A new modulus, a good time to forget the fields that have been dropped.
+        fields.retain(|_, field| field.strong_count() > 0);
+        let field = Arc::new(Field {
+            modulus: modulus.clone(),
+            reduction: Reduction::for_field(modulus),
+        });
+        fields.insert(modulus.clone(), Arc::downgrade(&field));
+        Ok(field)
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    // value mod the modulus, with the fast reduction for the NIST primes, see nist_primes.rs.
+    pub(crate) fn reduce(&self, value: BigUint) -> BigUint {
+        self.reduction.reduce(value, &self.modulus)
+    }
+}
+
+// There's only one Field per modulus, so the same Field is the same field.
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+impl Eq for Field {}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.modulus)
+    }
+}
@@ -0,0 +1,410 @@
+use crate::arithmetic::Arithmetic;
+use crate::clean_up::big;
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::{EllipticType, Inf, Point};
+use crate::field::Field;
+use crate::ladder::{self, Projective};
+use crate::modular_numbers::ModNum;
+use num_bigint::BigUint;
+use std::fmt;
+use std::sync::Arc;
+
+// Points in Jacobian coordinates, (X, Y, Z) stands for the affine point (X/Z^2, Y/Z^3). Adding and doubling
+//  in affine coordinates needs a division, a full extended Euclid in ModNum::mul_inv, every single time.
+//   Jacobian coordinates push all of those divisions into Z so a whole scalar multiplication only needs one,
+//  when the result is turned back into an EllipticType.
+// The formulas are the standard ones for y^2 = x^3 + a*x + b with any a, see
+//  https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html
+// The coordinates are elements of whichever Arithmetic backend with_arithmetic! picked, see arithmetic.rs.
+//  This is only used inside the crate, everything outside sees affine EllipticTypes.
+
+// Z = 0 is Infinity.
+#[derive(Debug, Clone)]
+pub(crate) struct Jacobian<F: Arithmetic> {
+    x: F::Elem,
+    y: F::Elem,
+    z: F::Elem,
+}
+
+// The field arithmetic and the bits of the curve the formulas need, converted once so they aren't converted
+//  for every operation. b3 = 3 * b is only used by the complete formulas in ladder.rs. shared_field is the
+//   curve's Field, for the EllipticTypes that come out at the end.
+#[derive(Debug, Clone)]
+pub(crate) struct Params<F: Arithmetic> {
+    pub(crate) field: F,
+    pub(crate) a: F::Elem,
+    pub(crate) b3: F::Elem,
+    pub(crate) shared_field: Arc<Field>,
+}
+
+impl<F: Arithmetic> Params<F> {
+    pub(crate) fn new(field: F, curve: &EllipticCurve) -> Params<F> {
+        let a = field.elem(curve.a().value());
+        let b3 = field.small(3, &field.elem(curve.b().value()));
+        Params {
+            field,
+            a,
+            b3,
+            shared_field: curve.shared_field().clone(),
+        }
+    }
+
+    // The EllipticType for affine x and y.
+    pub(crate) fn affine(&self, x: &F::Elem, y: &F::Elem) -> EllipticType {
+        EllipticType::Point(Point::from_trusted(
+            ModNum::in_field(&self.field.to_big(x), &self.shared_field),
+            ModNum::in_field(&self.field.to_big(y), &self.shared_field),
+        ))
+    }
+
+    pub(crate) fn infinity(&self) -> EllipticType {
+        EllipticType::Infinity(Inf::in_field(&self.shared_field))
+    }
+
+    pub(crate) fn mul(&self, left: &F::Elem, right: &F::Elem) -> F::Elem {
+        self.field.mul(left, right)
+    }
+    pub(crate) fn add(&self, left: &F::Elem, right: &F::Elem) -> F::Elem {
+        self.field.add(left, right)
+    }
+    pub(crate) fn sub(&self, left: &F::Elem, right: &F::Elem) -> F::Elem {
+        self.field.sub(left, right)
+    }
+    pub(crate) fn small(&self, times: u32, value: &F::Elem) -> F::Elem {
+        self.field.small(times, value)
+    }
+}
+
+impl<F: Arithmetic> Jacobian<F> {
+    pub(crate) fn infinity(params: &Params<F>) -> Jacobian<F> {
+        Jacobian {
+            x: params.field.one(),
+            y: params.field.one(),
+            z: params.field.zero(),
+        }
+    }
+
+    pub(crate) fn is_infinity(&self, params: &Params<F>) -> bool {
+        self.z == params.field.zero()
+    }
+
+    // (x, y) becomes (x, y, 1).
+    pub(crate) fn from_affine(point: &EllipticType, params: &Params<F>) -> Jacobian<F> {
+        match point {
+            EllipticType::Infinity(_) => Self::infinity(params),
+            EllipticType::Point(point) => Jacobian {
+                x: params.field.elem(point.x().value()),
+                y: params.field.elem(point.y().value()),
+                z: params.field.one(),
+            },
+        }
+    }
+
+    // Jacobian (X, Y, Z) is the same point as homogeneous projective (X*Z, Y, Z^3), both are (X/Z^2, Y/Z^3).
+    pub(crate) fn to_projective(&self, params: &Params<F>) -> Projective<F> {
+        if self.is_infinity(params) {
+            return Projective::identity(params);
+        }
+        let z_2 = params.mul(&self.z, &self.z);
+        Projective::new(
+            params.mul(&self.x, &self.z),
+            self.y.clone(),
+            params.mul(&z_2, &self.z),
+        )
+    }
+
+    // (X, Y, Z) becomes (X/Z^2, Y/Z^3). This is the one division.
+    pub(crate) fn to_affine(&self, params: &Params<F>) -> EllipticType {
+        if self.is_infinity(params) {
+            return params.infinity();
+        }
+        let z_inv = params.field.inv(&self.z);
+        let z_inv_2 = params.mul(&z_inv, &z_inv);
+        let x = params.mul(&self.x, &z_inv_2);
+        let y = params.mul(&self.y, &params.mul(&z_inv_2, &z_inv));
+        params.affine(&x, &y)
+    }
+
+    // -(X, Y, Z) = (X, -Y, Z)
+    fn neg(&self, params: &Params<F>) -> Jacobian<F> {
+        Jacobian {
+            x: self.x.clone(),
+            y: params.sub(&params.field.zero(), &self.y),
+            z: self.z.clone(),
+        }
+    }
+
+    // S = 4*X*Y^2, M = 3*X^2 + a*Z^4
+    // X' = M^2 - 2*S, Y' = M*(S - X') - 8*Y^4, Z' = 2*Y*Z
+    pub(crate) fn double(&self, params: &Params<F>) -> Jacobian<F> {
+        // Points with y = 0 are their own inverse so doubling them gives Infinity.
+        if self.is_infinity(params) || self.y == params.field.zero() {
+            return Self::infinity(params);
+        }
+        let y_2 = params.mul(&self.y, &self.y);
+        let s = params.small(4, &params.mul(&self.x, &y_2));
+        let z_2 = params.mul(&self.z, &self.z);
+        let m = params.add(
+            &params.small(3, &params.mul(&self.x, &self.x)),
+            &params.mul(&params.a, &params.mul(&z_2, &z_2)),
+        );
+        let x = params.sub(&params.mul(&m, &m), &params.small(2, &s));
+        let y = params.sub(
+            &params.mul(&m, &params.sub(&s, &x)),
+            &params.small(8, &params.mul(&y_2, &y_2)),
+        );
+        let z = params.small(2, &params.mul(&self.y, &self.z));
+        Jacobian { x, y, z }
+    }
+
+    // U1 = X1*Z2^2, U2 = X2*Z1^2, S1 = Y1*Z2^3, S2 = Y2*Z1^3, H = U2 - U1, R = S2 - S1
+    // X' = R^2 - H^3 - 2*U1*H^2, Y' = R*(U1*H^2 - X') - S1*H^3, Z' = H*Z1*Z2
+    pub(crate) fn add(&self, other: &Jacobian<F>, params: &Params<F>) -> Jacobian<F> {
+        if self.is_infinity(params) {
+            return other.clone();
+        }
+        if other.is_infinity(params) {
+            return self.clone();
+        }
+        let z1_2 = params.mul(&self.z, &self.z);
+        let z2_2 = params.mul(&other.z, &other.z);
+        let u1 = params.mul(&self.x, &z2_2);
+        let u2 = params.mul(&other.x, &z1_2);
+        let s1 = params.mul(&self.y, &params.mul(&z2_2, &other.z));
+        let s2 = params.mul(&other.y, &params.mul(&z1_2, &self.z));
+        // Same x, so either the same point or inverses of each other. The general formula divides by zero
+        //  for both.
+        if u1 == u2 {
+            if s1 == s2 {
+                return self.double(params);
+            }
+            return Self::infinity(params);
+        }
+        let h = params.sub(&u2, &u1);
+        let r = params.sub(&s2, &s1);
+        let h_2 = params.mul(&h, &h);
+        let h_3 = params.mul(&h_2, &h);
+        let u1_h_2 = params.mul(&u1, &h_2);
+        let x = params.sub(
+            &params.sub(&params.mul(&r, &r), &h_3),
+            &params.small(2, &u1_h_2),
+        );
+        let y = params.sub(
+            &params.mul(&r, &params.sub(&u1_h_2, &x)),
+            &params.mul(&s1, &h_3),
+        );
+        let z = params.mul(&h, &params.mul(&self.z, &other.z));
+        Jacobian { x, y, z }
+    }
+}
+
+// Left to right binary method, one doubling for every bit of exp and an addition for every 1 bit.
+pub(crate) fn mul_double_and_add<F: Arithmetic>(
+    base: &Jacobian<F>,
+    exp: &BigUint,
+    params: &Params<F>,
+) -> Jacobian<F> {
+    let mut result = Jacobian::infinity(params);
+    for i in (0..exp.bits()).rev() {
+        result = result.double(params);
+        if exp.bit(i) {
+            result = result.add(base, params);
+        }
+    }
+    result
+}
+
+// Reads exp width bits at a time. base^0 through base^(2^width - 1) are worked out first so each window is
+//  width doublings and a single addition.
+pub(crate) fn mul_fixed_window<F: Arithmetic>(
+    base: &Jacobian<F>,
+    exp: &BigUint,
+    width: u32,
+    params: &Params<F>,
+) -> Jacobian<F> {
+    let mut table = vec![Jacobian::infinity(params), base.clone()];
+    for i in 2..1usize << width {
+        table.push(table[i - 1].add(base, params));
+    }
+    let mut result = Jacobian::infinity(params);
+    for window in (0..exp.bits().div_ceil(width as u64)).rev() {
+        for _ in 0..width {
+            result = result.double(params);
+        }
+        result = result.add(&table[window_value(exp, window, width)], params);
+    }
+    result
+}
+
+// Width w non-adjacent form. exp is rewritten with digits that are either 0 or odd and less than 2^(w-1)
+//  in size, with at least w - 1 zeros after every non zero digit. Negative digits are free since negating a
+//   point is just negating y, so only the odd multiples base, base^3, ..., base^(2^(w-1) - 1) are needed and
+//  on average only one bit in w + 1 needs an addition.
+pub(crate) fn mul_wnaf<F: Arithmetic>(
+    base: &Jacobian<F>,
+    exp: &BigUint,
+    width: u32,
+    params: &Params<F>,
+) -> Jacobian<F> {
+    let doubled = base.double(params);
+    let mut odd_multiples = vec![base.clone()];
+    for i in 1..1usize << (width - 1).saturating_sub(1) {
+        odd_multiples.push(odd_multiples[i - 1].add(&doubled, params));
+    }
+    let mut result = Jacobian::infinity(params);
+    for &digit in wnaf(exp, width).iter().rev() {
+        result = result.double(params);
+        if digit > 0 {
+            result = result.add(&odd_multiples[(digit as usize) / 2], params);
+        } else if digit < 0 {
+            result = result.add(&odd_multiples[(-digit as usize) / 2].neg(params), params);
+        }
+    }
+    result
+}
+
+// The wNAF digits of exp, least significant first.
+fn wnaf(exp: &BigUint, width: u32) -> Vec<i64> {
+    let modulus = 1i64 << width;
+    let mut digits = Vec::with_capacity(exp.bits() as usize + 1);
+    let mut remaining = exp.clone();
+    while remaining != big(0) {
+        let digit = if remaining.bit(0) {
+            // The signed remainder mod 2^width, in (-2^(w-1), 2^(w-1)).
+            let low = window_value(&remaining, 0, width) as i64;
+            let digit = if low >= modulus / 2 {
+                low - modulus
+            } else {
+                low
+            };
+            if digit > 0 {
+                remaining -= BigUint::from(digit as u64);
+            } else {
+                remaining += BigUint::from((-digit) as u64);
+            }
+            digit
+        } else {
+            0
+        };
+        digits.push(digit);
+        remaining >>= 1;
+    }
+    digits
+}
+
+// Bits window * width through (window + 1) * width - 1 of exp as a number.
+fn window_value(exp: &BigUint, window: u64, width: u32) -> usize {
+    let start = window * width as u64;
+    (0..width as u64).fold(0, |value, i| value | (usize::from(exp.bit(start + i)) << i))
+}
+
+// A precomputed table for raising one fixed point, in practice init_point, to any power below 2^bits.
+//  Row i holds base^(j * 2^(width * i)) for every j < 2^width, so base^exp is just one table lookup and one
+//   addition per window of exp, with no doublings at all.
+// secret_rows is the table for pow_secret, in projective coordinates and with base added to every entry,
+//  base^(j * 2^(width * i) + 1). Infinity is (0, 1, 0) and multiplying by its zeros is quicker, so an
+//   exponent with lots of zero windows would otherwise be faster than a random one. Adding up one entry from
+//  every row gives base^(exp + rows), secret_offset is base^-rows to take the extra back off. The entries
+//   are randomized too, see Projective::randomize, otherwise the first entry of every row would have Z = 1.
+// The table keeps its own Params, EllipticCurve holds it as a dyn BaseTable so it doesn't need to know
+//  which field backend was used.
+pub(crate) struct FixedBaseTable<F: Arithmetic> {
+    params: Params<F>,
+    width: u32,
+    rows: Vec<Vec<Jacobian<F>>>,
+    secret_rows: Vec<Vec<Projective<F>>>,
+    secret_offset: Projective<F>,
+}
+
+const FIXED_BASE_WIDTH: u32 = 4;
+
+// The two ways of raising the base of a table, both return base^exp. Panics if exp is 2^bits or more.
+pub(crate) trait BaseTable: fmt::Debug + Send + Sync {
+    fn pow(&self, exp: &BigUint) -> EllipticType;
+    fn pow_secret(&self, exp: &BigUint) -> EllipticType;
+}
+
+impl<F: Arithmetic> FixedBaseTable<F> {
+    pub(crate) fn new(base: &EllipticType, bits: u64, params: Params<F>) -> FixedBaseTable<F> {
+        let width = FIXED_BASE_WIDTH;
+        let base = Jacobian::from_affine(base, &params);
+        let mut rows = Vec::with_capacity(bits.div_ceil(width as u64) as usize);
+        let mut row_base = base.clone();
+        for _ in 0..bits.div_ceil(width as u64) {
+            let mut row = vec![Jacobian::infinity(&params), row_base.clone()];
+            for j in 2..1usize << width {
+                row.push(row[j - 1].add(&row_base, &params));
+            }
+            // The next row starts at row_base^(2^width).
+            row_base = row[(1 << width) - 1].add(&row_base, &params);
+            rows.push(row);
+        }
+        let secret_rows = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|point| {
+                        point
+                            .add(&base, &params)
+                            .to_projective(&params)
+                            .randomize(&params)
+                    })
+                    .collect()
+            })
+            .collect();
+        let secret_offset = mul_double_and_add(&base, &BigUint::from(rows.len()), &params)
+            .neg(&params)
+            .to_projective(&params);
+        FixedBaseTable {
+            params,
+            width,
+            rows,
+            secret_rows,
+            secret_offset,
+        }
+    }
+}
+
+impl<F: Arithmetic> BaseTable for FixedBaseTable<F> {
+    fn pow(&self, exp: &BigUint) -> EllipticType {
+        let params = &self.params;
+        let windows = exp.bits().div_ceil(self.width as u64);
+        assert!(
+            windows <= self.rows.len() as u64,
+            "exponent is too large for the table"
+        );
+        let mut result = Jacobian::infinity(params);
+        for (window, row) in self.rows.iter().enumerate().take(windows as usize) {
+            result = result.add(&row[window_value(exp, window as u64, self.width)], params);
+        }
+        result.to_affine(params)
+    }
+
+    // Every row is used, even when the top windows of exp are zero, the entry is picked out with
+    //  ladder::select, which reads the whole row, and the additions are the complete ones.
+    fn pow_secret(&self, exp: &BigUint) -> EllipticType {
+        let params = &self.params;
+        assert!(
+            exp.bits() <= self.secret_rows.len() as u64 * self.width as u64,
+            "exponent is too large for the table"
+        );
+        let mut result = self.secret_offset.randomize(params);
+        for (window, row) in self.secret_rows.iter().enumerate() {
+            let entry = ladder::select(row, window_value(exp, window as u64, self.width), params);
+            result = result.add(&entry, params);
+        }
+        result.to_affine(params)
+    }
+}
+
+// The table is far too big to print, only its shape is shown.
+impl<F: Arithmetic> fmt::Debug for FixedBaseTable<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FixedBaseTable {{ width: {}, rows: {} }}",
+            self.width,
+            self.rows.len()
+        )
+    }
+}
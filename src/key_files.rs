@@ -0,0 +1,214 @@
+use crate::asn1::{self, Reader};
+use crate::el_gamal::{Curves, ElGamal};
+use crate::elliptic_curve::EllipticCurve;
+use crate::error::{Error, Result};
+use crate::keys::PublicKey;
+use crate::pem;
+use num_bigint::BigUint;
+
+// Reading and writing keys in the file formats other tools use.
+//  Private keys - PKCS#8 PrivateKeyInfo, https://www.rfc-editor.org/rfc/rfc5208, wrapping a SEC 1
+//   ECPrivateKey, https://www.rfc-editor.org/rfc/rfc5915. The bare ECPrivateKey is supported too since older
+//  tools write that instead.
+//  Public keys - SubjectPublicKeyInfo, https://www.rfc-editor.org/rfc/rfc5480
+// Only the named curves are supported. Custom curves would need their parameters written out in full which
+//  almost nothing else understands.
+
+// id-ecPublicKey, the algorithm for every elliptic curve key.
+const EC_PUBLIC_KEY: &[u64] = &[1, 2, 840, 10045, 2, 1];
+
+const PKCS8_LABEL: &str = "PRIVATE KEY";
+const SEC1_LABEL: &str = "EC PRIVATE KEY";
+const PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
+fn curve_oid(curve: &EllipticCurve) -> Result<&'static [u64]> {
+    Curves::identify(curve).oid().ok_or(Error::UnsupportedCurve)
+}
+
+fn curve_from_oid(oid: &[u64]) -> Result<Curves> {
+    Curves::by_oid(oid).ok_or(Error::UnsupportedCurve)
+}
+
+// AlgorithmIdentifier ::= SEQUENCE { id-ecPublicKey, namedCurve }
+fn algorithm_identifier(curve_oid: &[u64]) -> Vec<u8> {
+    asn1::sequence(&[asn1::oid(EC_PUBLIC_KEY), asn1::oid(curve_oid)])
+}
+
+// Reads an AlgorithmIdentifier and returns the curve it names.
+fn read_algorithm_identifier(reader: &mut Reader) -> Result<Curves> {
+    let mut algorithm = reader.read_sequence()?;
+    if algorithm.read_oid()? != EC_PUBLIC_KEY {
+        return Err(Error::InvalidEncoding("key isn't an elliptic curve key"));
+    }
+    let curve = curve_from_oid(&algorithm.read_oid()?)?;
+    algorithm.finish()?;
+    Ok(curve)
+}
+
+// ECPrivateKey ::= SEQUENCE {
+//   version        INTEGER 1,
+//   privateKey     OCTET STRING,
+//   parameters [0] namedCurve OPTIONAL,
+//   publicKey  [1] BIT STRING OPTIONAL }
+// Inside PKCS#8 the parameters are left out since the outer AlgorithmIdentifier already has them.
+fn ec_private_key(el_gamal: &ElGamal, with_parameters: bool) -> Result<Vec<u8>> {
+    let curve = el_gamal.curve();
+    let oid = curve_oid(curve)?;
+    let private_key = match el_gamal.private() {
+        Some(private_key) => private_key,
+        None => return Err(Error::MissingPrivateKey),
+    };
+    let mut members = vec![
+        asn1::integer(&BigUint::from(1u32)),
+        asn1::octet_string(&private_key.to_bytes(curve)),
+    ];
+    if with_parameters {
+        members.push(asn1::encode(asn1::context_tag(0), &asn1::oid(oid)));
+    }
+    members.push(asn1::encode(
+        asn1::context_tag(1),
+        &asn1::bit_string(&el_gamal.public().to_bytes()),
+    ));
+    Ok(asn1::sequence(&members))
+}
+
+// Reads an ECPrivateKey. outer_curve is the curve from the PKCS#8 wrapper, if there is one. If the key also
+//  names a curve or includes its public key they have to agree with everything else.
+fn read_ec_private_key(der: &[u8], outer_curve: Option<Curves>) -> Result<ElGamal> {
+    let mut reader = Reader::new(der);
+    let mut key = reader.read_sequence()?;
+    reader.finish()?;
+    if key.read_integer()? != BigUint::from(1u32) {
+        return Err(Error::InvalidEncoding("unsupported ECPrivateKey version"));
+    }
+    let scalar = BigUint::from_bytes_be(key.read_octet_string()?);
+    let mut curve = outer_curve;
+    if key.peek_tag() == Some(asn1::context_tag(0)) {
+        let mut parameters = Reader::new(key.read(asn1::context_tag(0))?);
+        let inner_curve = curve_from_oid(&parameters.read_oid()?)?;
+        parameters.finish()?;
+        if let Some(outer) = &curve {
+            if std::mem::discriminant(outer) != std::mem::discriminant(&inner_curve) {
+                return Err(Error::CurveMismatch);
+            }
+        }
+        curve = Some(inner_curve);
+    }
+    let curve = match curve {
+        Some(curve) => curve,
+        None => {
+            return Err(Error::InvalidEncoding(
+                "ECPrivateKey doesn't name its curve",
+            ))
+        }
+    };
+    let el_gamal = ElGamal::from_private_key(curve, scalar)?;
+    if key.peek_tag() == Some(asn1::context_tag(1)) {
+        let mut public_key = Reader::new(key.read(asn1::context_tag(1))?);
+        let public_key_bytes = public_key.read_bit_string()?;
+        public_key.finish()?;
+        let stored = PublicKey::from_bytes(public_key_bytes, el_gamal.curve())?;
+        if &stored != el_gamal.public() {
+            return Err(Error::InvalidEncoding(
+                "stored public key doesn't match the private key",
+            ));
+        }
+    }
+    key.finish()?;
+    Ok(el_gamal)
+}
+
+impl ElGamal {
+    // PrivateKeyInfo ::= SEQUENCE {
+    //   version             INTEGER 0,
+    //   privateKeyAlgorithm AlgorithmIdentifier,
+    //   privateKey          OCTET STRING containing an ECPrivateKey }
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>> {
+        let oid = curve_oid(self.curve())?;
+        Ok(asn1::sequence(&[
+            asn1::integer(&BigUint::from(0u32)),
+            algorithm_identifier(oid),
+            asn1::octet_string(&ec_private_key(self, false)?),
+        ]))
+    }
+
+    pub fn to_pkcs8_pem(&self) -> Result<String> {
+        Ok(pem::encode(PKCS8_LABEL, &self.to_pkcs8_der()?))
+    }
+
+    // Reads a PKCS#8 private key. Version 2 keys, RFC 5958, are accepted too but their extra attributes and
+    //  public key are ignored.
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<ElGamal> {
+        let mut reader = Reader::new(der);
+        let mut info = reader.read_sequence()?;
+        reader.finish()?;
+        let version = info.read_integer()?;
+        if version > BigUint::from(1u32) {
+            return Err(Error::InvalidEncoding("unsupported PKCS#8 version"));
+        }
+        let curve = read_algorithm_identifier(&mut info)?;
+        let el_gamal = read_ec_private_key(info.read_octet_string()?, Some(curve))?;
+        // [0] attributes and [1] public key.
+        if info.peek_tag() == Some(asn1::context_tag(0)) {
+            info.read(asn1::context_tag(0))?;
+        }
+        if info.peek_tag() == Some(0x81) {
+            info.read(0x81)?;
+        }
+        info.finish()?;
+        Ok(el_gamal)
+    }
+
+    pub fn from_pkcs8_pem(text: &str) -> Result<ElGamal> {
+        Self::from_pkcs8_der(&pem::decode(PKCS8_LABEL, text)?)
+    }
+
+    // The bare SEC 1 ECPrivateKey, which names its own curve.
+    pub fn to_sec1_der(&self) -> Result<Vec<u8>> {
+        ec_private_key(self, true)
+    }
+
+    pub fn to_sec1_pem(&self) -> Result<String> {
+        Ok(pem::encode(SEC1_LABEL, &self.to_sec1_der()?))
+    }
+
+    pub fn from_sec1_der(der: &[u8]) -> Result<ElGamal> {
+        read_ec_private_key(der, None)
+    }
+
+    pub fn from_sec1_pem(text: &str) -> Result<ElGamal> {
+        Self::from_sec1_der(&pem::decode(SEC1_LABEL, text)?)
+    }
+
+    // SubjectPublicKeyInfo ::= SEQUENCE {
+    //   algorithm        AlgorithmIdentifier,
+    //   subjectPublicKey BIT STRING containing the uncompressed SEC 1 point }
+    pub fn to_public_key_der(&self) -> Result<Vec<u8>> {
+        let oid = curve_oid(self.curve())?;
+        Ok(asn1::sequence(&[
+            algorithm_identifier(oid),
+            asn1::bit_string(&self.public().to_bytes()),
+        ]))
+    }
+
+    pub fn to_public_key_pem(&self) -> Result<String> {
+        Ok(pem::encode(PUBLIC_KEY_LABEL, &self.to_public_key_der()?))
+    }
+
+    // Reads a SubjectPublicKeyInfo into an encrypt only ElGamal.
+    pub fn from_public_key_der(der: &[u8]) -> Result<ElGamal> {
+        let mut reader = Reader::new(der);
+        let mut info = reader.read_sequence()?;
+        reader.finish()?;
+        let curve = read_algorithm_identifier(&mut info)?;
+        let public_key_bytes = info.read_bit_string()?;
+        info.finish()?;
+        let curve = curve.try_into_curve()?;
+        let public_key = PublicKey::from_bytes(public_key_bytes, &curve)?;
+        ElGamal::from_public_key(Curves::Custom(curve), public_key.point().clone())
+    }
+
+    pub fn from_public_key_pem(text: &str) -> Result<ElGamal> {
+        Self::from_public_key_der(&pem::decode(PUBLIC_KEY_LABEL, text)?)
+    }
+}
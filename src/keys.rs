@@ -0,0 +1,94 @@
+use crate::clean_up::{big, byte_len, to_fixed_bytes};
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::EllipticType;
+use crate::error::{Error, Result};
+use num_bigint::BigUint;
+use std::fmt;
+
+// A private key is just a number, the scalar, from [1, n). It doesn't hold on to the curve it belongs to, so
+//  every function that needs the curve takes it as an argument, the same as encrypt and decrypt do.
+#[derive(Clone, Eq, PartialEq)]
+pub struct PrivateKey {
+    scalar: BigUint,
+}
+
+// A public key is the point init_point^private_key. It is checked to be a valid point on its curve when it is
+//  created so a key received from someone else can't be used to attack the private key.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PublicKey {
+    point: EllipticType,
+}
+
+impl PrivateKey {
+    // Creates a private key after checking that the scalar is in [1, n), or [1, field) if the order of the
+    //  curve isn't known.
+    pub fn try_new(scalar: BigUint, curve: &EllipticCurve) -> Result<PrivateKey> {
+        if scalar == big(0) || &scalar >= curve.scalar_bound() {
+            return Err(Error::InvalidPrivateKey);
+        }
+        Ok(PrivateKey { scalar })
+    }
+
+    // Rebuilds a private key from the bytes produced by to_bytes.
+    pub fn from_bytes(bytes: &[u8], curve: &EllipticCurve) -> Result<PrivateKey> {
+        if bytes.len() != byte_len(curve.scalar_bound()) {
+            return Err(Error::InvalidEncoding(
+                "private key has the wrong number of bytes for the curve",
+            ));
+        }
+        Self::try_new(BigUint::from_bytes_be(bytes), curve)
+    }
+
+    // Writes the scalar as a fixed width big-endian number, the same width as n.
+    pub fn to_bytes(&self, curve: &EllipticCurve) -> Vec<u8> {
+        to_fixed_bytes(&self.scalar, byte_len(curve.scalar_bound()))
+    }
+
+    pub fn scalar(&self) -> &BigUint {
+        &self.scalar
+    }
+
+    // Calculates the public key that goes with this private key, init_point^scalar.
+    pub fn public_key(&self, curve: &EllipticCurve) -> PublicKey {
+        PublicKey {
+            point: curve.init_point_pow_secret(&self.scalar),
+        }
+    }
+}
+
+// Printing a private key, or an ElGamal holding one, with {:?} would otherwise put the scalar in logs and
+//  panic messages. It's written as PrivateKey { .. }, use scalar or to_bytes to get at it on purpose.
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey").finish_non_exhaustive()
+    }
+}
+
+impl PublicKey {
+    // Creates a public key after checking that the point is on the curve, isn't Infinity, and, if the order of
+    //  the curve is known, that it is in the group generated by init_point, see
+    //   EllipticCurve::validate_public_key.
+    pub fn try_new(point: EllipticType, curve: &EllipticCurve) -> Result<PublicKey> {
+        curve.validate_public_key(&point)?;
+        Ok(PublicKey { point })
+    }
+
+    // Rebuilds a public key from SEC 1 encoded bytes, either compressed or uncompressed.
+    pub fn from_bytes(bytes: &[u8], curve: &EllipticCurve) -> Result<PublicKey> {
+        Self::try_new(EllipticType::from_sec1_bytes(bytes, curve)?, curve)
+    }
+
+    // Writes the point out in the uncompressed SEC 1 format, 0x04 followed by x and y.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.point.to_sec1_bytes(false)
+    }
+
+    // Writes the point out in the compressed SEC 1 format, 0x02 or 0x03 followed by x.
+    pub fn to_compressed_bytes(&self) -> Vec<u8> {
+        self.point.to_sec1_bytes(true)
+    }
+
+    pub fn point(&self) -> &EllipticType {
+        &self.point
+    }
+}
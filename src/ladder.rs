@@ -0,0 +1,161 @@
+use crate::arithmetic::Arithmetic;
+use crate::clean_up::big;
+use crate::elliptic_point::EllipticType;
+use crate::jacobian::Params;
+use num_bigint::{BigUint, RandBigInt};
+
+// Scalar multiplication for secret exponents, private keys and nonces. pow and group_op decide what to do
+//  based on the bits of the exponent and on whether two points are equal or inverses, so the time they take
+//   says something about the exponent. Everything here does the same sequence of operations no matter what
+//  the exponent is.
+//   - The Montgomery ladder does one addition and one doubling for every bit, and always the same number of
+//     bits, the bit only decides which of two points goes where and that is done with masks, not branches.
+//   - Points are in homogeneous projective coordinates, (X, Y, Z) is (X/Z, Y/Z), and are added with the
+//     complete formulas from Renes, Costello, and Batina, https://eprint.iacr.org/2015/1060 algorithm 1.
+//     They give the right answer for every pair of points, equal, inverse, or Infinity, with no special cases.
+// The Montgomery field backend always does the same work, but BigArithmetic, used for unusual fields, doesn't, a
+//  BigUint multiplication can be quicker when a number happens to have leading zeros. The coordinates are
+//   randomized, see Projective::randomize, so those small differences aren't tied to the exponent. The
+//  timing test in tests/timing.rs measures what is left.
+
+// Infinity is (0, 1, 0).
+#[derive(Debug, Clone)]
+pub(crate) struct Projective<F: Arithmetic> {
+    x: F::Elem,
+    y: F::Elem,
+    z: F::Elem,
+}
+
+impl<F: Arithmetic> Projective<F> {
+    pub(crate) fn new(x: F::Elem, y: F::Elem, z: F::Elem) -> Projective<F> {
+        Projective { x, y, z }
+    }
+
+    pub(crate) fn identity(params: &Params<F>) -> Projective<F> {
+        Projective {
+            x: params.field.zero(),
+            y: params.field.one(),
+            z: params.field.zero(),
+        }
+    }
+
+    pub(crate) fn from_affine(point: &EllipticType, params: &Params<F>) -> Projective<F> {
+        match point {
+            EllipticType::Infinity(_) => Self::identity(params),
+            EllipticType::Point(point) => Projective {
+                x: params.field.elem(point.x().value()),
+                y: params.field.elem(point.y().value()),
+                z: params.field.one(),
+            },
+        }
+    }
+
+    // (X, Y, Z) becomes (X/Z, Y/Z). The result is about to be handed back so branching on it is fine.
+    pub(crate) fn to_affine(&self, params: &Params<F>) -> EllipticType {
+        if self.z == params.field.zero() {
+            return params.infinity();
+        }
+        let z_inv = params.field.inv(&self.z);
+        params.affine(&params.mul(&self.x, &z_inv), &params.mul(&self.y, &z_inv))
+    }
+
+    // The same point with X, Y, and Z all multiplied by a random non zero lambda. Without this the same
+    //  exponent goes through exactly the same numbers every time, and how long BigUint takes depends a
+    //   little on the numbers.
+    pub(crate) fn randomize(&self, params: &Params<F>) -> Projective<F> {
+        let field = &params.field;
+        let lambda = rand::thread_rng().gen_biguint_range(&big(1), field.modulus());
+        let lambda = field.elem(&lambda);
+        Projective {
+            x: params.mul(&self.x, &lambda),
+            y: params.mul(&self.y, &lambda),
+            z: params.mul(&self.z, &lambda),
+        }
+    }
+
+    // Algorithm 1 of Renes, Costello, and Batina, complete addition for y^2 = x^3 + a*x + b with any a. Works
+    //  for doubling too, just pass the same point twice.
+    pub(crate) fn add(&self, other: &Projective<F>, params: &Params<F>) -> Projective<F> {
+        let mul = |l: &F::Elem, r: &F::Elem| params.mul(l, r);
+        let add = |l: &F::Elem, r: &F::Elem| params.add(l, r);
+        let sub = |l: &F::Elem, r: &F::Elem| params.sub(l, r);
+        let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+        let (x2, y2, z2) = (&other.x, &other.y, &other.z);
+
+        let t0 = mul(x1, x2);
+        let t1 = mul(y1, y2);
+        let t2 = mul(z1, z2);
+        let t3 = sub(&mul(&add(x1, y1), &add(x2, y2)), &add(&t0, &t1));
+        let t4 = sub(&mul(&add(x1, z1), &add(x2, z2)), &add(&t0, &t2));
+        let t5 = sub(&mul(&add(y1, z1), &add(y2, z2)), &add(&t1, &t2));
+        let z3 = add(&mul(&params.a, &t4), &mul(&params.b3, &t2));
+        let x3 = sub(&t1, &z3);
+        let z3 = add(&t1, &z3);
+        let y3 = mul(&x3, &z3);
+        let t1 = params.small(3, &t0);
+        let t2 = mul(&params.a, &t2);
+        let t4 = mul(&params.b3, &t4);
+        let t1 = add(&t1, &t2);
+        let t2 = mul(&params.a, &sub(&t0, &t2));
+        let t4 = add(&t4, &t2);
+        let y3 = add(&y3, &mul(&t1, &t4));
+        let x3 = sub(&mul(&t3, &x3), &mul(&t5, &t4));
+        let z3 = add(&mul(&t5, &z3), &mul(&t3, &t1));
+        Projective {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+// Swaps left and right when mask is all ones and leaves them alone when it is 0, see Arithmetic::cswap.
+fn cswap<F: Arithmetic>(
+    left: &mut Projective<F>,
+    right: &mut Projective<F>,
+    mask: u64,
+    params: &Params<F>,
+) {
+    params.field.cswap(&mut left.x, &mut right.x, mask);
+    params.field.cswap(&mut left.y, &mut right.y, mask);
+    params.field.cswap(&mut left.z, &mut right.z, mask);
+}
+
+// Picks entries[index] by looking at every entry, so which one was wanted can't be told from the memory that
+//  was touched or the time taken.
+pub(crate) fn select<F: Arithmetic>(
+    entries: &[Projective<F>],
+    index: usize,
+    params: &Params<F>,
+) -> Projective<F> {
+    let mut selected = entries[0].clone();
+    for (i, entry) in entries.iter().enumerate().skip(1) {
+        // All ones when i == index, without comparing them with a branch.
+        let difference = (i ^ index) as u64;
+        let mask = ((difference | difference.wrapping_neg()) >> 63).wrapping_sub(1);
+        let mut candidate = entry.clone();
+        cswap(&mut selected, &mut candidate, mask, params);
+    }
+    selected
+}
+
+// base^exp with the Montgomery ladder. Always runs for bits steps, which should come from the curve and not
+//  from exp, so the length of exp isn't given away either. exp must be less than 2^bits.
+pub(crate) fn mul_ladder<F: Arithmetic>(
+    base: &Projective<F>,
+    exp: &BigUint,
+    bits: u64,
+    params: &Params<F>,
+) -> Projective<F> {
+    // r_1 is always r_0 + base.
+    let mut r_0 = Projective::identity(params);
+    let mut r_1 = base.randomize(params);
+    for i in (0..bits).rev() {
+        let mask = 0u64.wrapping_sub(u64::from(exp.bit(i)));
+        cswap(&mut r_0, &mut r_1, mask, params);
+        r_1 = r_0.add(&r_1, params);
+        r_0 = r_0.add(&r_0, params);
+        cswap(&mut r_0, &mut r_1, mask, params);
+    }
+    r_0
+}
@@ -1,5 +1,29 @@
+mod arithmetic;
+pub mod asn1;
+pub mod chacha20_poly1305;
+pub mod ciphertext;
 pub mod clean_up;
+pub mod curve_builder;
+pub mod curve_point;
+pub mod ecdh;
+pub mod ecdsa;
+pub mod ecies;
 pub mod el_gamal;
 pub mod elliptic_curve;
 pub mod elliptic_point;
+pub mod error;
+pub mod field;
+mod jacobian;
+pub mod key_files;
+pub mod keys;
+mod ladder;
 pub mod modular_numbers;
+mod montgomery;
+mod named_curves;
+mod nist_primes;
+pub mod pem;
+pub mod point_counting;
+pub mod primes;
+pub mod rfc6979;
+mod schoof;
+pub mod sha2;
@@ -5,6 +5,7 @@ fn main() {
     // 256 - Curves::TwoFiveSix
     // 384 - Curves::ThreeEightFour
     // 521 - Curves::FiveTwoOne
+    // Other named curves - Curves::all() lists them and Curves::by_name finds one, e.g. "secp256k1"
     // Custom - Create you're own curve object and pass it in. The field of your custom curve must be greater
     //           than 256^2 + 1
     let twofivesix = ElGamal::new(Curves::TwoFiveSix);
@@ -56,11 +57,11 @@ fn main() {
         .to_string();
 
     // To encode you must call the encrypt function and pass the public key, the curve, and the plain text
-    let cipher_text = encrypt(&twofivesix.public_key(), &twofivesix.curve(), &plain_text);
+    let cipher_text = encrypt(twofivesix.public_key(), twofivesix.curve(), &plain_text);
 
     // To decode you call decrypt with the private key, curve, and cypher text. The program will most likely
     //  panic if the wrong private key is used b/c that could lead to a utf8 encoding error. Possibly you will
     //   get text back with the wrong private key but it will not be the original.
-    let message = decrypt(&twofivesix.private_key(), twofivesix.curve(), &cipher_text);
+    let message = decrypt(twofivesix.private_key(), twofivesix.curve(), &cipher_text);
     println!("{}", message);
 }
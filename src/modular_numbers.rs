@@ -1,26 +1,50 @@
 use crate::clean_up::big;
+use crate::error::Result;
+use crate::field::Field;
 use num_bigint::BigUint;
 use num_integer::Integer;
 use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::sync::Arc;
 
 //Modular number, private fields to prevent creating invalid modular numbers.
 // Aka fields of 0 or values greater than the field.
-#[derive(Debug, Eq, PartialEq)]
+// The field is shared, see field.rs, so every number in a field points at the same Field rather than having
+//  its own copy of the modulus, and checking two numbers are from the same field is a pointer comparison.
+// The arithmetic is done on BigUints, only scalar multiplication uses the fixed width backends in arithmetic.rs.
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ModNum {
     value: BigUint,
-    field: BigUint,
+    field: Arc<Field>,
+}
+
+// The algorithms sqrt can use, see sqrt_with.
+//   TonelliShanks - the usual choice, needs only a few multiplications for fields that are 3 mod 4.
+//   Cipolla - works in a quadratic extension of the field, which is faster when field - 1 is divisible by a
+//    large power of two.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum SqrtMethod {
+    #[default]
+    TonelliShanks,
+    Cipolla,
 }
 
 impl ModNum {
     //creates a new modular number and forces the value to conform.
     // panics if the field is zero.
     pub fn new(value: &BigUint, field: &BigUint) -> ModNum {
-        assert!(
-            field != &big(0),
-            "Cannot create a modular number with field of  0."
-        );
+        Self::try_new(value, field).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Same as new but returns Error::ZeroField instead of panicking.
+    pub fn try_new(value: &BigUint, field: &BigUint) -> Result<ModNum> {
+        Ok(Self::in_field(value, &Field::try_new(field)?))
+    }
+
+    // Same as new for a field that has already been looked up, which saves finding it again.
+    pub fn in_field(value: &BigUint, field: &Arc<Field>) -> ModNum {
         ModNum {
-            value: value % field,
+            value: field.reduce(value.clone()),
             field: field.clone(),
         }
     }
@@ -28,13 +52,29 @@ impl ModNum {
     pub fn value(&self) -> &BigUint {
         &self.value
     }
+    // The modulus of the field.
     pub fn field(&self) -> &BigUint {
+        self.field.modulus()
+    }
+    pub fn shared_field(&self) -> &Arc<Field> {
         &self.field
     }
 
-    // Chose to create add function instead of overriding because the standard function requires
-    //  consuming the left hand side. This prevents potentially copying very large numbers with the
-    //   downside of being slightly unreadable.
+    // A ModNum in the same field as self. value must already be reduced.
+    fn with_value(&self, value: BigUint) -> ModNum {
+        ModNum {
+            value,
+            field: self.field.clone(),
+        }
+    }
+
+    fn reduce(&self, value: BigUint) -> BigUint {
+        self.field.reduce(value)
+    }
+
+    // The +, -, *, /, and unary - operators are implemented below for both ModNum and &ModNum so
+    //  x * x * x + a * x + b works without consuming anything when written with references. add and mul
+    //   are kept as methods for the code that already uses them.
     pub fn add(&self, other: &Self) -> Self {
         // Ensures self/other are from the same field. Operations are undefined for values from
         //  different fields
@@ -42,37 +82,49 @@ impl ModNum {
             self.field == other.field,
             "Can't add Modular Numbers with different fields"
         );
-        let value: BigUint = (&self.value + &other.value) % &self.field;
-        ModNum {
-            value: value,
-            field: self.field.clone(),
-        }
+        let value: BigUint = (&self.value + &other.value) % self.field();
+        self.with_value(value)
     }
 
-    // Chose to create mul function instead of overriding because the standard function requires
-    //  consuming the left hand side. This prevents potentially copying very large numbers with the
-    //   downside of being slightly unreadable.
     pub fn mul(&self, other: &Self) -> Self {
         // Ensures self/other are from the same field. Operations are undefined for values from
         //  different fields
         assert!(
             self.field == other.field,
-            "Can't add Modular Numbers with different fields"
+            "Can't multiply Modular Numbers with different fields"
         );
-        let value: BigUint = (&self.value * &other.value) % &self.field;
-        ModNum {
-            value,
-            field: self.field.clone(),
-        }
+        let value = self.reduce(&self.value * &other.value);
+        self.with_value(value)
     }
 
     // Returns the additive inverse of self - self + self.add_inv() = 0
     pub fn add_inv(&self) -> Self {
-        let value: BigUint = (&self.field - &self.value) % &self.field;
-        ModNum {
-            value,
-            field: self.field().clone(),
-        }
+        let value: BigUint = (self.field() - &self.value) % self.field();
+        self.with_value(value)
+    }
+
+    // Returns self - other.
+    pub fn sub(&self, other: &Self) -> Self {
+        assert!(
+            self.field == other.field,
+            "Can't subtract Modular Numbers with different fields"
+        );
+        let value = if self.value >= other.value {
+            &self.value - &other.value
+        } else {
+            self.field() - &other.value + &self.value
+        };
+        self.with_value(value)
+    }
+
+    // Returns self / other, panics if other is 0 since 0 doesn't have an inverse.
+    pub fn div(&self, other: &Self) -> Self {
+        assert!(
+            self.field == other.field,
+            "Can't divide Modular Numbers with different fields"
+        );
+        assert!(other.value != big(0), "Can't divide by zero");
+        self.mul(&other.mul_inv())
     }
 
     // Returns the multiplicate inverse of self - self * self.mul_inv() = 1
@@ -80,13 +132,13 @@ impl ModNum {
     //    https://en.wikipedia.org/wiki/Extended_Euclidean_algorithm
     pub fn mul_inv(&self) -> Self {
         let mut r_one = self.value.clone();
-        let mut r_zero = self.field.clone();
+        let mut r_zero = self.field().clone();
         let mut t_zero = big(0);
         let mut t_one = big(1);
-        let mul_field = &self.field;
+        let mul_field = self.field();
         loop {
             // Div_mod_floor returns (quotient, remainder)
-            let q = (&r_zero).div_mod_floor(&r_one);
+            let q = r_zero.div_mod_floor(&r_one);
             r_zero = r_one.clone();
             r_one = q.1.clone();
             let test_val = q.0 * &t_one;
@@ -102,131 +154,213 @@ impl ModNum {
             }
         }
 
-        ModNum {
-            value: t_zero,
-            field: self.field.clone(),
-        }
+        self.with_value(t_zero)
     }
 
-    // Returns self^exp - uses the recursive algorithm implementation
+    // Returns self^exp - scans the bits of exp from the top, squaring for every bit and multiplying by self for
+    //  every 1 bit.
     pub fn pow(&self, exp: &BigUint) -> Self {
-        if exp == &big(0) {
-            return ModNum {
-                value: big(1),
-                field: self.field.clone(),
-            };
-        } else if exp == &big(1) {
-            return ModNum {
-                value: self.value.clone(),
-                field: self.field.clone(),
-            };
-        } else {
-            if exp % &big(2) == big(0) {
-                let z = self.pow(&(exp / &big(2)));
-                return z.mul(&z);
-            } else {
-                let z = self.pow(&((exp - &big(1)) / &big(2)));
-
-                return z.mul(&z).mul(&self);
+        let mut value = big(1) % self.field();
+        for i in (0..exp.bits()).rev() {
+            value = self.reduce(&value * &value);
+            if exp.bit(i) {
+                value = self.reduce(value * &self.value);
             }
         }
+        self.with_value(value)
     }
 
-    // Returns the sqrt(self) if it exists.
-    //  Returns 0 if the sqrt(self) doesn't exist.
-    // In a finite field only ~50% of numbers have a square root or "is a quadratic residue of the field."
-    pub fn sqrt(&self) -> Self {
-        let sqrt_field = &self.field;
+    // Returns 1 if self is a non-zero square, -1 if it isn't a square, and 0 if it's 0. Uses Euler's criterion,
+    //  self^((field - 1)/2), which is only meaningful when the field is an odd prime.
+    pub fn legendre_symbol(&self) -> i8 {
+        if self.value == big(0) {
+            return 0;
+        }
+        let symbol = self.pow(&((self.field() - 1u32) >> 1));
+        if symbol.value == big(1) {
+            1
+        } else {
+            -1
+        }
+    }
 
-        // this function is used first to check that self is a quadratic residue aka has a square root.
-        fn legendre_symbol(value: &BigUint, field: &BigUint) -> i8 {
-            // value^((field -1)/2) mod field
-            let leg_sym = value.modpow(&((field - &big(1)) / big(2)), field);
+    // Whether self has a square root, 0 counts as a square.
+    pub fn is_square(&self) -> bool {
+        self.legendre_symbol() != -1
+    }
 
-            // if value^((field -1)/2) mod field is congruent to -1 -(field -1) return -1
-            // Have to use (field - 1) instead of -1 because these are unsigned integers.
-            if leg_sym == field - &big(1) {
-                return -1;
-            }
-            // if value^((field -1)/2) mod field is congruent to 1 it is a quadratic residue
-            else if leg_sym == big(1) {
-                return 1;
-            }
-            // This is a placeholder value thats only use is that it is not 1 or -1.
-            else {
-                return 2;
-            }
-        }
-        // Check to determine if self is a quadratic residue
-        //  If self isn't a quadratic residue return 0.
-        if legendre_symbol(&self.value, sqrt_field) != 1 {
-            return ModNum::new(&big(0), &self.field);
+    // Returns a square root of self using SqrtMethod::default(), or None if self doesn't have one. In a prime
+    //  field only about half of the numbers have a square root, or "are quadratic residues of the field".
+    //   When there is a root the other one is its additive inverse, callers that need a particular one, like
+    //  the even or odd y for a compressed point, pick between them.
+    // The field has to be prime. For any other modulus the answer is checked and None is returned rather than
+    //  a wrong root, but a root that does exist might not be found.
+    pub fn sqrt(&self) -> Option<Self> {
+        self.sqrt_with(SqrtMethod::default())
+    }
+
+    // Same as sqrt but with the given method. Both find a root whenever there is one, they may not find the
+    //  same one.
+    pub fn sqrt_with(&self, method: SqrtMethod) -> Option<Self> {
+        // Every number is its own square root mod 2, and 0 is its own square root in any field.
+        if self.value == big(0) || self.field() == &big(2) {
+            return Some(self.clone());
         }
-        // sqrt(0) = 0
-        if self.value == big(0) {
-            return ModNum::new(&big(0), &self.field);
-        }
-        // If the field is congruent to 3 mod 4 then we can directly calculate the sqrt with this formula.
-        if self.field.mod_floor(&big(4)) == big(3) {
-            // self.value^((field+1)/4) mod field
-            let value = (&self.value).modpow(&((&self.field + big(1)) / &big(4)), &self.field);
-            return ModNum {
-                value,
-                field: self.field.clone(),
-            };
+        if self.legendre_symbol() != 1 {
+            return None;
         }
+        let root = match method {
+            SqrtMethod::TonelliShanks => self.tonelli_shanks()?,
+            SqrtMethod::Cipolla => self.cipolla()?,
+        };
+        (&root * &root == *self).then_some(root)
+    }
 
-        let mut s = &self.field - big(1);
-        let mut e = big(1);
+    // https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm
+    //  Writes field - 1 = q * 2^s with q odd. r = self^((q+1)/2) is a root of self * t where t = self^q, and
+    //   t's order is a power of two. Every round multiplies r by a power of c, a non-residue raised to q, which
+    //  makes the order of t smaller until t is 1 and r is a root of self.
+    // Fields that are 3 mod 4 have s = 1 so r is already the root, self^((field + 1)/4), and no non-residue
+    //  is needed.
+    fn tonelli_shanks(&self) -> Option<Self> {
+        let one = self.with_value(big(1));
+        let field_minus_one = self.field() - 1u32;
+        let s = field_minus_one.trailing_zeros()?;
+        let q = &field_minus_one >> s;
+        let mut r = self.pow(&((&q + 1u32) >> 1));
+        if s == 1 {
+            return Some(r);
+        }
 
-        while s.mod_floor(&big(2)) == big(0) {
-            s = s.mod_floor(&big(2));
-            e = e + &big(1);
+        // Half of the numbers in a prime field are non-residues so this doesn't take long, but it would
+        //  never end for a modulus that isn't prime and has none, so give up after trying all of them.
+        let mut z = self.with_value(big(2));
+        while z.legendre_symbol() != -1 {
+            z = &z + &one;
+            if z.value == big(0) {
+                return None;
+            }
         }
-        let mut n = big(2);
 
-        // Looking for a value of n congruent to field -1 mod field
-        while legendre_symbol(&n, &self.field) != -1 {
-            n = n + &big(1);
+        let mut m = s;
+        let mut c = z.pow(&q);
+        let mut t = self.pow(&q);
+        while t != one {
+            // The smallest i with t^(2^i) = 1, which is less than m in a prime field.
+            let mut i = 0;
+            let mut t_to_the_2_i = t.clone();
+            while t_to_the_2_i != one {
+                t_to_the_2_i = &t_to_the_2_i * &t_to_the_2_i;
+                i += 1;
+                if i == m {
+                    return None;
+                }
+            }
+            // b = c^(2^(m - i - 1))
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = &b * &b;
+            }
+            m = i;
+            c = &b * &b;
+            t = &t * &c;
+            r = &r * &b;
         }
-        let mut xenon = (&self.value).modpow(&((&s + &big(1)) / &big(2)), sqrt_field);
-        let mut baby = self.value.modpow(&s, sqrt_field);
-        let mut garnish = n.modpow(&s, sqrt_field);
+        Some(r)
+    }
 
-        loop {
-            let mut t = baby.clone();
-            let mut m = big(0);
+    // https://en.wikipedia.org/wiki/Cipolla%27s_algorithm
+    //  Finds an a where w = a^2 - self isn't a square and works in the field extended with sqrt(w), where
+    //   numbers are x + y*sqrt(w). There (a + sqrt(w))^((field + 1)/2) is a root of self with no sqrt(w)
+    //  part. Slower than Tonelli-Shanks when field - 1 has only a few factors of 2, but it doesn't slow down
+    //   when it has many.
+    fn cipolla(&self) -> Option<Self> {
+        let one = self.with_value(big(1));
+        let mut a = self.with_value(big(0));
+        let w = loop {
+            let w = &(&a * &a) - self;
+            if w.legendre_symbol() == -1 {
+                break w;
+            }
+            a = &a + &one;
+            if a.value == big(0) {
+                return None;
+            }
+        };
 
-            for i in 0..256 {
-                if t == big(1) {
-                    break;
-                }
-                t = t.modpow(&big(2), sqrt_field);
-                m = m + &big(1);
-                if i == 256 {
-                    println!("You should increase the size of for loop in sqrt function!");
-                    panic!();
-                }
-                if m == big(0) {
-                    println!("xenon is {:?}", xenon);
-                    return ModNum::new(&xenon, &self.field);
-                }
-                let garnishes = (&garnish).modpow(
-                    &(&big(2).modpow(&(&e - &m - &big(1)), sqrt_field)),
-                    sqrt_field,
-                );
-                garnish = (&garnishes).modpow(&big(2), sqrt_field);
-                xenon = (xenon * garnishes).mod_floor(sqrt_field);
-                baby = (baby * &garnish).mod_floor(sqrt_field);
-                e = m.clone();
+        // (x0 + x1*sqrt(w)) * (y0 + y1*sqrt(w)) = (x0*y0 + x1*y1*w) + (x0*y1 + x1*y0)*sqrt(w)
+        let ext_mul = |(x0, x1): &(ModNum, ModNum), (y0, y1): &(ModNum, ModNum)| {
+            (x0 * y0 + &(x1 * y1) * &w, x0 * y1 + x1 * y0)
+        };
+        let exp: BigUint = (self.field() + 1u32) >> 1;
+        let base = (a, one.clone());
+        let mut value = (one, self.with_value(big(0)));
+        for i in (0..exp.bits()).rev() {
+            value = ext_mul(&value, &value);
+            if exp.bit(i) {
+                value = ext_mul(&value, &base);
             }
         }
+        (value.1.value == big(0)).then_some(value.0)
     }
 }
 
-impl Clone for ModNum {
-    fn clone(&self) -> ModNum {
-        ModNum::new(&self.value(), &self.field())
+// Every combination of owned and borrowed operands for each operator, all going through the methods above.
+//  The owned forms are there so results can be chained, (x * x) * x, without writing & everywhere.
+macro_rules! mod_num_op {
+    ($op:ident, $method:ident, $assign:ident, $assign_method:ident) => {
+        impl $op<&ModNum> for &ModNum {
+            type Output = ModNum;
+            fn $method(self, other: &ModNum) -> ModNum {
+                ModNum::$method(self, other)
+            }
+        }
+        impl $op<ModNum> for &ModNum {
+            type Output = ModNum;
+            fn $method(self, other: ModNum) -> ModNum {
+                ModNum::$method(self, &other)
+            }
+        }
+        impl $op<&ModNum> for ModNum {
+            type Output = ModNum;
+            fn $method(self, other: &ModNum) -> ModNum {
+                ModNum::$method(&self, other)
+            }
+        }
+        impl $op<ModNum> for ModNum {
+            type Output = ModNum;
+            fn $method(self, other: ModNum) -> ModNum {
+                ModNum::$method(&self, &other)
+            }
+        }
+        impl $assign<&ModNum> for ModNum {
+            fn $assign_method(&mut self, other: &ModNum) {
+                *self = ModNum::$method(self, other);
+            }
+        }
+        impl $assign<ModNum> for ModNum {
+            fn $assign_method(&mut self, other: ModNum) {
+                *self = ModNum::$method(self, &other);
+            }
+        }
+    };
+}
+
+mod_num_op!(Add, add, AddAssign, add_assign);
+mod_num_op!(Sub, sub, SubAssign, sub_assign);
+mod_num_op!(Mul, mul, MulAssign, mul_assign);
+mod_num_op!(Div, div, DivAssign, div_assign);
+
+impl Neg for &ModNum {
+    type Output = ModNum;
+    fn neg(self) -> ModNum {
+        self.add_inv()
+    }
+}
+impl Neg for ModNum {
+    type Output = ModNum;
+    fn neg(self) -> ModNum {
+        self.add_inv()
     }
 }
 
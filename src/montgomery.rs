@@ -0,0 +1,193 @@
+use crate::arithmetic::Arithmetic;
+use crate::clean_up::big;
+use num_bigint::BigUint;
+
+// Montgomery multiplication, https://en.wikipedia.org/wiki/Montgomery_modular_multiplication
+//  Numbers are kept as a * R mod p with R = 2^(64 * N). Multiplying two of them and dividing by R gives the
+//   product in the same form, and dividing by R is just shifting out words once a multiple of p has been
+//  added to clear them, so there's no long division like the % in ModNum::mul.
+// Each element is a fixed size [u64; N] on the stack, N = 4 covers fields up to 256 bits, 6 up to 384, and
+//  9 up to 576, which is enough for P-521. Nothing is allocated and every operation runs through the same
+//   words in the same order whatever the values are, the choices between a result and the result minus p
+//  are made with masks.
+// Only odd fields work, R has to be invertible mod p.
+
+#[derive(Debug, Clone)]
+pub(crate) struct Montgomery<const N: usize> {
+    modulus: BigUint,
+    p: [u64; N],
+    // -p^-1 mod 2^64
+    p_inv: u64,
+    // R mod p, which is 1 in Montgomery form.
+    r: [u64; N],
+    // R^2 mod p, multiplying by it turns a number into Montgomery form.
+    r_2: [u64; N],
+}
+
+impl<const N: usize> Montgomery<N> {
+    // Returns None if modulus is even, less than 3, or too big to fit in N words.
+    pub(crate) fn new(modulus: &BigUint) -> Option<Montgomery<N>> {
+        if !modulus.bit(0) || modulus < &big(3) || modulus.bits() > 64 * N as u64 {
+            return None;
+        }
+        let p = to_limbs(modulus);
+        // Newton's method, every step doubles the number of correct low bits of p^-1 and p is its own
+        //  inverse mod 8, so 3 bits to start, 6 steps gets past 64.
+        let mut inv = p[0];
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(p[0].wrapping_mul(inv)));
+        }
+        let r = big(1) << (64 * N);
+        Some(Montgomery {
+            modulus: modulus.clone(),
+            p,
+            p_inv: inv.wrapping_neg(),
+            r: to_limbs(&(&r % modulus)),
+            r_2: to_limbs(&((&r * &r) % modulus)),
+        })
+    }
+
+    // Subtracts p from value, and from the extra top word carry, when the result doesn't go negative.
+    fn reduce_once(&self, value: [u64; N], carry: u64) -> [u64; N] {
+        let mut reduced = [0; N];
+        let mut borrow = 0;
+        for i in 0..N {
+            (reduced[i], borrow) = sbb(value[i], self.p[i], borrow);
+        }
+        let (_, borrow) = sbb(carry, 0, borrow);
+        // borrow is 1 when value < p, keep value in that case.
+        select(&reduced, &value, 0u64.wrapping_sub(borrow))
+    }
+}
+
+impl<const N: usize> Arithmetic for Montgomery<N> {
+    type Elem = [u64; N];
+
+    fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    fn elem(&self, value: &BigUint) -> [u64; N] {
+        self.mul(&to_limbs(&(value % &self.modulus)), &self.r_2)
+    }
+
+    fn to_big(&self, elem: &[u64; N]) -> BigUint {
+        let mut one = [0; N];
+        one[0] = 1;
+        from_limbs(&self.mul(elem, &one))
+    }
+
+    fn zero(&self) -> [u64; N] {
+        [0; N]
+    }
+
+    fn one(&self) -> [u64; N] {
+        self.r
+    }
+
+    fn add(&self, left: &[u64; N], right: &[u64; N]) -> [u64; N] {
+        let mut sum = [0; N];
+        let mut carry = 0;
+        for i in 0..N {
+            (sum[i], carry) = adc(left[i], right[i], carry);
+        }
+        self.reduce_once(sum, carry)
+    }
+
+    fn sub(&self, left: &[u64; N], right: &[u64; N]) -> [u64; N] {
+        let mut difference = [0; N];
+        let mut borrow = 0;
+        for i in 0..N {
+            (difference[i], borrow) = sbb(left[i], right[i], borrow);
+        }
+        // Went negative, add p back, or add 0 if it didn't.
+        let mask = 0u64.wrapping_sub(borrow);
+        let mut carry = 0;
+        for (word, p_word) in difference.iter_mut().zip(self.p) {
+            (*word, carry) = adc(*word, p_word & mask, carry);
+        }
+        difference
+    }
+
+    // Coarsely Integrated Operand Scanning, section 4 of Koc, Acar, and Kaliski, "Analyzing and Comparing
+    //  Montgomery Multiplication Algorithms". For each word of right, add left * word, then add the multiple
+    //   of p that clears the bottom word and shift down a word. The result is left * right / R, less than 2p
+    //  before the final subtraction.
+    fn mul(&self, left: &[u64; N], right: &[u64; N]) -> [u64; N] {
+        let mut t = [0u64; N];
+        // The two words above t.
+        let mut t_n = 0u64;
+        let mut t_n1;
+        for &word in right {
+            let mut carry = 0;
+            for j in 0..N {
+                (t[j], carry) = mac(t[j], left[j], word, carry);
+            }
+            (t_n, t_n1) = adc(t_n, carry, 0);
+
+            let m = t[0].wrapping_mul(self.p_inv);
+            let (_, mut carry) = mac(t[0], m, self.p[0], 0);
+            for j in 1..N {
+                (t[j - 1], carry) = mac(t[j], m, self.p[j], carry);
+            }
+            (t[N - 1], carry) = adc(t_n, carry, 0);
+            t_n = t_n1 + carry;
+        }
+        self.reduce_once(t, t_n)
+    }
+
+    fn cswap(&self, left: &mut [u64; N], right: &mut [u64; N], mask: u64) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let difference = mask & (*l ^ *r);
+            *l ^= difference;
+            *r ^= difference;
+        }
+    }
+}
+
+// left + right + carry, returned as the low word and the carry out.
+fn adc(left: u64, right: u64, carry: u64) -> (u64, u64) {
+    let sum = u128::from(left) + u128::from(right) + u128::from(carry);
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// left - right - borrow, returned as the low word and the borrow out, which is 0 or 1.
+fn sbb(left: u64, right: u64, borrow: u64) -> (u64, u64) {
+    let difference = u128::from(left)
+        .wrapping_sub(u128::from(right))
+        .wrapping_sub(u128::from(borrow));
+    (difference as u64, (difference >> 127) as u64)
+}
+
+// acc + left * right + carry, which always fits in 128 bits.
+fn mac(acc: u64, left: u64, right: u64, carry: u64) -> (u64, u64) {
+    let sum = u128::from(acc) + u128::from(left) * u128::from(right) + u128::from(carry);
+    (sum as u64, (sum >> 64) as u64)
+}
+
+// when_zero where mask is 0 and when_ones where it is all ones.
+fn select<const N: usize>(when_zero: &[u64; N], when_ones: &[u64; N], mask: u64) -> [u64; N] {
+    let mut selected = [0; N];
+    for i in 0..N {
+        selected[i] = (when_zero[i] & !mask) | (when_ones[i] & mask);
+    }
+    selected
+}
+
+// value must fit in N words.
+fn to_limbs<const N: usize>(value: &BigUint) -> [u64; N] {
+    let mut limbs = [0; N];
+    for (limb, digit) in limbs.iter_mut().zip(value.iter_u64_digits()) {
+        *limb = digit;
+    }
+    limbs
+}
+
+fn from_limbs(limbs: &[u64]) -> BigUint {
+    BigUint::new(
+        limbs
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect(),
+    )
+}
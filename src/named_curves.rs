@@ -0,0 +1,197 @@
+use crate::clean_up::big;
+use crate::el_gamal::Curves;
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::{EllipticType, Point};
+use crate::error::Result;
+use crate::modular_numbers::ModNum;
+use num_bigint::BigUint;
+use std::sync::OnceLock;
+
+// The parameters of one of the standard curves, straight from the documents that define them.
+//  SEC 2 - https://www.secg.org/sec2-v2.pdf, secp curves, which FIPS 186-4 also publishes as P-192 to P-521.
+//  RFC 5639 - https://www.rfc-editor.org/rfc/rfc5639, the Brainpool curves.
+// Every number is hex. The generator is stored as the published (Gx, Gy) rather than recovered from Gx, so it
+//  is exactly the G every other implementation uses and keys made here work with them.
+#[derive(Debug)]
+pub(crate) struct NamedCurve {
+    pub(crate) curve: Curves,
+    pub(crate) name: &'static str,
+    // Other names for the same curve, FIPS 186-4 and ANSI X9.62 name some of them differently.
+    pub(crate) aliases: &'static [&'static str],
+    pub(crate) oid: &'static [u64],
+    p: &'static str,
+    a: &'static str,
+    b: &'static str,
+    gx: &'static str,
+    gy: &'static str,
+    order: &'static str,
+    cofactor: u32,
+    // Built the first time it's asked for, with the init_point table built up front so every copy of the
+    //  curve handed out shares it.
+    built: OnceLock<Result<EllipticCurve>>,
+}
+
+impl NamedCurve {
+    pub(crate) fn curve(&self) -> &Result<EllipticCurve> {
+        self.built.get_or_init(|| {
+            let curve = self.build()?;
+            curve.init_table();
+            Ok(curve)
+        })
+    }
+
+    // The field, without building the rest of the curve.
+    pub(crate) fn field(&self) -> BigUint {
+        hex(self.p)
+    }
+
+    pub(crate) fn is_called(&self, name: &str) -> bool {
+        std::iter::once(&self.name)
+            .chain(self.aliases)
+            .any(|known| known.eq_ignore_ascii_case(name))
+    }
+
+    fn build(&self) -> Result<EllipticCurve> {
+        let field = self.field();
+        let a = ModNum::try_new(&hex(self.a), &field)?;
+        let b = ModNum::try_new(&hex(self.b), &field)?;
+        let gx = ModNum::try_new(&hex(self.gx), &field)?;
+        let gy = ModNum::try_new(&hex(self.gy), &field)?;
+        let init_point = EllipticType::Point(Point::try_new(gx, gy, &a, &b)?);
+        EllipticCurve::try_new_with_order(a, b, init_point, hex(self.order), big(self.cofactor))
+    }
+}
+
+fn hex(value: &str) -> BigUint {
+    BigUint::parse_bytes(value.as_bytes(), 16).unwrap()
+}
+
+// Every named curve. Curves::all goes through them in this order.
+pub(crate) static NAMED_CURVES: [NamedCurve; 9] = [
+    NamedCurve {
+        curve: Curves::TwoFiveSix,
+        name: "secp256r1",
+        aliases: &["P-256", "prime256v1"],
+        oid: &[1, 2, 840, 10045, 3, 1, 7],
+        p: "FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF",
+        a: "FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFC",
+        b: "5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B",
+        gx: "6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296",
+        gy: "4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5",
+        order: "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::ThreeEightFour,
+        name: "secp384r1",
+        aliases: &["P-384"],
+        oid: &[1, 3, 132, 0, 34],
+        p: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFF0000000000000000FFFFFFFF",
+        a: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFF0000000000000000FFFFFFFC",
+        b: "B3312FA7E23EE7E4988E056BE3F82D19181D9C6EFE8141120314088F5013875AC656398D8A2ED19D2A85C8EDD3EC2AEF",
+        gx: "AA87CA22BE8B05378EB1C71EF320AD746E1D3B628BA79B9859F741E082542A385502F25DBF55296C3A545E3872760AB7",
+        gy: "3617DE4A96262C6F5D9E98BF9292DC29F8F41DBD289A147CE9DA3113B5F0B8C00A60B1CE1D7E819D7A431D7C90EA0E5F",
+        order: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC7634D81F4372DDF581A0DB248B0A77AECEC196ACCC52973",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::FiveTwoOne,
+        name: "secp521r1",
+        aliases: &["P-521"],
+        oid: &[1, 3, 132, 0, 35],
+        p: "01FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF",
+        a: "01FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFC",
+        b: "0051953EB9618E1C9A1F929A21A0B68540EEA2DA725B99B315F3B8B489918EF109E156193951EC7E937B1652C0BD3BB1BF073573DF883D2C34F1EF451FD46B503F00",
+        gx: "00C6858E06B70404E9CD9E3ECB662395B4429C648139053FB521F828AF606B4D3DBAA14B5E77EFE75928FE1DC127A2FFA8DE3348B3C1856A429BF97E7E31C2E5BD66",
+        gy: "011839296A789A3BC0045C8A5FB42C7D1BD998F54449579B446817AFBD17273E662C97EE72995EF42640C550B9013FAD0761353C7086A272C24088BE94769FD16650",
+        order: "01FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFA51868783BF2F966B7FCC0148F709A5D03BB5C9B8899C47AEBB6FB71E91386409",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::Secp256k1,
+        name: "secp256k1",
+        aliases: &[],
+        oid: &[1, 3, 132, 0, 10],
+        p: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F",
+        a: "0",
+        b: "7",
+        gx: "79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+        gy: "483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8",
+        order: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::Secp224r1,
+        name: "secp224r1",
+        aliases: &["P-224"],
+        oid: &[1, 3, 132, 0, 33],
+        p: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF000000000000000000000001",
+        a: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFE",
+        b: "B4050A850C04B3ABF54132565044B0B7D7BFD8BA270B39432355FFB4",
+        gx: "B70E0CBD6BB4BF7F321390B94A03C1D356C21122343280D6115C1D21",
+        gy: "BD376388B5F723FB4C22DFE6CD4375A05A07476444D5819985007E34",
+        order: "FFFFFFFFFFFFFFFFFFFFFFFFFFFF16A2E0B8F03E13DD29455C5C2A3D",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::Secp192r1,
+        name: "secp192r1",
+        aliases: &["P-192", "prime192v1"],
+        oid: &[1, 2, 840, 10045, 3, 1, 1],
+        p: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFFFFFFFFFF",
+        a: "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFFFFFFFFFFFC",
+        b: "64210519E59C80E70FA7E9AB72243049FEB8DEECC146B9B1",
+        gx: "188DA80EB03090F67CBF20EB43A18800F4FF0AFD82FF1012",
+        gy: "07192B95FFC8DA78631011ED6B24CDD573F977A11E794811",
+        order: "FFFFFFFFFFFFFFFFFFFFFFFF99DEF836146BC9B1B4D22831",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::BrainpoolP256r1,
+        name: "brainpoolP256r1",
+        aliases: &[],
+        oid: &[1, 3, 36, 3, 3, 2, 8, 1, 1, 7],
+        p: "A9FB57DBA1EEA9BC3E660A909D838D726E3BF623D52620282013481D1F6E5377",
+        a: "7D5A0975FC2C3057EEF67530417AFFE7FB8055C126DC5C6CE94A4B44F330B5D9",
+        b: "26DC5C6CE94A4B44F330B5D9BBD77CBF958416295CF7E1CE6BCCDC18FF8C07B6",
+        gx: "8BD2AEB9CB7E57CB2C4B482FFC81B7AFB9DE27E1E3BD23C23A4453BD9ACE3262",
+        gy: "547EF835C3DAC4FD97F8461A14611DC9C27745132DED8E545C1D54C72F046997",
+        order: "A9FB57DBA1EEA9BC3E660A909D838D718C397AA3B561A6F7901E0E82974856A7",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::BrainpoolP384r1,
+        name: "brainpoolP384r1",
+        aliases: &[],
+        oid: &[1, 3, 36, 3, 3, 2, 8, 1, 1, 11],
+        p: "8CB91E82A3386D280F5D6F7E50E641DF152F7109ED5456B412B1DA197FB71123ACD3A729901D1A71874700133107EC53",
+        a: "7BC382C63D8C150C3C72080ACE05AFA0C2BEA28E4FB22787139165EFBA91F90F8AA5814A503AD4EB04A8C7DD22CE2826",
+        b: "04A8C7DD22CE28268B39B55416F0447C2FB77DE107DCD2A62E880EA53EEB62D57CB4390295DBC9943AB78696FA504C11",
+        gx: "1D1C64F068CF45FFA2A63A81B7C13F6B8847A3E77EF14FE3DB7FCAFE0CBD10E8E826E03436D646AAEF87B2E247D4AF1E",
+        gy: "8ABE1D7520F9C2A45CB1EB8E95CFD55262B70B29FEEC5864E19C054FF99129280E4646217791811142820341263C5315",
+        order: "8CB91E82A3386D280F5D6F7E50E641DF152F7109ED5456B31F166E6CAC0425A7CF3AB6AF6B7FC3103B883202E9046565",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+    NamedCurve {
+        curve: Curves::BrainpoolP512r1,
+        name: "brainpoolP512r1",
+        aliases: &[],
+        oid: &[1, 3, 36, 3, 3, 2, 8, 1, 1, 13],
+        p: "AADD9DB8DBE9C48B3FD4E6AE33C9FC07CB308DB3B3C9D20ED6639CCA703308717D4D9B009BC66842AECDA12AE6A380E62881FF2F2D82C68528AA6056583A48F3",
+        a: "7830A3318B603B89E2327145AC234CC594CBDD8D3DF91610A83441CAEA9863BC2DED5D5AA8253AA10A2EF1C98B9AC8B57F1117A72BF2C7B9E7C1AC4D77FC94CA",
+        b: "3DF91610A83441CAEA9863BC2DED5D5AA8253AA10A2EF1C98B9AC8B57F1117A72BF2C7B9E7C1AC4D77FC94CADC083E67984050B75EBAE5DD2809BD638016F723",
+        gx: "81AEE4BDD82ED9645A21322E9C4C6A9385ED9F70B5D916C1B43B62EEF4D0098EFF3B1F78E2D0D48D50D1687B93B97D5F7C6D5047406A5E688B352209BCB9F822",
+        gy: "7DDE385D566332ECC0EABFA9CF7822FDF209F70024A57B1AA000C55B881F8111B2DCDE494A5F485E5BCA4BD88A2763AED1CA2B2FA8F0540678CD1E0F3AD80892",
+        order: "AADD9DB8DBE9C48B3FD4E6AE33C9FC07CB308DB3B3C9D20ED6639CCA70330870553E5C414CA92619418661197FAC10471DB1D381085DDADDB58796829CA90069",
+        cofactor: 1,
+        built: OnceLock::new(),
+    },
+];
@@ -0,0 +1,153 @@
+use crate::clean_up::big;
+use num_bigint::BigUint;
+use std::sync::OnceLock;
+
+// Fast reduction for the fields of the three built in curves, FIPS 186-4 appendix D.2,
+//  https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+//   The primes are generalized Mersenne numbers, sums and differences of powers of 2, so a number twice as
+//  long as the prime can be reduced by adding and subtracting some of its 32 bit words instead of dividing.
+// ModNum picks the reduction when it is made, see Reduction::for_field, every other field uses %.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reduction {
+    Generic,
+    P256,
+    P384,
+    P521,
+}
+
+// Marks a word of the term as 0 rather than one of the words of the number being reduced.
+const Z: usize = usize::MAX;
+
+// r = s1 + 2*s2 + 2*s3 + s4 + s5 - d1 - d2 - d3 - d4, D.2.3. Each term is the multiple and the words that
+//  make it up, most significant first the same as FIPS 186-4 writes them.
+const P256_TERMS: [(i64, [usize; 8]); 9] = [
+    (1, [7, 6, 5, 4, 3, 2, 1, 0]),
+    (2, [15, 14, 13, 12, 11, Z, Z, Z]),
+    (2, [Z, 15, 14, 13, 12, Z, Z, Z]),
+    (1, [15, 14, Z, Z, Z, 10, 9, 8]),
+    (1, [8, 13, 15, 14, 13, 11, 10, 9]),
+    (-1, [10, 8, Z, Z, Z, 13, 12, 11]),
+    (-1, [11, 9, Z, Z, 15, 14, 13, 12]),
+    (-1, [12, Z, 10, 9, 8, 15, 14, 13]),
+    (-1, [13, Z, 11, 10, 9, Z, 15, 14]),
+];
+// 2^256 = 2^224 - 2^192 - 2^96 + 1 mod p, as the multiple and the word it goes into.
+const P256_FOLD: [(i64, usize); 4] = [(1, 7), (-1, 6), (-1, 3), (1, 0)];
+
+// r = t + 2*s1 + s2 + s3 + s4 + s5 + s6 - d1 - d2 - d3, D.2.4.
+const P384_TERMS: [(i64, [usize; 12]); 10] = [
+    (1, [11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]),
+    (2, [Z, Z, Z, Z, Z, 23, 22, 21, Z, Z, Z, Z]),
+    (1, [23, 22, 21, 20, 19, 18, 17, 16, 15, 14, 13, 12]),
+    (1, [20, 19, 18, 17, 16, 15, 14, 13, 12, 23, 22, 21]),
+    (1, [19, 18, 17, 16, 15, 14, 13, 12, 20, Z, 23, Z]),
+    (1, [Z, Z, Z, Z, 23, 22, 21, 20, Z, Z, Z, Z]),
+    (1, [Z, Z, Z, Z, Z, Z, 23, 22, 21, Z, Z, 20]),
+    (-1, [22, 21, 20, 19, 18, 17, 16, 15, 14, 13, 12, 23]),
+    (-1, [Z, Z, Z, Z, Z, Z, Z, 23, 22, 21, 20, Z]),
+    (-1, [Z, Z, Z, Z, Z, Z, Z, 23, 23, Z, Z, Z]),
+];
+// 2^384 = 2^128 + 2^96 - 2^32 + 1 mod p.
+const P384_FOLD: [(i64, usize); 4] = [(1, 4), (1, 3), (-1, 1), (1, 0)];
+
+fn primes() -> &'static [BigUint; 3] {
+    static PRIMES: OnceLock<[BigUint; 3]> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        let power = |bits: u32| big(1) << bits;
+        [
+            power(256) - power(224) + power(192) + power(96) - big(1),
+            power(384) - power(128) - power(96) + power(32) - big(1),
+            power(521) - big(1),
+        ]
+    })
+}
+
+impl Reduction {
+    pub(crate) fn for_field(field: &BigUint) -> Reduction {
+        let [p256, p384, p521] = primes();
+        match field.bits() {
+            256 if field == p256 => Reduction::P256,
+            384 if field == p384 => Reduction::P384,
+            521 if field == p521 => Reduction::P521,
+            _ => Reduction::Generic,
+        }
+    }
+
+    // value mod field, where field is the one this Reduction was picked for. The fast routines only handle
+    //  numbers up to the square of the field, anything bigger goes through % like every other field.
+    pub(crate) fn reduce(self, value: BigUint, field: &BigUint) -> BigUint {
+        if value.bits() > 2 * field.bits() {
+            return value % field;
+        }
+        match self {
+            Reduction::Generic => value % field,
+            Reduction::P256 => solinas(&value, &P256_TERMS, &P256_FOLD, field),
+            Reduction::P384 => solinas(&value, &P384_TERMS, &P384_FOLD, field),
+            // p = 2^521 - 1 so 2^521 = 1 mod p, the top 521 bits are just added to the bottom 521, and p
+            //  itself is the mask for the bottom 521. D.2.5. The sum can be as big as 2p, so up to two
+            //   subtractions.
+            Reduction::P521 => {
+                let mut reduced = (&value >> 521u32) + (value & field);
+                while &reduced >= field {
+                    reduced -= field;
+                }
+                reduced
+            }
+        }
+    }
+}
+
+// Adds up the terms one 32 bit column at a time, then carries. What carries out of the top word is worth
+//  carry * 2^(32 * N), which fold says how to add back in, and that's repeated until nothing carries out.
+//   What's left is less than 2^(32 * N), which is less than 2 * field, so at most one subtraction remains.
+fn solinas<const N: usize, const T: usize>(
+    value: &BigUint,
+    terms: &[(i64, [usize; N]); T],
+    fold: &[(i64, usize); 4],
+    field: &BigUint,
+) -> BigUint {
+    // Big enough for twice the words of P-384, read into an array so nothing is allocated.
+    let mut c = [0u32; 24];
+    for (word, digit) in c.iter_mut().zip(value.iter_u32_digits()) {
+        *word = digit;
+    }
+    let mut columns = [0i64; N];
+    for (multiple, words) in terms {
+        for (column, &word) in columns.iter_mut().zip(words.iter().rev()) {
+            if word != Z {
+                *column += multiple * i64::from(c[word]);
+            }
+        }
+    }
+
+    let mut words = [0u32; N];
+    let mut carry = propagate(&columns, &mut words);
+    while carry != 0 {
+        let mut columns = words.map(i64::from);
+        for (multiple, word) in fold {
+            columns[*word] += carry * multiple;
+        }
+        carry = propagate(&columns, &mut words);
+    }
+
+    let reduced = BigUint::new(words.to_vec());
+    if &reduced >= field {
+        reduced - field
+    } else {
+        reduced
+    }
+}
+
+// Splits each column into a 32 bit word and a signed carry into the next one. Returns the carry out of the
+//  top column.
+fn propagate<const N: usize>(columns: &[i64; N], words: &mut [u32; N]) -> i64 {
+    let mut carry = 0;
+    for (word, column) in words.iter_mut().zip(columns) {
+        let total = column + carry;
+        *word = total as u32;
+        // Arithmetic shift, so a negative total borrows from the next column.
+        carry = total >> 32;
+    }
+    carry
+}
@@ -0,0 +1,93 @@
+use crate::error::{Error, Result};
+
+// Base64 and PEM style armoring so binary data, cipher texts and keys, can be pasted into text files.
+//  https://www.rfc-editor.org/rfc/rfc4648 and https://www.rfc-editor.org/rfc/rfc7468
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// PEM files wrap the base64 text at 64 characters.
+const LINE_LENGTH: usize = 64;
+
+// Encodes the bytes as standard, padded, base64.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        // Pack up to three bytes into 24 bits and then read them back out 6 bits at a time.
+        let mut group = [0u8; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let n = (u32::from(group[0]) << 16) | (u32::from(group[1]) << 8) | u32::from(group[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Decodes standard, padded, base64. Whitespace is ignored so wrapped text can be passed straight in.
+pub fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    fn value(c: u8) -> Result<u32> {
+        match ALPHABET.iter().position(|&a| a == c) {
+            Some(v) => Ok(v as u32),
+            None => Err(Error::InvalidEncoding("invalid base64 character")),
+        }
+    }
+    let chars: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !chars.len().is_multiple_of(4) {
+        return Err(Error::InvalidEncoding(
+            "base64 length isn't a multiple of 4",
+        ));
+    }
+    let mut decoded = Vec::with_capacity(chars.len() / 4 * 3);
+    let groups = chars.len() / 4;
+    for (i, group) in chars.chunks(4).enumerate() {
+        // Padding is only allowed at the very end, and never more than two characters of it.
+        let padding = group.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && i != groups - 1) {
+            return Err(Error::InvalidEncoding("misplaced base64 padding"));
+        }
+        let mut n = 0u32;
+        for &c in &group[..4 - padding] {
+            n = (n << 6) | value(c)?;
+        }
+        n <<= 6 * padding;
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        decoded.extend_from_slice(&bytes[..3 - padding]);
+    }
+    Ok(decoded)
+}
+
+// Wraps the bytes in a PEM style block:
+//  -----BEGIN <label>-----
+//  base64 text, 64 characters per line
+//  -----END <label>-----
+pub fn encode(label: &str, bytes: &[u8]) -> String {
+    let body = base64_encode(bytes);
+    let mut armored = format!("-----BEGIN {}-----\n", label);
+    for line in body.as_bytes().chunks(LINE_LENGTH) {
+        // base64 is always ascii so splitting on any byte boundary is fine.
+        armored.push_str(std::str::from_utf8(line).unwrap());
+        armored.push('\n');
+    }
+    armored.push_str(&format!("-----END {}-----\n", label));
+    armored
+}
+
+// Pulls the bytes back out of a PEM style block with the given label. Anything before the BEGIN line or after
+//  the END line is ignored, the same as other PEM readers.
+pub fn decode(label: &str, text: &str) -> Result<Vec<u8>> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let start = match text.find(&begin) {
+        Some(i) => i + begin.len(),
+        None => return Err(Error::InvalidEncoding("missing PEM BEGIN line")),
+    };
+    let stop = match text[start..].find(&end) {
+        Some(i) => start + i,
+        None => return Err(Error::InvalidEncoding("missing PEM END line")),
+    };
+    base64_decode(&text[start..stop])
+}
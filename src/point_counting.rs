@@ -0,0 +1,264 @@
+use crate::arithmetic::with_arithmetic;
+use crate::clean_up::big;
+use crate::curve_builder::hasse_bounds;
+use crate::elliptic_curve::EllipticCurve;
+use crate::elliptic_point::{EllipticType, Inf, Point};
+use crate::error::{Error, Result};
+use crate::modular_numbers::ModNum;
+use crate::schoof;
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+use std::collections::HashMap;
+
+// Fields up to this many bits are always counted naively, Mestre's method needs the field to be over 229 and
+//  Schoof's needs it to be well clear of the primes it works with. Naive counting is refused above it.
+pub const NAIVE_BITS: u64 = 16;
+
+// Fields up to this many bits are counted with Mestre's method by PointCounting::for_field, larger ones with
+//  Schoof's. Mestre's method is refused above it, its baby steps would need gigabytes past 64 bits or so.
+pub const MESTRE_BITS: u64 = 48;
+
+// The largest field, in bits, that points are counted on. A 128 bit field takes tens of seconds in a release
+//  build with Schoof's algorithm, the 192 bit and larger fields of the named curves far longer, hours and more.
+pub const SCHOOF_BITS: u64 = 128;
+
+// Schoof's algorithm stops once the count is known to be one of at most the square root of the Hasse
+//  bound's width, and at most 2^this, candidates. Mestre's method picks between them, the last few primes
+//   cost far more than that search.
+const SCHOOF_CANDIDATE_BITS: u32 = 32;
+
+// How EllipticCurve::count_points_with finds the number of points on a curve.
+//  Naive - adds up the square roots of x^3 + a*x + b for every x in the field. Linear in the field so only
+//   good for tiny fields, up to NAIVE_BITS.
+//  Mestre - baby step giant step, finds the multiples of a random point's order within the Hasse bound,
+//   using points on the quadratic twist too when they don't narrow it down to one. About the fourth root of
+//  the field in time and memory, used up to MESTRE_BITS.
+//  Schoof - Schoof's algorithm, finds the count modulo small primes l from the l-torsion and then uses
+//   Mestre's method to pick between the counts that are left. Polynomial in the size of the field, but the
+//  polynomials are big, 128 bit fields take tens of seconds. Used up to SCHOOF_BITS.
+// Every method gives the same count, fields up to 16 bits are always counted naively. A method given a field
+//  over its limit returns Error::FieldTooLargeToCount instead of running out of time or memory.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PointCounting {
+    Naive,
+    Mestre,
+    Schoof,
+}
+
+impl PointCounting {
+    // The method EllipticCurve::count_points uses for a field.
+    pub fn for_field(field: &BigUint) -> PointCounting {
+        match field.bits() {
+            bits if bits <= NAIVE_BITS => PointCounting::Naive,
+            bits if bits <= MESTRE_BITS => PointCounting::Mestre,
+            _ => PointCounting::Schoof,
+        }
+    }
+}
+
+// The random points Mestre's method needs are picked with rng.
+pub(crate) fn count_points<R: Rng + ?Sized>(
+    curve: &EllipticCurve,
+    method: PointCounting,
+    rng: &mut R,
+) -> Result<BigUint> {
+    let p = curve.field();
+    let (low, high) = hasse_bounds(p);
+    if p.bits() <= NAIVE_BITS {
+        return Ok(naive(curve));
+    }
+    let limit = match method {
+        PointCounting::Naive => NAIVE_BITS,
+        PointCounting::Mestre => MESTRE_BITS,
+        PointCounting::Schoof => SCHOOF_BITS,
+    };
+    if p.bits() > limit {
+        return Err(Error::FieldTooLargeToCount);
+    }
+    match method {
+        PointCounting::Naive => Ok(naive(curve)),
+        PointCounting::Mestre => narrow(curve, low.clone(), big(1), high - low + 1u32, rng),
+        PointCounting::Schoof => {
+            let width = &high - &low;
+            let bound = (&width >> SCHOOF_CANDIDATE_BITS).max(width.sqrt());
+            let (count, modulus) = with_arithmetic!(p, |field| {
+                schoof::count_mod(&field, curve.a().value(), curve.b().value(), &bound)
+            });
+            // The first count in the Hasse bound that matches count mod modulus.
+            let start = &low + (&count + &modulus - &low % &modulus) % &modulus;
+            let candidates = (&high - &start) / &modulus + 1u32;
+            narrow(curve, start, modulus, candidates, rng)
+        }
+    }
+}
+
+// Infinity and then two points for every x where x^3 + a*x + b is a non-zero square, one where it's 0.
+fn naive(curve: &EllipticCurve) -> BigUint {
+    let field = curve.shared_field();
+    let mut count = big(1);
+    let mut x = ModNum::in_field(&big(0), field);
+    let one = ModNum::in_field(&big(1), field);
+    loop {
+        let rhs = &x * &x * &x + curve.a() * &x + curve.b();
+        count += (1 + rhs.legendre_symbol()) as u32;
+        x += &one;
+        if x.value() == &big(0) {
+            return count;
+        }
+    }
+}
+
+// Finds the number of points on the curve, given that it's start + k * step for some k in [0, candidates).
+//  Each random point P rules out the candidates N with N * P != Infinity, which leaves another arithmetic
+//   progression of them. A point on the twist, which has 2p + 2 - N points, does the same. Mestre showed
+//  that for fields over 229 the curve or its twist has a point that leaves only one, so this alternates
+//   between them.
+// Error::PointCountFailed when no candidate fits, which only happens when the curve is singular or its field
+//  isn't prime.
+fn narrow<R: Rng + ?Sized>(
+    curve: &EllipticCurve,
+    mut start: BigUint,
+    mut step: BigUint,
+    mut candidates: BigUint,
+    rng: &mut R,
+) -> Result<BigUint> {
+    let twist = twist(curve, rng);
+    let twist_sum = curve.field() * 2u32 + 2u32;
+    for attempt in 0.. {
+        if candidates == big(1) {
+            break;
+        }
+        // N * P = Infinity with N = start + k * step is k * (step * P) = -start * P, and on the twist
+        //  (2p + 2 - N) * P = Infinity is k * (step * P) = (2p + 2 - start) * P.
+        let (on, target_exp) = if attempt % 2 == 0 {
+            (curve, &start)
+        } else {
+            (&twist, &(&twist_sum - &start))
+        };
+        let point = random_point(on.a(), on.b(), rng);
+        let base = point.pow(&step, on);
+        let mut target = point.pow(target_exp, on);
+        if attempt % 2 == 0 {
+            target = target.group_inv();
+        }
+        let (first, second) = match baby_step_giant_step(&base, &target, &candidates, on)? {
+            (Some(first), second) => (first, second),
+            (None, _) => return Err(Error::PointCountFailed),
+        };
+        start += &first * &step;
+        match second {
+            Some(second) => {
+                let gap = second - &first;
+                candidates = (candidates - first - 1u32) / &gap + 1u32;
+                step *= gap;
+            }
+            None => candidates = big(1),
+        }
+    }
+    Ok(start)
+}
+
+// The quadratic twist, y^2 = x^3 + c^2 a x + c^3 b for a c that isn't a square. It has 2p + 2 - N points when
+//  the curve has N.
+fn twist<R: Rng + ?Sized>(curve: &EllipticCurve, rng: &mut R) -> EllipticCurve {
+    let field = curve.shared_field();
+    let mut c = ModNum::in_field(&big(2), field);
+    while c.is_square() {
+        c += ModNum::in_field(&big(1), field);
+    }
+    let a = curve.a() * &c * &c;
+    let b = curve.b() * &c * &c * &c;
+    let point = random_point(&a, &b, rng);
+    EllipticCurve::new(a, b, point)
+}
+
+// A random point on y^2 = x^3 + a*x + b, with either square root picked for y.
+pub(crate) fn random_point<R: Rng + ?Sized>(a: &ModNum, b: &ModNum, rng: &mut R) -> EllipticType {
+    loop {
+        let x = ModNum::in_field(&rng.gen_biguint_below(a.field()), a.shared_field());
+        if let Some(y) = (&x * &x * &x + a * &x + b).sqrt() {
+            let y = if rng.gen() { -y } else { y };
+            return EllipticType::Point(Point::new(x, y, a, b));
+        }
+    }
+}
+
+// The first two k in [0, count) with k * base = target, https://en.wikipedia.org/wiki/Baby-step_giant-step
+//  Every other one is the first plus a multiple of their difference, the order of base.
+fn baby_step_giant_step(
+    base: &EllipticType,
+    target: &EllipticType,
+    count: &BigUint,
+    curve: &EllipticCurve,
+) -> Result<(Option<BigUint>, Option<BigUint>)> {
+    let m: u64 = match (count.sqrt() + 1u32).try_into() {
+        Ok(m) => m,
+        Err(_) => return Err(Error::FieldTooLargeToCount),
+    };
+    let mut baby_steps = HashMap::new();
+    let mut step = EllipticType::Infinity(Inf::in_field(curve.shared_field()));
+    for j in 0..m {
+        if j > 0 && matches!(step, EllipticType::Infinity(_)) {
+            // base has order j, all the k are in baby_steps.
+            let first = match baby_steps.get(&key(target)) {
+                Some(&first) => BigUint::from(first),
+                None => return Ok((None, None)),
+            };
+            let second = Some(&first + j).filter(|second| second < count);
+            return Ok((Some(first), second));
+        }
+        baby_steps.insert(key(&step), j);
+        step = step.group_op(base, curve);
+    }
+
+    let giant_step = base.pow(&BigUint::from(m), curve).group_inv();
+    let mut found = vec![];
+    let mut giant = target.clone();
+    let mut i = big(0);
+    while &(&i * m) < count && found.len() < 2 {
+        if let Some(&j) = baby_steps.get(&key(&giant)) {
+            let k = &i * m + j;
+            if &k < count {
+                found.push(k);
+            }
+        }
+        giant = giant.group_op(&giant_step, curve);
+        i += 1u32;
+    }
+    let mut found = found.into_iter();
+    Ok((found.next(), found.next()))
+}
+
+fn key(point: &EllipticType) -> Option<(BigUint, BigUint)> {
+    match point {
+        EllipticType::Point(point) => Some((point.x().value().clone(), point.y().value().clone())),
+        EllipticType::Infinity(_) => None,
+    }
+}
+
+// The order of point, given the number of points on the curve, or any multiple of the point's order, as
+//  prime factors and their powers. For each prime q the order only needs as many factors of q as it takes
+//   for the rest of the multiple to send point to Infinity.
+pub(crate) fn point_order(
+    curve: &EllipticCurve,
+    point: &EllipticType,
+    factors: &[(BigUint, u32)],
+) -> Result<BigUint> {
+    curve.validate_point(point)?;
+    let multiple = factors
+        .iter()
+        .fold(big(1), |multiple, (q, e)| multiple * q.pow(*e));
+    if let EllipticType::Point(_) = point.pow(&multiple, curve) {
+        return Err(Error::WrongGroupOrder);
+    }
+    let mut order = multiple;
+    for (q, e) in factors {
+        order /= q.pow(*e);
+        let mut remaining = point.pow(&order, curve);
+        while let EllipticType::Point(_) = remaining {
+            remaining = remaining.pow(q, curve);
+            order *= q;
+        }
+    }
+    Ok(order)
+}
@@ -0,0 +1,153 @@
+use crate::clean_up::big;
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use rand::Rng;
+use std::sync::OnceLock;
+
+// The number of Miller-Rabin rounds. Each one lets a composite through with probability at most 1/4, so
+//  this is at most 2^-80, and far less in practice for numbers that weren't built to fool the test.
+const ROUNDS: usize = 40;
+
+// How many steps Pollard's rho takes for each constant it tries, and how many constants it tries, before
+//  factor gives up. It finds a prime factor q in about sqrt(q) steps so this covers factors up to 32 bits or so.
+const RHO_STEPS: u32 = 1 << 16;
+const RHO_CONSTANTS: u32 = 4;
+
+// Whether n is prime, using trial division by small primes and then the Miller-Rabin test with random bases.
+//  https://en.wikipedia.org/wiki/Miller%E2%80%93Rabin_primality_test
+// A prime is never reported as composite, a composite is reported as prime with probability at most 2^-80.
+pub fn is_probable_prime(n: &BigUint) -> bool {
+    is_probable_prime_with(n, &mut rand::thread_rng())
+}
+
+// Same as is_probable_prime with the bases picked by rng, so a seeded one makes the same choices every time.
+pub fn is_probable_prime_with<R: Rng + ?Sized>(n: &BigUint, rng: &mut R) -> bool {
+    for &p in small_primes() {
+        let p = big(p);
+        if n == &p {
+            return true;
+        }
+        if (n % &p) == big(0) {
+            return false;
+        }
+    }
+    if n < &big(2) {
+        return false;
+    }
+
+    // n - 1 = d * 2^s with d odd.
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+    'rounds: for _ in 0..ROUNDS {
+        let base = rng.gen_biguint_range(&big(2), &n_minus_one);
+        let mut x = base.modpow(&d, n);
+        if x == big(1) || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&big(2), n);
+            if x == n_minus_one {
+                continue 'rounds;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// A random prime with exactly the given number of bits, bits must be at least 2. Random odd numbers with the
+//  top bit set are tried until one is prime, about bits * ln(2) / 2 of them.
+pub fn random_prime<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> BigUint {
+    assert!(bits >= 2, "there are no primes with fewer than 2 bits");
+    loop {
+        let candidate = rng.gen_biguint(bits - 1) | (big(1) << (bits - 1)) | big(1);
+        if is_probable_prime_with(&candidate, rng) {
+            return candidate;
+        }
+    }
+}
+
+// The prime factors of n and their powers, smallest first. Trial division takes out the primes below 1000 and
+//  Pollard's rho splits what's left, https://en.wikipedia.org/wiki/Pollard%27s_rho_algorithm
+// None when n is 0, or when what's left has two or more prime factors too big for rho to find, see RHO_STEPS.
+//  A single large prime factor is fine, that is the usual case for the number of points on a curve.
+pub fn factor(n: &BigUint) -> Option<Vec<(BigUint, u32)>> {
+    factor_with(n, &mut rand::thread_rng())
+}
+
+// Same as factor with the primality tests' bases picked by rng.
+pub fn factor_with<R: Rng + ?Sized>(n: &BigUint, rng: &mut R) -> Option<Vec<(BigUint, u32)>> {
+    if n == &big(0) {
+        return None;
+    }
+    let mut rest = n.clone();
+    let mut primes = vec![];
+    for &p in small_primes() {
+        let p = big(p);
+        while (&rest % &p) == big(0) {
+            rest /= &p;
+            primes.push(p.clone());
+        }
+    }
+    let mut composites = vec![rest];
+    while let Some(composite) = composites.pop() {
+        if composite == big(1) {
+            continue;
+        }
+        if is_probable_prime_with(&composite, rng) {
+            primes.push(composite);
+            continue;
+        }
+        let divisor = pollard_rho(&composite)?;
+        composites.push(&composite / &divisor);
+        composites.push(divisor);
+    }
+    primes.sort();
+    let mut factors: Vec<(BigUint, u32)> = vec![];
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, power)) if last == &p => *power += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    Some(factors)
+}
+
+// A divisor of the composite n other than 1 and n. x -> x^2 + c mod n is a pseudo-random walk that, mod a prime
+//  factor q, falls into a cycle after about sqrt(q) steps. Floyd's cycle finding notices when x and y, which
+//   moves twice as fast, meet mod q, gcd(x - y, n) is then a multiple of q. Meeting mod n as well gives n,
+//  and a different c is tried.
+fn pollard_rho(n: &BigUint) -> Option<BigUint> {
+    if n.is_even() {
+        return Some(big(2));
+    }
+    let step = |x: &BigUint, c: u32| (x * x + c) % n;
+    for c in 1..=RHO_CONSTANTS {
+        let mut x = big(2);
+        let mut y = big(2);
+        for _ in 0..RHO_STEPS {
+            x = step(&x, c);
+            y = step(&step(&y, c), c);
+            let difference = if x > y { &x - &y } else { &y - &x };
+            let divisor = difference.gcd(n);
+            if &divisor == n {
+                break;
+            }
+            if divisor != big(1) {
+                return Some(divisor);
+            }
+        }
+    }
+    None
+}
+
+// The primes below 1000, for trial division.
+fn small_primes() -> &'static [u32] {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        (2..1000u32)
+            .filter(|n| (2..*n).take_while(|d| d * d <= *n).all(|d| n % d != 0))
+            .collect()
+    })
+}
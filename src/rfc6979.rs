@@ -0,0 +1,97 @@
+use crate::clean_up::{big, byte_len, to_fixed_bytes};
+use crate::sha2::Hash;
+use num_bigint::BigUint;
+
+// Deterministic nonces as described in RFC 6979 section 3.2, https://www.rfc-editor.org/rfc/rfc6979
+//  An HMAC-DRBG is seeded with the private key and the hash of the message, so the same key and message always
+//   give the same nonce, but without the private key the nonce looks random. This takes the random number
+//  generator out of signing entirely, a broken or repeating RNG can no longer leak the private key.
+
+// Produces nonces in [1, q). next can be called again if a nonce turns out to be unusable, e.g. it gives a
+//  signature with r = 0, and it will carry on with the sequence the same way RFC 6979 section 3.4 does.
+#[derive(Debug, Clone)]
+pub struct NonceGenerator {
+    hash: Hash,
+    q: BigUint,
+    k: Vec<u8>,
+    v: Vec<u8>,
+    // Whether next has already returned a nonce and so needs to update K and V before making another.
+    started: bool,
+}
+
+impl NonceGenerator {
+    // Seeds the generator, steps a through f of section 3.2. The secret is the private key, x, and q is the
+    //  order of the curve. msg_hash is H(m), the hash the nonce will be used to sign.
+    pub fn new(hash: Hash, secret: &BigUint, q: &BigUint, msg_hash: &[u8]) -> NonceGenerator {
+        let rlen = byte_len(q);
+        let mut seed = to_fixed_bytes(&(secret % q), rlen);
+        seed.extend(bits2octets(msg_hash, q));
+
+        let mut generator = NonceGenerator {
+            hash,
+            q: q.clone(),
+            k: vec![0x00; hash.output_len()],
+            v: vec![0x01; hash.output_len()],
+            started: false,
+        };
+        generator.update(&seed);
+        generator
+    }
+
+    // Steps g and h of section 3.2, returns the next candidate nonce.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> BigUint {
+        if self.started {
+            self.skip();
+        }
+        self.started = true;
+        loop {
+            let mut t = Vec::with_capacity(byte_len(&self.q));
+            while (t.len() as u64) * 8 < self.q.bits() {
+                self.v = self.hash.hmac(&self.k, &self.v);
+                t.extend_from_slice(&self.v);
+            }
+            let k = bits2int(&t, &self.q);
+            if k != big(0) && k < self.q {
+                return k;
+            }
+            self.skip();
+        }
+    }
+
+    // K = HMAC_K(V || 0x00), V = HMAC_K(V), moves on from a nonce that was out of range or already used.
+    fn skip(&mut self) {
+        let mut data = self.v.clone();
+        data.push(0x00);
+        self.k = self.hash.hmac(&self.k, &data);
+        self.v = self.hash.hmac(&self.k, &self.v);
+    }
+
+    // K = HMAC_K(V || 0x00 || seed), V = HMAC_K(V), and then the same again with 0x01.
+    fn update(&mut self, seed: &[u8]) {
+        for separator in [0x00, 0x01] {
+            let mut data = self.v.clone();
+            data.push(separator);
+            data.extend_from_slice(seed);
+            self.k = self.hash.hmac(&self.k, &data);
+            self.v = self.hash.hmac(&self.k, &self.v);
+        }
+    }
+}
+
+// Section 2.3.2, the leftmost bits of the bytes as a number, keeping only as many bits as q has. This is also
+//  how ECDSA turns a hash into a number, FIPS 186-4 section 6.4.
+pub(crate) fn bits2int(bytes: &[u8], q: &BigUint) -> BigUint {
+    let value = BigUint::from_bytes_be(bytes);
+    let bits = bytes.len() as u64 * 8;
+    if bits > q.bits() {
+        value >> (bits - q.bits())
+    } else {
+        value
+    }
+}
+
+// Section 2.3.4, bits2int reduced mod q and written out with the same number of bytes as q.
+fn bits2octets(bytes: &[u8], q: &BigUint) -> Vec<u8> {
+    to_fixed_bytes(&(bits2int(bytes, q) % q), byte_len(q))
+}
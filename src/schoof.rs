@@ -0,0 +1,568 @@
+use crate::arithmetic::Arithmetic;
+use crate::clean_up::big;
+use num_bigint::BigUint;
+
+// Schoof's algorithm, https://en.wikipedia.org/wiki/Schoof%27s_algorithm
+//  The number of points on y^2 = x^3 + a*x + b over the field p is p + 1 - t, where the trace t is the
+//   number that makes the Frobenius map phi(x, y) = (x^p, y^p) satisfy phi^2 - t * phi + p = 0 on every
+//  point. Working on the points of a small prime order l, the l-torsion, gives t mod l, and enough of those
+//   pin down the count modulo the product of the l's.
+// The l-torsion points are the roots of the division polynomial psi_l, so arithmetic on a "generic" point of
+//  order l is done with polynomials in x mod psi_l, and y^2 replaced by x^3 + a*x + b. A point is (X(x),
+//   Y(x) * y). Adding two of them divides by X_2 - X_1, which might share a factor with psi_l without being
+//  0 mod it, the points are equal for some of the torsion and not the rest. Then the factor is used in
+//   place of psi_l and l is started again, the relation holds on every l-torsion point so any Frobenius
+//  stable part of them will do.
+
+// A polynomial over the field, lowest degree first and without zero leading coefficients, so equal
+//  polynomials are equal Vecs and 0 is empty.
+type Poly<F> = Vec<<F as Arithmetic>::Elem>;
+
+// A point of the l-torsion, with coordinates that are polynomials mod the torsion's modulus.
+#[derive(Debug, Clone, PartialEq)]
+enum TorsionPoint<F: Arithmetic> {
+    Zero,
+    Point(Poly<F>, Poly<F>),
+}
+
+// Multiplications where the shorter polynomial has fewer coefficients than this are done the schoolbook way,
+//  splitting them up any further costs more than it saves.
+const KARATSUBA_CUTOFF: usize = 24;
+
+// A monic polynomial that others are reduced by, and the first deg - 1 coefficients of the power series
+//  1 / x^deg modulus(1 / x). The quotient of anything of degree below 2 deg is then a product with that
+//   series, reversed, so a remainder is two multiplications instead of a long division.
+struct Modulus<F: Arithmetic> {
+    poly: Poly<F>,
+    reciprocal: Vec<F::Elem>,
+}
+
+// Polynomial arithmetic over the field.
+struct Polys<'a, F: Arithmetic> {
+    field: &'a F,
+}
+
+impl<F: Arithmetic> Polys<'_, F> {
+    fn trim(&self, mut poly: Poly<F>) -> Poly<F> {
+        let zero = self.field.zero();
+        while poly.last() == Some(&zero) {
+            poly.pop();
+        }
+        poly
+    }
+
+    fn constant(&self, value: &F::Elem) -> Poly<F> {
+        self.trim(vec![value.clone()])
+    }
+
+    fn x(&self) -> Poly<F> {
+        vec![self.field.zero(), self.field.one()]
+    }
+
+    fn add(&self, left: &[F::Elem], right: &[F::Elem]) -> Poly<F> {
+        let (long, short) = if left.len() >= right.len() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        let mut sum = long.to_vec();
+        for (coefficient, other) in sum.iter_mut().zip(short) {
+            *coefficient = self.field.add(coefficient, other);
+        }
+        self.trim(sum)
+    }
+
+    fn sub(&self, left: &[F::Elem], right: &[F::Elem]) -> Poly<F> {
+        let mut difference = left.to_vec();
+        difference.resize(left.len().max(right.len()), self.field.zero());
+        for (coefficient, other) in difference.iter_mut().zip(right) {
+            *coefficient = self.field.sub(coefficient, other);
+        }
+        self.trim(difference)
+    }
+
+    fn scale(&self, poly: &[F::Elem], by: &F::Elem) -> Poly<F> {
+        self.trim(poly.iter().map(|c| self.field.mul(c, by)).collect())
+    }
+
+    fn mul(&self, left: &[F::Elem], right: &[F::Elem]) -> Poly<F> {
+        if left.is_empty() || right.is_empty() {
+            return vec![];
+        }
+        self.trim(self.product(left, right))
+    }
+
+    // left * right with Karatsuba's method, https://en.wikipedia.org/wiki/Karatsuba_algorithm, untrimmed so it
+    //  always has left.len() + right.len() - 1 coefficients. Neither can be empty.
+    fn product(&self, left: &[F::Elem], right: &[F::Elem]) -> Vec<F::Elem> {
+        let field = self.field;
+        let mut result = vec![field.zero(); left.len() + right.len() - 1];
+        let (long, short) = if left.len() >= right.len() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        if short.len() < KARATSUBA_CUTOFF {
+            for (i, l) in long.iter().enumerate() {
+                for (j, s) in short.iter().enumerate() {
+                    result[i + j] = field.add(&result[i + j], &field.mul(l, s));
+                }
+            }
+            return result;
+        }
+        let half = long.len() / 2;
+        if short.len() <= half {
+            // Too lopsided to split both, short times each half of long.
+            self.add_at(&mut result, 0, &self.product(&long[..half], short));
+            self.add_at(&mut result, half, &self.product(&long[half..], short));
+            return result;
+        }
+        let (long_0, long_1) = long.split_at(half);
+        let (short_0, short_1) = short.split_at(half);
+        let low = self.product(long_0, short_0);
+        let high = self.product(long_1, short_1);
+        let mut middle = self.product(&self.sum(long_0, long_1), &self.sum(short_0, short_1));
+        for (coefficient, other) in middle.iter_mut().zip(low.iter()) {
+            *coefficient = field.sub(coefficient, other);
+        }
+        for (coefficient, other) in middle.iter_mut().zip(high.iter()) {
+            *coefficient = field.sub(coefficient, other);
+        }
+        self.add_at(&mut result, 0, &low);
+        self.add_at(&mut result, half, &middle);
+        self.add_at(&mut result, 2 * half, &high);
+        result
+    }
+
+    // Untrimmed left + right.
+    fn sum(&self, left: &[F::Elem], right: &[F::Elem]) -> Vec<F::Elem> {
+        let mut sum = left.to_vec();
+        sum.resize(left.len().max(right.len()), self.field.zero());
+        for (coefficient, other) in sum.iter_mut().zip(right) {
+            *coefficient = self.field.add(coefficient, other);
+        }
+        sum
+    }
+
+    // Adds poly * x^offset into result, which has to be long enough.
+    fn add_at(&self, result: &mut [F::Elem], offset: usize, poly: &[F::Elem]) {
+        for (coefficient, other) in result[offset..].iter_mut().zip(poly) {
+            *coefficient = self.field.add(coefficient, other);
+        }
+    }
+
+    // The quotient and remainder of left / right, right can't be 0.
+    fn div_rem(&self, left: &[F::Elem], right: &[F::Elem]) -> (Poly<F>, Poly<F>) {
+        let lead_inv = self.field.inv(right.last().unwrap());
+        let mut remainder = left.to_vec();
+        if remainder.len() < right.len() {
+            return (vec![], remainder);
+        }
+        let mut quotient = vec![self.field.zero(); left.len() - right.len() + 1];
+        for i in (0..quotient.len()).rev() {
+            let c = self.field.mul(&remainder[i + right.len() - 1], &lead_inv);
+            for (j, r) in right.iter().enumerate() {
+                remainder[i + j] = self.field.sub(&remainder[i + j], &self.field.mul(&c, r));
+            }
+            quotient[i] = c;
+        }
+        remainder.truncate(right.len() - 1);
+        (self.trim(quotient), self.trim(remainder))
+    }
+
+    fn rem(&self, left: &[F::Elem], right: &[F::Elem]) -> Poly<F> {
+        self.div_rem(left, right).1
+    }
+
+    fn monic(&self, poly: &[F::Elem]) -> Poly<F> {
+        match poly.last() {
+            Some(lead) => self.scale(poly, &self.field.inv(lead)),
+            None => vec![],
+        }
+    }
+
+    fn gcd(&self, left: &[F::Elem], right: &[F::Elem]) -> Poly<F> {
+        let (mut left, mut right) = (left.to_vec(), right.to_vec());
+        while !right.is_empty() {
+            let remainder = self.rem(&left, &right);
+            left = right;
+            right = remainder;
+        }
+        self.monic(&left)
+    }
+
+    // modulus has to be monic.
+    fn modulus(&self, modulus: Poly<F>) -> Modulus<F> {
+        let field = self.field;
+        let degree = modulus.len() - 1;
+        let reversed: Vec<_> = modulus.iter().rev().collect();
+        let mut reciprocal: Vec<F::Elem> = vec![];
+        for i in 0..degree.saturating_sub(1) {
+            let mut coefficient = if i == 0 { field.one() } else { field.zero() };
+            for j in 1..=i.min(degree) {
+                coefficient = field.sub(&coefficient, &field.mul(reversed[j], &reciprocal[i - j]));
+            }
+            reciprocal.push(coefficient);
+        }
+        Modulus {
+            poly: modulus,
+            reciprocal,
+        }
+    }
+
+    fn reduce(&self, poly: &[F::Elem], modulus: &Modulus<F>) -> Poly<F> {
+        let degree = modulus.poly.len() - 1;
+        if poly.len() <= degree {
+            return poly.to_vec();
+        }
+        if poly.len() >= 2 * degree {
+            return self.rem(poly, &modulus.poly);
+        }
+        let length = poly.len() - degree;
+        let top: Vec<_> = poly[degree..].iter().rev().cloned().collect();
+        let mut quotient = self.product(&top, &modulus.reciprocal[..length]);
+        quotient.truncate(length);
+        quotient.reverse();
+        let multiple = self.product(&quotient, &modulus.poly);
+        self.sub(&poly[..degree], &multiple[..degree])
+    }
+
+    // base^exp mod modulus.
+    fn pow_mod(&self, base: &[F::Elem], exp: &BigUint, modulus: &Modulus<F>) -> Poly<F> {
+        let base = self.reduce(base, modulus);
+        let mut result = self.reduce(&self.constant(&self.field.one()), modulus);
+        for i in (0..exp.bits()).rev() {
+            result = self.reduce(&self.mul(&result, &result), modulus);
+            if exp.bit(i) {
+                result = self.reduce(&self.mul(&result, &base), modulus);
+            }
+        }
+        result
+    }
+}
+
+// The division polynomials psi_n, built up with the usual recurrences. psi_n has a factor of y for even n,
+//  which is left out so every one is a polynomial in x, with y^2 = x^3 + a*x + b put back in by the
+//   recurrences.
+struct DivisionPolys<'a, F: Arithmetic> {
+    polys: &'a Polys<'a, F>,
+    rhs: Poly<F>,
+    half: F::Elem,
+    psi: Vec<Poly<F>>,
+}
+
+impl<'a, F: Arithmetic> DivisionPolys<'a, F> {
+    fn new(polys: &'a Polys<'a, F>, a: &F::Elem, b: &F::Elem) -> DivisionPolys<'a, F> {
+        let field = polys.field;
+        let small = |times: u32, value: &F::Elem| field.small(times, value);
+        let one = field.one();
+        let a2 = field.mul(a, a);
+        let psi_3 = vec![
+            field.sub(&field.zero(), &a2),
+            small(12, b),
+            small(6, a),
+            field.zero(),
+            small(3, &one),
+        ];
+        let psi_4 = vec![
+            field.sub(
+                &field.sub(&field.zero(), &small(8, &field.mul(b, b))),
+                &field.mul(&a2, a),
+            ),
+            field.sub(&field.zero(), &small(4, &field.mul(a, b))),
+            field.sub(&field.zero(), &small(5, &a2)),
+            small(20, b),
+            small(5, a),
+            field.zero(),
+            one.clone(),
+        ];
+        DivisionPolys {
+            polys,
+            rhs: polys.trim(vec![b.clone(), a.clone(), field.zero(), one.clone()]),
+            half: field.inv(&small(2, &one)),
+            psi: vec![
+                vec![],
+                polys.constant(&one),
+                polys.constant(&small(2, &one)),
+                polys.trim(psi_3),
+                polys.scale(&polys.trim(psi_4), &small(4, &one)),
+            ],
+        }
+    }
+
+    fn get(&mut self, n: usize) -> &Poly<F> {
+        while self.psi.len() <= n {
+            let next = self.psi.len();
+            let m = next / 2;
+            let p = self.polys;
+            let psi = &self.psi;
+            let cube = |i: usize| p.mul(&psi[i], &p.mul(&psi[i], &psi[i]));
+            let square = |i: usize| p.mul(&psi[i], &psi[i]);
+            let poly = if next % 2 == 1 {
+                // psi_2m+1 = psi_m+2 psi_m^3 - psi_m-1 psi_m+1^3, with a y^4 on whichever term has the even
+                //  factors.
+                let rhs_2 = p.mul(&self.rhs, &self.rhs);
+                let mut first = p.mul(&psi[m + 2], &cube(m));
+                let mut second = p.mul(&psi[m - 1], &cube(m + 1));
+                if m.is_multiple_of(2) {
+                    first = p.mul(&first, &rhs_2);
+                } else {
+                    second = p.mul(&second, &rhs_2);
+                }
+                p.sub(&first, &second)
+            } else {
+                // psi_2m = psi_m (psi_m+2 psi_m-1^2 - psi_m-2 psi_m+1^2) / 2y, the y's cancel either way.
+                let difference = p.sub(
+                    &p.mul(&psi[m + 2], &square(m - 1)),
+                    &p.mul(&psi[m - 2], &square(m + 1)),
+                );
+                p.scale(&p.mul(&psi[m], &difference), &self.half)
+            };
+            self.psi.push(poly);
+        }
+        &self.psi[n]
+    }
+}
+
+// Arithmetic on the l-torsion, polynomials mod modulus, a factor of psi_l.
+struct Torsion<'a, F: Arithmetic> {
+    polys: &'a Polys<'a, F>,
+    modulus: Modulus<F>,
+    // x^3 + a*x + b, y^2.
+    rhs: Poly<F>,
+    a: F::Elem,
+}
+
+// A factor of the modulus, found when something that needed to be inverted wasn't invertible.
+type Split<F> = Poly<F>;
+
+impl<F: Arithmetic> Torsion<'_, F> {
+    fn reduce(&self, poly: &[F::Elem]) -> Poly<F> {
+        self.polys.reduce(poly, &self.modulus)
+    }
+
+    fn mul(&self, left: &[F::Elem], right: &[F::Elem]) -> Poly<F> {
+        self.reduce(&self.polys.mul(left, right))
+    }
+
+    // The inverse of value with the extended Euclidean algorithm, or the factor it shares with the modulus.
+    fn inv(&self, value: &[F::Elem]) -> Result<Poly<F>, Split<F>> {
+        let p = self.polys;
+        let (mut r_0, mut r_1) = (self.modulus.poly.clone(), value.to_vec());
+        let (mut s_0, mut s_1) = (vec![], p.constant(&p.field.one()));
+        while !r_1.is_empty() {
+            let (quotient, remainder) = p.div_rem(&r_0, &r_1);
+            let s = p.sub(&s_0, &self.mul(&quotient, &s_1));
+            (r_0, r_1) = (r_1, remainder);
+            (s_0, s_1) = (s_1, s);
+        }
+        if r_0.len() > 1 {
+            return Err(p.monic(&r_0));
+        }
+        Ok(p.scale(&s_0, &p.field.inv(&r_0[0])))
+    }
+
+    // Where left and right are equal on part of the torsion, that part.
+    fn split(&self, left: &[F::Elem], right: &[F::Elem]) -> Split<F> {
+        self.polys
+            .gcd(&self.polys.sub(left, right), &self.modulus.poly)
+    }
+
+    fn add(
+        &self,
+        left: &TorsionPoint<F>,
+        right: &TorsionPoint<F>,
+    ) -> Result<TorsionPoint<F>, Split<F>> {
+        let p = self.polys;
+        let ((x_1, y_1), (x_2, y_2)) = match (left, right) {
+            (TorsionPoint::Zero, _) => return Ok(right.clone()),
+            (_, TorsionPoint::Zero) => return Ok(left.clone()),
+            (TorsionPoint::Point(x_1, y_1), TorsionPoint::Point(x_2, y_2)) => {
+                ((x_1, y_1), (x_2, y_2))
+            }
+        };
+        if x_1 == x_2 {
+            if y_1 == y_2 {
+                return self.double(left);
+            }
+            if p.add(y_1, y_2).is_empty() {
+                return Ok(TorsionPoint::Zero);
+            }
+            return Err(self.split(y_1, y_2));
+        }
+        let slope = self.mul(&p.sub(y_2, y_1), &self.inv(&p.sub(x_2, x_1))?);
+        Ok(self.line(&slope, x_1, y_1, x_2))
+    }
+
+    // The points have no y = 0, those are the 2-torsion, so the tangent always exists.
+    fn double(&self, point: &TorsionPoint<F>) -> Result<TorsionPoint<F>, Split<F>> {
+        let (x, y) = match point {
+            TorsionPoint::Zero => return Ok(TorsionPoint::Zero),
+            TorsionPoint::Point(x, y) => (x, y),
+        };
+        let p = self.polys;
+        let field = p.field;
+        // (3x^2 + a) / 2y, which is ((3x^2 + a) / (2 Y y^2)) y.
+        let numerator = p.add(
+            &p.scale(&self.mul(x, x), &field.small(3, &field.one())),
+            &p.constant(&self.a),
+        );
+        let denominator = p.scale(&self.mul(y, &self.rhs), &field.small(2, &field.one()));
+        let slope = self.mul(&numerator, &self.inv(&denominator)?);
+        Ok(self.line(&slope, x, y, x))
+    }
+
+    // The third point on the line through (x_1, y_1) with slope * y, reflected.
+    fn line(
+        &self,
+        slope: &[F::Elem],
+        x_1: &[F::Elem],
+        y_1: &[F::Elem],
+        x_2: &[F::Elem],
+    ) -> TorsionPoint<F> {
+        let p = self.polys;
+        let x_3 = p.sub(
+            &p.sub(&self.mul(&self.mul(slope, slope), &self.rhs), x_1),
+            x_2,
+        );
+        let y_3 = p.sub(&self.mul(slope, &p.sub(x_1, &x_3)), y_1);
+        TorsionPoint::Point(x_3, y_3)
+    }
+
+    fn times(&self, point: &TorsionPoint<F>, times: u64) -> Result<TorsionPoint<F>, Split<F>> {
+        let mut result = TorsionPoint::Zero;
+        for i in (0..u64::BITS - times.leading_zeros()).rev() {
+            result = self.double(&result)?;
+            if times >> i & 1 == 1 {
+                result = self.add(&result, point)?;
+            }
+        }
+        Ok(result)
+    }
+
+    // t mod l, found from phi^2(P) + (p mod l) P = t phi(P).
+    fn trace(&self, l: u64, field: &BigUint) -> Result<u64, Split<F>> {
+        let p = self.polys;
+        let q = (field % l).try_into().unwrap();
+        let x_p = p.pow_mod(&p.x(), field, &self.modulus);
+        let y_p = p.pow_mod(&self.rhs, &((field - 1u32) >> 1), &self.modulus);
+        let x_p2 = p.pow_mod(&x_p, field, &self.modulus);
+        let y_p2 = self.mul(&p.pow_mod(&y_p, field, &self.modulus), &y_p);
+        let phi = TorsionPoint::Point(x_p, y_p);
+        let generic = TorsionPoint::Point(self.reduce(&p.x()), p.constant(&p.field.one()));
+        let q_point = self.times(&generic, q)?;
+
+        let (x_q, y_q) = match &q_point {
+            TorsionPoint::Point(x, y) => (x, y),
+            TorsionPoint::Zero => unreachable!("p is a multiple of l"),
+        };
+        if &x_p2 == x_q {
+            if p.add(&y_p2, y_q).is_empty() {
+                // phi^2 = -p, so t phi = 0.
+                return Ok(0);
+            }
+            if &y_p2 != y_q {
+                return Err(self.split(&y_p2, y_q));
+            }
+            // phi^2 = p, so phi = w for a square root w of p mod l and t = 2w.
+            let w = (1..l)
+                .find(|w| w * w % l == q)
+                .expect("p is a square mod l");
+            let (x_w, y_w) = match self.times(&generic, w)? {
+                TorsionPoint::Point(x, y) => (x, y),
+                TorsionPoint::Zero => unreachable!("w is less than l"),
+            };
+            let (x_phi, y_phi) = match &phi {
+                TorsionPoint::Point(x, y) => (x, y),
+                TorsionPoint::Zero => unreachable!(),
+            };
+            if x_phi != &x_w {
+                return Err(self.split(x_phi, &x_w));
+            }
+            return Ok(if y_phi == &y_w {
+                2 * w % l
+            } else {
+                (l - 2 * w % l) % l
+            });
+        }
+
+        let sum = self.add(&TorsionPoint::Point(x_p2, y_p2), &q_point)?;
+        let (x_sum, y_sum) = match &sum {
+            TorsionPoint::Point(x, y) => (x, y),
+            TorsionPoint::Zero => unreachable!("the x's differ"),
+        };
+        let mut multiple = phi.clone();
+        for tau in 1..=l / 2 {
+            if let TorsionPoint::Point(x, y) = &multiple {
+                if x == x_sum {
+                    return Ok(if y == y_sum { tau } else { l - tau });
+                }
+            }
+            multiple = self.add(&multiple, &phi)?;
+        }
+        unreachable!("no tau with tau phi = phi^2 + p")
+    }
+}
+
+// The number of points on y^2 = x^3 + a*x + b over field, modulo the returned modulus. Works through the primes
+//  l = 2, 3, 5, ... until their product is at least bound.
+pub(crate) fn count_mod<F: Arithmetic>(
+    field: &F,
+    a: &BigUint,
+    b: &BigUint,
+    bound: &BigUint,
+) -> (BigUint, BigUint) {
+    let p = field.modulus();
+    let polys = Polys { field };
+    let (a, b) = (field.elem(a), field.elem(b));
+    let mut division_polys = DivisionPolys::new(&polys, &a, &b);
+    let rhs = division_polys.rhs.clone();
+
+    // t is even exactly when there is a point of order 2, a root of x^3 + a*x + b.
+    let x_p = polys.pow_mod(&polys.x(), p, &polys.modulus(rhs.clone()));
+    let roots = polys.gcd(&polys.sub(&x_p, &polys.x()), &rhs);
+    let trace_2: u32 = if roots.len() > 1 { 0 } else { 1 };
+    // (count, modulus), count = p + 1 - t.
+    let mut count = (p + 1u32 + trace_2) % 2u32;
+    let mut modulus = big(2);
+
+    let mut l = 3u64;
+    while &modulus < bound {
+        if (2..l)
+            .take_while(|d| d * d <= l)
+            .all(|d| !l.is_multiple_of(d))
+        {
+            let mut torsion = Torsion {
+                polys: &polys,
+                modulus: polys.modulus(polys.monic(division_polys.get(l as usize))),
+                rhs: vec![],
+                a: a.clone(),
+            };
+            let trace = loop {
+                torsion.rhs = torsion.reduce(&rhs);
+                match torsion.trace(l, p) {
+                    Ok(trace) => break trace,
+                    Err(factor) => {
+                        let other = polys.div_rem(&torsion.modulus.poly, &factor).0;
+                        let smaller = if factor.len() <= other.len() {
+                            factor
+                        } else {
+                            other
+                        };
+                        torsion.modulus = polys.modulus(smaller);
+                    }
+                }
+            };
+            // count mod l, then the Chinese remainder theorem.
+            let residue = |value: &BigUint| -> u64 { (value % l).try_into().unwrap() };
+            let count_l = (residue(&(p + 1u32)) + l - trace) % l;
+            let inverse =
+                BigUint::from(residue(&modulus)).modpow(&BigUint::from(l - 2), &BigUint::from(l));
+            let step = (count_l + l - residue(&count)) % l * residue(&inverse) % l;
+            count += &modulus * step;
+            modulus *= l;
+        }
+        l += 2;
+    }
+    (count, modulus)
+}
@@ -0,0 +1,251 @@
+use encryption::ciphertext::Ciphertext;
+use encryption::el_gamal::{self, Curves, ElGamal};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::error::Error;
+use encryption::pem;
+
+const HEADER_LEN: usize = 18;
+
+// A P-256 cipher text of a message that takes two chunks, along with the curve and key to decrypt it.
+fn two_chunks() -> (ElGamal, Ciphertext) {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let message = "thirty bytes fit in one chunk, this needs two".to_string();
+    let cipher_text = el_gamal::encrypt(el_gamal.public_key(), el_gamal.curve(), &message);
+    assert_eq!(cipher_text.chunks().len(), 2);
+    (el_gamal, cipher_text)
+}
+
+fn invalid_encoding(bytes: &[u8], curve: &EllipticCurve) -> bool {
+    matches!(
+        Ciphertext::from_bytes(bytes, curve),
+        Err(Error::InvalidEncoding(_))
+    )
+}
+
+#[test]
+fn layout() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let bytes = cipher_text.to_bytes(curve).unwrap();
+    assert_eq!(&bytes[0..4], b"EGCT");
+    assert_eq!(bytes[4], 1);
+    assert_eq!(bytes[5], 1);
+    assert_eq!(&bytes[6..10], &2u32.to_be_bytes());
+    assert_eq!(&bytes[10..18], &(cipher_text.len() as u64).to_be_bytes());
+    // Two chunks of two compressed points, each a prefix byte and a 32 byte x.
+    assert_eq!(bytes.len(), HEADER_LEN + 2 * 2 * 33);
+    for point in bytes[HEADER_LEN..].chunks(33) {
+        assert!(point[0] == 0x02 || point[0] == 0x03);
+    }
+    assert_eq!(
+        Ciphertext::from_bytes(&bytes, curve).as_ref(),
+        Ok(&cipher_text)
+    );
+
+    // Uncompressed points are read too.
+    let mut uncompressed = bytes[..HEADER_LEN].to_vec();
+    for (c_0, c_1) in cipher_text.chunks() {
+        uncompressed.extend(c_0.to_sec1_bytes(false));
+        uncompressed.extend(c_1.to_sec1_bytes(false));
+    }
+    assert_eq!(
+        Ciphertext::from_bytes(&uncompressed, curve).as_ref(),
+        Ok(&cipher_text)
+    );
+
+    // Custom curves share the id 0.
+    let custom = Curves::Custom(EllipticCurve::new(
+        curve.a().clone(),
+        curve.b().clone(),
+        curve.init_point().clone(),
+    ))
+    .into_curve();
+    assert_eq!(cipher_text.to_bytes(&custom).unwrap()[5], 0);
+}
+
+// Every prefix of a cipher text, cut in the header, between points, or part way through one, is refused.
+#[test]
+fn truncated() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let bytes = cipher_text.to_bytes(curve).unwrap();
+    for len in 0..bytes.len() {
+        assert!(invalid_encoding(&bytes[..len], curve), "length {}", len);
+    }
+}
+
+#[test]
+fn bad_header() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let bytes = cipher_text.to_bytes(curve).unwrap();
+
+    for (i, value) in [(0, b'X'), (3, b't'), (4, 0), (4, 2), (4, 0xFF)] {
+        let mut changed = bytes.clone();
+        changed[i] = value;
+        assert!(invalid_encoding(&changed, curve), "byte {}", i);
+    }
+
+    // Another curve's id, and the right bytes read with the wrong curve.
+    for id in [0, 2, 4, 0xFF] {
+        let mut changed = bytes.clone();
+        changed[5] = id;
+        assert_eq!(
+            Ciphertext::from_bytes(&changed, curve),
+            Err(Error::CurveMismatch)
+        );
+    }
+    for other in [Curves::ThreeEightFour, Curves::Secp256k1] {
+        assert_eq!(
+            Ciphertext::from_bytes(&bytes, &other.into_curve()),
+            Err(Error::CurveMismatch)
+        );
+    }
+}
+
+// The chunk count has to be the number of chunks the plain text length needs.
+#[test]
+fn chunk_count_mismatch() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let bytes = cipher_text.to_bytes(curve).unwrap();
+    let with_header = |count: u32, length: u64| {
+        let mut changed = bytes.clone();
+        changed[6..10].copy_from_slice(&count.to_be_bytes());
+        changed[10..18].copy_from_slice(&length.to_be_bytes());
+        changed
+    };
+    for (count, length) in [
+        (1, cipher_text.len() as u64),
+        (3, cipher_text.len() as u64),
+        (u32::MAX, cipher_text.len() as u64),
+        (2, 30),
+        (2, 61),
+        (2, 0),
+        (0, cipher_text.len() as u64),
+        (2, u64::MAX),
+    ] {
+        let changed = with_header(count, length);
+        assert!(
+            matches!(
+                Ciphertext::from_bytes(&changed, curve),
+                Err(Error::ChunkCountMismatch) | Err(Error::InvalidEncoding(_))
+            ),
+            "{} chunks {} bytes",
+            count,
+            length
+        );
+    }
+    assert_eq!(
+        Ciphertext::from_bytes(&with_header(1, cipher_text.len() as u64), curve),
+        Err(Error::ChunkCountMismatch)
+    );
+    // A shorter length that still needs two chunks is read, it's decrypting that notices the second chunk
+    //  holds more than the one byte it should.
+    let shorter = Ciphertext::from_bytes(&with_header(2, 31), curve).unwrap();
+    assert_eq!(shorter.len(), 31);
+    assert_eq!(
+        el_gamal::try_decrypt_bytes(el_gamal.private_key(), curve, &shorter),
+        Err(Error::ChunkTooLong)
+    );
+}
+
+#[test]
+fn trailing_bytes() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let bytes = cipher_text.to_bytes(curve).unwrap();
+    let mut extra_byte = bytes.clone();
+    extra_byte.push(0);
+    let mut extra_chunk = bytes.clone();
+    extra_chunk.extend(&bytes[HEADER_LEN..HEADER_LEN + 66]);
+    let mut extra_infinity = bytes.clone();
+    extra_infinity.push(0x00);
+    for changed in [extra_byte, extra_chunk, extra_infinity] {
+        assert!(invalid_encoding(&changed, curve));
+    }
+}
+
+#[test]
+fn invalid_points() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let bytes = cipher_text.to_bytes(curve).unwrap();
+    let second_point = HEADER_LEN + 33;
+
+    // Unknown prefixes, and an x of p or more.
+    for prefix in [0x01, 0x05, 0x06, 0xFF] {
+        let mut changed = bytes.clone();
+        changed[second_point] = prefix;
+        assert!(invalid_encoding(&changed, curve), "prefix {:#x}", prefix);
+    }
+    let mut too_big = bytes.clone();
+    too_big[second_point + 1..second_point + 33].fill(0xFF);
+    assert!(invalid_encoding(&too_big, curve));
+
+    // An x that no point has, found by trying each one from 0 up.
+    let mut x = 0u8;
+    let not_on_curve = loop {
+        let mut changed = bytes.clone();
+        changed[second_point + 1..second_point + 33].fill(0);
+        changed[second_point + 32] = x;
+        match Ciphertext::from_bytes(&changed, curve) {
+            Err(Error::NotOnCurve) => break changed,
+            Ok(_) => x += 1,
+            Err(e) => panic!("{:?}", e),
+        }
+    };
+    assert_eq!(
+        Ciphertext::from_bytes(&not_on_curve, curve),
+        Err(Error::NotOnCurve)
+    );
+}
+
+#[test]
+fn armored() {
+    let (el_gamal, cipher_text) = two_chunks();
+    let curve = el_gamal.curve();
+    let armored = cipher_text.to_armored(curve).unwrap();
+    assert!(armored.starts_with("-----BEGIN EL GAMAL CIPHERTEXT-----\n"));
+    assert!(armored.ends_with("-----END EL GAMAL CIPHERTEXT-----\n"));
+    assert!(armored.lines().all(|line| line.len() <= 64));
+    assert_eq!(
+        Ciphertext::from_armored(&armored, curve).as_ref(),
+        Ok(&cipher_text)
+    );
+    // Text around the block is ignored.
+    let surrounded = format!("config:\n{}trailing", armored);
+    assert_eq!(
+        Ciphertext::from_armored(&surrounded, curve).as_ref(),
+        Ok(&cipher_text)
+    );
+
+    let body = pem::base64_encode(&cipher_text.to_bytes(curve).unwrap());
+    let block = |body: &str| {
+        format!(
+            "-----BEGIN EL GAMAL CIPHERTEXT-----\n{}\n-----END EL GAMAL CIPHERTEXT-----\n",
+            body
+        )
+    };
+    let truncated_bytes = pem::base64_encode(&cipher_text.to_bytes(curve).unwrap()[..100]);
+    for text in [
+        armored.replace("EL GAMAL CIPHERTEXT", "PUBLIC KEY"),
+        armored.replace("-----END EL GAMAL CIPHERTEXT-----", ""),
+        armored.replace("-----BEGIN EL GAMAL CIPHERTEXT-----", ""),
+        block(&body[..body.len() - 1]),
+        block(&body.replacen('A', "*", 1)),
+        block(&format!("{}====", body)),
+        block(&format!("=={}", &body[2..])),
+        block(&truncated_bytes),
+        String::new(),
+    ] {
+        assert!(
+            matches!(
+                Ciphertext::from_armored(&text, curve),
+                Err(Error::InvalidEncoding(_))
+            ),
+            "{}",
+            text
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use encryption::curve_builder::{
+    CurveError, EllipticCurveBuilder, Weakness, MAX_GENERATED_COFACTOR,
+};
+use encryption::el_gamal::{self, Curves};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::EllipticType;
+use encryption::error::Error;
+use encryption::point_counting::SCHOOF_BITS;
+use encryption::primes::{factor, factor_with, is_probable_prime, is_probable_prime_with};
+use num_bigint::BigUint;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn big(value: u64) -> BigUint {
+    BigUint::from(value)
+}
+
+// A builder with every parameter of the given named curve except the cofactor.
+fn builder(curve: &EllipticCurve) -> EllipticCurveBuilder {
+    let (x, y) = match curve.init_point() {
+        EllipticType::Point(point) => (point.x().value().clone(), point.y().value().clone()),
+        EllipticType::Infinity(_) => panic!("init_point is Infinity"),
+    };
+    EllipticCurveBuilder::new(curve.field().clone())
+        .a(curve.a().value().clone())
+        .b(curve.b().value().clone())
+        .init_point(x, y)
+        .order(curve.order().unwrap().clone())
+}
+
+fn invalid(builder: EllipticCurveBuilder) -> CurveError {
+    match builder.build() {
+        Err(Error::InvalidCurve(e)) => e,
+        other => panic!("expected InvalidCurve, got {:?}", other),
+    }
+}
+
+#[test]
+fn primes() {
+    let primes = [
+        2,
+        3,
+        997,
+        1009,
+        65_537,
+        2_147_483_647,
+        18_446_744_073_709_551_557,
+    ];
+    for prime in primes {
+        assert!(is_probable_prime(&big(prime)), "{}", prime);
+    }
+    for named in Curves::all() {
+        let curve = named.into_curve();
+        assert!(is_probable_prime(curve.field()));
+        assert!(is_probable_prime(curve.order().unwrap()));
+    }
+    // Carmichael numbers fool the Fermat test, 3_215_031_751 is a strong pseudoprime to the bases 2, 3, 5,
+    //  and 7.
+    let composites = [
+        0,
+        1,
+        4,
+        561,
+        1_105,
+        41_041,
+        825_265,
+        3_215_031_751,
+        1_000_001,
+    ];
+    for composite in composites {
+        assert!(!is_probable_prime(&big(composite)), "{}", composite);
+    }
+    assert!(!is_probable_prime(&(big(1 << 32) * big(1 << 32) + 1u32)));
+}
+
+// Named curves rebuilt from their parameters come out the same, with the cofactor worked out when it isn't
+//  given.
+#[test]
+fn rebuilds_named_curves() {
+    for named in Curves::all() {
+        let curve = named.into_curve();
+        assert_eq!(builder(&curve).build().unwrap(), curve);
+        assert_eq!(
+            builder(&curve)
+                .cofactor(curve.cofactor().unwrap().clone())
+                .build()
+                .unwrap(),
+            curve
+        );
+        assert!(curve.weaknesses().is_empty());
+    }
+
+    // Fields over SCHOOF_BITS are too large to count the points on, the order has to be given.
+    let curve = Curves::Secp256k1.into_curve();
+    let (x, y) = match curve.init_point() {
+        EllipticType::Point(point) => (point.x().value().clone(), point.y().value().clone()),
+        EllipticType::Infinity(_) => panic!("init_point is Infinity"),
+    };
+    assert_eq!(
+        invalid(
+            EllipticCurveBuilder::new(curve.field().clone())
+                .a(big(0))
+                .b(big(7))
+                .init_point(x, y)
+        ),
+        CurveError::OrderRequired
+    );
+}
+
+#[test]
+fn factors() {
+    let factored = |n: u64| {
+        factor(&big(n)).map(|factors| {
+            factors
+                .into_iter()
+                .map(|(p, e)| (p.try_into().unwrap(), e))
+                .collect::<Vec<(u64, u32)>>()
+        })
+    };
+    assert_eq!(factored(0), None);
+    assert_eq!(factored(1), Some(vec![]));
+    assert_eq!(factored(65_439), Some(vec![(3, 2), (11, 1), (661, 1)]));
+    assert_eq!(factored(1 << 40), Some(vec![(2, 40)]));
+    // Both factors are past trial division, so Pollard's rho has to split them.
+    assert_eq!(
+        factored(1_000_003 * 999_983 * 999_983),
+        Some(vec![(999_983, 2), (1_000_003, 1)])
+    );
+    assert_eq!(
+        factored(4 * 2_147_483_647),
+        Some(vec![(2, 2), (2_147_483_647, 1)])
+    );
+    for named in Curves::all() {
+        let curve = named.into_curve();
+        let n = curve.order().unwrap();
+        assert_eq!(factor(n), Some(vec![(n.clone(), 1)]));
+    }
+}
+
+// Without an order the points are counted and the order of init_point worked out from the count, and the curve
+//  is checked the same as if it had been given. These are the curves from cofactors, anomalous, and mov below.
+#[test]
+fn without_order() {
+    let curve = |p: u64, a: u64, b: u64, x: u64, y: u64| {
+        EllipticCurveBuilder::new(big(p))
+            .a(big(a))
+            .b(big(b))
+            .init_point(big(x), big(y))
+    };
+
+    let built = curve(65_539, 2, 1, 9_998, 621).build().unwrap();
+    assert_eq!(built.order(), Some(&big(661)));
+    assert_eq!(built.cofactor(), Some(&big(99)));
+    assert_eq!(
+        built,
+        curve(65_539, 2, 1, 9_998, 621)
+            .order(big(661))
+            .build()
+            .unwrap()
+    );
+    assert_eq!(
+        curve(65_539, 2, 1, 9_998, 621).cofactor(big(99)).build(),
+        Ok(built)
+    );
+    assert_eq!(
+        invalid(curve(65_539, 2, 1, 9_998, 621).cofactor(big(98))),
+        CurveError::WrongCofactor
+    );
+
+    // The count pins down the cofactor even when the order is too small to.
+    assert_eq!(
+        invalid(curve(65_539, 2, 1, 37_776, 43_641)),
+        CurveError::Weak(Weakness::Mov(1))
+    );
+    let order_11 = curve(65_539, 2, 1, 37_776, 43_641)
+        .allow_weak(true)
+        .build()
+        .unwrap();
+    assert_eq!(order_11.order(), Some(&big(11)));
+    assert_eq!(order_11.cofactor(), Some(&big(5_949)));
+
+    // The sum of the points of order 11 and 661 has order 11 * 661.
+    let order_661 = curve(65_539, 2, 1, 9_998, 621).build().unwrap();
+    let sum = order_661
+        .init_point()
+        .group_op(order_11.init_point(), &order_661);
+    let (x, y) = match sum {
+        EllipticType::Point(point) => (point.x().value().clone(), point.y().value().clone()),
+        EllipticType::Infinity(_) => panic!("the sum is Infinity"),
+    };
+    assert_eq!(
+        invalid(
+            EllipticCurveBuilder::new(big(65_539))
+                .a(big(2))
+                .b(big(1))
+                .init_point(x, y)
+        ),
+        CurveError::OrderNotPrime
+    );
+
+    assert_eq!(
+        invalid(curve(65_543, 10, 47, 2, 4_871)),
+        CurveError::Weak(Weakness::Anomalous)
+    );
+    assert_eq!(
+        invalid(curve(66_523, 1, 0, 1_061, 51_263)),
+        CurveError::Weak(Weakness::Mov(2))
+    );
+    let built = curve(66_523, 1, 0, 1_061, 51_263)
+        .allow_weak(true)
+        .build()
+        .unwrap();
+    assert_eq!(built.order(), Some(&big(16_631)));
+    assert_eq!(built.weaknesses(), vec![Weakness::Mov(2)]);
+    // A generated curve rebuilt from just its parameters.
+    let generated = EllipticCurve::generate_seeded(40, 7);
+    let (x, y) = match generated.init_point() {
+        EllipticType::Point(point) => (point.x().value().clone(), point.y().value().clone()),
+        EllipticType::Infinity(_) => panic!("init_point is Infinity"),
+    };
+    let rebuilt = EllipticCurveBuilder::new(generated.field().clone())
+        .a(generated.a().value().clone())
+        .b(generated.b().value().clone())
+        .init_point(x, y)
+        .build()
+        .unwrap();
+    assert_eq!(rebuilt, generated);
+    assert_eq!(rebuilt.cofactor(), generated.cofactor());
+}
+
+#[test]
+fn invalid_parameters() {
+    let curve = Curves::Secp256k1.into_curve();
+    let p = curve.field().clone();
+    let n = curve.order().unwrap().clone();
+
+    assert_eq!(
+        invalid(builder(&curve).a(p.clone())),
+        CurveError::NotInField("a")
+    );
+    assert_eq!(
+        invalid(EllipticCurveBuilder::new(p.clone()).a(big(0))),
+        CurveError::Missing("b")
+    );
+    assert_eq!(
+        invalid(EllipticCurveBuilder::new(p.clone()).a(big(0)).b(big(7))),
+        CurveError::Missing("init_point")
+    );
+    assert_eq!(
+        invalid(builder(&curve).init_point(big(1), big(2))),
+        CurveError::InitPointNotOnCurve
+    );
+    assert_eq!(
+        invalid(builder(&curve).order(&n + 1u32)),
+        CurveError::OrderNotPrime
+    );
+    let mut other_prime = &n - 2u32;
+    while !is_probable_prime(&other_prime) {
+        other_prime -= 2u32;
+    }
+    assert_eq!(
+        invalid(builder(&curve).order(other_prime)),
+        CurveError::WrongOrder
+    );
+    assert_eq!(
+        invalid(builder(&curve).cofactor(big(2))),
+        CurveError::WrongCofactor
+    );
+
+    // 4 * (-3)^3 + 27 * 2^2 = 0, y^2 = x^3 - 3x + 2 = (x - 1)^2 (x + 2).
+    let singular = EllipticCurveBuilder::new(p.clone())
+        .a(&p - 3u32)
+        .b(big(2))
+        .init_point(big(1), big(0));
+    assert_eq!(invalid(singular), CurveError::Singular);
+
+    assert_eq!(
+        invalid(builder(&curve).a(big(0)).b(big(0))),
+        CurveError::Singular
+    );
+    assert_eq!(
+        invalid(EllipticCurveBuilder::new(big(65_537))),
+        CurveError::FieldTooSmall
+    );
+    assert_eq!(
+        invalid(EllipticCurveBuilder::new(&p + 2u32)),
+        CurveError::FieldNotPrime
+    );
+}
+
+// y^2 = x^3 + 2x + 1 mod 65_539 has 65_439 = 3^2 * 11 * 661 points. (37_776, 43_641) has order 11, too small
+//  to pick one of the many cofactors that fit the Hasse bound. (9_998, 621) has order 661, which only one
+//   does.
+#[test]
+fn cofactors() {
+    let builder = EllipticCurveBuilder::new(big(65_539))
+        .a(big(2))
+        .b(big(1))
+        .init_point(big(37_776), big(43_641))
+        .order(big(11));
+    assert_eq!(invalid(builder.clone()), CurveError::AmbiguousCofactor);
+    assert_eq!(
+        invalid(builder.clone().cofactor(big(5_000))),
+        CurveError::WrongCofactor
+    );
+    // p = 1 mod 11.
+    assert_eq!(
+        invalid(builder.clone().cofactor(big(5_949))),
+        CurveError::Weak(Weakness::Mov(1))
+    );
+    let curve = builder
+        .cofactor(big(5_949))
+        .allow_weak(true)
+        .build()
+        .unwrap();
+    assert_eq!(curve.group_order(), Some(big(65_439)));
+
+    let builder = EllipticCurveBuilder::new(big(65_539))
+        .a(big(2))
+        .b(big(1))
+        .init_point(big(9_998), big(621))
+        .order(big(661));
+    assert_eq!(
+        invalid(builder.clone().cofactor(big(98))),
+        CurveError::WrongCofactor
+    );
+    assert_eq!(builder.build().unwrap().cofactor(), Some(&big(99)));
+}
+
+// y^2 = x^3 + 10x + 47 mod 65_543 has 65_543 points.
+#[test]
+fn anomalous() {
+    let builder = EllipticCurveBuilder::new(big(65_543))
+        .a(big(10))
+        .b(big(47))
+        .init_point(big(2), big(4_871))
+        .order(big(65_543));
+    assert_eq!(
+        invalid(builder.clone()),
+        CurveError::Weak(Weakness::Anomalous)
+    );
+    let curve = builder.allow_weak(true).build().unwrap();
+    assert_eq!(curve.cofactor(), Some(&big(1)));
+    assert_eq!(curve.weaknesses(), vec![Weakness::Anomalous]);
+}
+
+// y^2 = x^3 + x mod 66_523 is supersingular with 66_524 = 4 * 16_631 points, so the order divides p^2 - 1.
+#[test]
+fn mov() {
+    let builder = EllipticCurveBuilder::new(big(66_523))
+        .a(big(1))
+        .b(big(0))
+        .init_point(big(1_061), big(51_263))
+        .order(big(16_631));
+    assert_eq!(invalid(builder.clone()), CurveError::Weak(Weakness::Mov(2)));
+    let curve = builder.allow_weak(true).build().unwrap();
+    assert_eq!(curve.cofactor(), Some(&big(4)));
+    assert_eq!(curve.weaknesses(), vec![Weakness::Mov(2)]);
+
+    // A weak curve still works, it just isn't safe.
+    let message = "weak".to_string();
+    let el_gamal = el_gamal::ElGamal::new(Curves::Custom(curve));
+    let cipher_text = el_gamal::encrypt(el_gamal.public_key(), el_gamal.curve(), &message);
+    assert_eq!(
+        el_gamal::decrypt(el_gamal.private_key(), el_gamal.curve(), &cipher_text),
+        message
+    );
+}
+
+// Generated curves pass the builder's checks, have a prime order and a small cofactor that match the number of
+//  points, and the same seed gives the same curve.
+#[test]
+fn generate() {
+    for bits in [18, 24, 40] {
+        let curve = EllipticCurve::generate(bits, &mut rand::thread_rng());
+        assert_eq!(curve.field().bits(), bits);
+        assert!(is_probable_prime(curve.order().unwrap()));
+        assert!(curve.cofactor().unwrap() <= &big(MAX_GENERATED_COFACTOR as u64));
+        assert_eq!(curve.count_points().ok(), curve.group_order());
+        assert!(curve.weaknesses().is_empty());
+        assert_eq!(builder(&curve).build().unwrap(), curve);
+    }
+
+    let curve = EllipticCurve::generate_seeded(32, 25);
+    let message = "generated".to_string();
+    let el_gamal = el_gamal::ElGamal::new(Curves::Custom(curve));
+    let cipher_text = el_gamal::encrypt(el_gamal.public_key(), el_gamal.curve(), &message);
+    assert_eq!(
+        el_gamal::decrypt(el_gamal.private_key(), el_gamal.curve(), &cipher_text),
+        message
+    );
+
+    assert_eq!(
+        EllipticCurve::try_generate(17, &mut rand::thread_rng()),
+        Err(Error::InvalidCurve(CurveError::FieldTooSmall))
+    );
+    assert_eq!(
+        EllipticCurve::try_generate(SCHOOF_BITS + 1, &mut rand::thread_rng()),
+        Err(Error::FieldTooLargeToCount)
+    );
+}
+
+// Every random choice generate makes, the Miller-Rabin bases and the points used to count, comes from the rng
+//  it's given, so the same seed gives the same curve every time.
+#[test]
+fn generate_seeded() {
+    for (bits, seed) in [(24, 1), (32, 25), (40, 3)] {
+        let curve = EllipticCurve::generate_seeded(bits, seed);
+        assert_eq!(EllipticCurve::generate_seeded(bits, seed), curve);
+        assert_eq!(
+            EllipticCurve::generate(bits, &mut StdRng::seed_from_u64(seed)),
+            curve
+        );
+        assert_ne!(EllipticCurve::generate_seeded(bits, seed + 1), curve);
+    }
+
+    let mut first = StdRng::seed_from_u64(5);
+    let mut second = StdRng::seed_from_u64(5);
+    for n in [2_147_483_647u64, 3_215_031_751, 18_446_744_073_709_551_557] {
+        assert_eq!(
+            is_probable_prime_with(&big(n), &mut first),
+            is_probable_prime_with(&big(n), &mut second)
+        );
+    }
+    // Both rngs made the same choices so they're still in step.
+    assert_eq!(first.gen::<u64>(), second.gen::<u64>());
+    assert_eq!(
+        factor_with(&big(1_000_003 * 999_983), &mut first),
+        factor(&big(1_000_003 * 999_983))
+    );
+}
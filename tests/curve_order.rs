@@ -0,0 +1,105 @@
+use encryption::el_gamal::{Curves, ElGamal};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Point};
+use encryption::error::Error;
+use encryption::modular_numbers::ModNum;
+use num_bigint::BigUint;
+use std::collections::HashSet;
+
+fn big(value: u32) -> BigUint {
+    BigUint::from(value)
+}
+
+fn num(value: u32) -> ModNum {
+    ModNum::new(&big(value), &big(211))
+}
+
+fn point(x: u32, y: u32) -> EllipticType {
+    EllipticType::Point(Point::new(num(x), num(y), &num(2), &num(3)))
+}
+
+// y^2 = x^3 + 2x + 3 mod 211 has 204 = 4 * 51 points, (3, 6) generates the subgroup of order 51.
+fn small_curve() -> EllipticCurve {
+    EllipticCurve::new_with_order(num(2), num(3), point(3, 6), big(51), big(4))
+}
+
+#[test]
+fn group_order() {
+    let curve = small_curve();
+    assert_eq!(curve.order(), Some(&big(51)));
+    assert_eq!(curve.cofactor(), Some(&big(4)));
+    assert_eq!(curve.group_order(), Some(big(204)));
+    assert_eq!(curve.scalar_bound(), &big(51));
+
+    for named in Curves::all() {
+        let curve = named.into_curve();
+        let (n, h) = (curve.order().unwrap(), curve.cofactor().unwrap());
+        assert_eq!(curve.group_order(), Some(n * h));
+        assert_eq!(curve.scalar_bound(), n);
+        assert!(matches!(
+            curve.init_point().pow(n, &curve),
+            EllipticType::Infinity(_)
+        ));
+    }
+
+    let no_order = EllipticCurve::new(num(2), num(3), point(3, 6));
+    assert_eq!(no_order.group_order(), None);
+    assert_eq!(no_order.scalar_bound(), &big(211));
+}
+
+#[test]
+fn invalid_order() {
+    for (order, cofactor) in [(0, 4), (51, 0), (0, 0)] {
+        assert_eq!(
+            EllipticCurve::try_new_with_order(
+                num(2),
+                num(3),
+                point(3, 6),
+                big(order),
+                big(cofactor)
+            ),
+            Err(Error::InvalidOrder)
+        );
+    }
+}
+
+// Private keys come from [1, n), every value in it turns up and nothing outside it does.
+#[test]
+fn keys_in_range() {
+    let mut seen = HashSet::new();
+    for _ in 0..2_000 {
+        let el_gamal = ElGamal::new(Curves::Custom(small_curve()));
+        let key = el_gamal.private_key().clone();
+        assert!(key >= big(1) && key < big(51), "{}", key);
+        seen.insert(key);
+    }
+    assert_eq!(seen.len(), 50);
+
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    assert!(el_gamal.private_key() < el_gamal.curve().order().unwrap());
+}
+
+// pow reduces the exponent by the number of points on the curve, which works for points outside the subgroup
+//  generated by init_point too. (145, 50) has order 4.
+#[test]
+fn scalar_reduction() {
+    let curve = small_curve();
+    let no_order = EllipticCurve::new(num(2), num(3), point(3, 6));
+    for base in [point(3, 6), point(145, 50), point(20, 5)] {
+        for exp in [0, 1, 3, 50, 51, 203] {
+            let expected = base.pow(&big(exp), &no_order);
+            for multiple in [0, 1, 5] {
+                let exp = big(exp) + big(204) * big(multiple);
+                assert_eq!(base.pow(&exp, &curve), expected);
+                assert_eq!(base.pow_secret(&exp, &curve), expected);
+                assert_eq!(base.pow(&exp, &no_order), expected);
+            }
+        }
+    }
+    for exp in [0, 1, 50, 51, 52, 51 * 7 + 3] {
+        assert_eq!(
+            curve.init_point_pow(&big(exp)),
+            no_order.init_point().pow(&big(exp % 51), &no_order)
+        );
+    }
+}
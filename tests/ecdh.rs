@@ -0,0 +1,107 @@
+use encryption::ecdh::shared_secret;
+use encryption::el_gamal::{Curves, ElGamal};
+use encryption::elliptic_point::EllipticType;
+use encryption::error::Error;
+use num_bigint::BigUint;
+
+// Runs every vector in tests/data/ecdh_cdh.txt. Each one checks that dIUT gives the public key (QIUTx, QIUTy)
+//  and that the secret shared with (QCAVSx, QCAVSy) is ZIUT.
+#[test]
+fn nist_cavs_cdh() {
+    let vectors = include_str!("data/ecdh_cdh.txt");
+    let mut curve = None;
+    let mut fields = Vec::new();
+    let mut count = 0;
+    for line in vectors.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            curve = Some(match &line[1..6] {
+                "P-256" => Curves::TwoFiveSix,
+                "P-384" => Curves::ThreeEightFour,
+                other => panic!("unknown curve {}", other),
+            });
+            continue;
+        }
+        let (_, value) = line.split_once(" = ").unwrap();
+        fields.push(hex(value));
+        if fields.len() == 6 {
+            check_vector(curve.clone().unwrap(), &fields);
+            fields.clear();
+            count += 1;
+        }
+    }
+    assert_eq!(count, 50);
+}
+
+fn check_vector(curve: Curves, fields: &[Vec<u8>]) {
+    let [q_cavs_x, q_cavs_y, d, q_x, q_y, z] = fields else {
+        unreachable!()
+    };
+    let el_gamal = ElGamal::from_private_key(curve, BigUint::from_bytes_be(d)).unwrap();
+    let curve = el_gamal.curve();
+    let mut public_key = vec![0x04];
+    public_key.extend(q_x);
+    public_key.extend(q_y);
+    assert_eq!(el_gamal.public().to_bytes(), public_key);
+
+    let mut their_public = vec![0x04];
+    their_public.extend(q_cavs_x);
+    their_public.extend(q_cavs_y);
+    let their_public = EllipticType::from_sec1_bytes(&their_public, curve).unwrap();
+    assert_eq!(
+        &shared_secret(el_gamal.private_key(), &their_public, curve).unwrap(),
+        z
+    );
+}
+
+#[test]
+fn both_sides_agree() {
+    let alice = ElGamal::new(Curves::TwoFiveSix);
+    let bob = ElGamal::new(Curves::TwoFiveSix);
+    let curve = alice.curve();
+    let secret = shared_secret(alice.private_key(), bob.public_key(), curve).unwrap();
+    assert_eq!(
+        secret,
+        shared_secret(bob.private_key(), alice.public_key(), curve).unwrap()
+    );
+    assert_eq!(secret.len(), 32);
+}
+
+// Public keys that aren't valid are rejected instead of being used.
+#[test]
+fn invalid_public_keys() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let infinity = el_gamal.public_key().pow(curve.order().unwrap(), curve);
+    assert_eq!(
+        shared_secret(el_gamal.private_key(), &infinity, curve),
+        Err(Error::InvalidPublicKey)
+    );
+
+    let other_curve = Curves::ThreeEightFour.into_curve();
+    assert_eq!(
+        shared_secret(el_gamal.private_key(), other_curve.init_point(), curve),
+        Err(Error::FieldMismatch)
+    );
+
+    // From the NIST CAVS KAS validity tests, the peer's public key has had its x or y changed so it is no longer
+    //  on the curve. EllipticType::from_sec1_bytes refuses to build them.
+    for point in [
+        "04eec6ea7be0362fa496af12e551982a7d9c06b5ef735fadc37990c78ab9be87f4f45058db687e98326036c88eb846476a05385d7bda1d6dd6ca600499b7cc613f",
+        "0439883b3aadd78b044dadbd9be6a2cc8360da8df4241a4d11665a14e1ce17d19296d5cc675c252d902da5118245e738d57442ca042add79f1d07752bb98a7b805",
+    ] {
+        assert_eq!(
+            EllipticType::from_sec1_bytes(&hex(point), curve),
+            Err(Error::NotOnCurve)
+        );
+    }
+}
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
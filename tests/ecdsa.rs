@@ -0,0 +1,97 @@
+use encryption::ecdsa::{sign, sign_with_nonce, verify, Signature};
+use encryption::el_gamal::{Curves, ElGamal};
+use num_bigint::BigUint;
+
+// Runs every vector in tests/data/ecdsa_siggen.txt. Each one checks that d gives the public key (Qx, Qy),
+//  that signing H with the nonce k gives (R, S), and that the signature verifies.
+#[test]
+fn nist_cavp_sig_gen() {
+    let vectors = include_str!("data/ecdsa_siggen.txt");
+    let mut curve = None;
+    let mut fields = Vec::new();
+    let mut count = 0;
+    for line in vectors.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            curve = Some(match &line[1..6] {
+                "P-256" => Curves::TwoFiveSix,
+                "P-384" => Curves::ThreeEightFour,
+                "P-521" => Curves::FiveTwoOne,
+                other => panic!("unknown curve {}", other),
+            });
+            continue;
+        }
+        let (_, value) = line.split_once(" = ").unwrap();
+        fields.push(hex(value));
+        if fields.len() == 7 {
+            check_vector(curve.clone().unwrap(), &fields);
+            fields.clear();
+            count += 1;
+        }
+    }
+    assert_eq!(count, 45);
+}
+
+fn check_vector(curve: Curves, fields: &[Vec<u8>]) {
+    let [d, q_x, q_y, k, h, r, s] = fields else {
+        unreachable!()
+    };
+    let el_gamal = ElGamal::from_private_key(curve, BigUint::from_bytes_be(d)).unwrap();
+    let curve = el_gamal.curve();
+    let mut public_key = vec![0x04];
+    public_key.extend(q_x);
+    public_key.extend(q_y);
+    assert_eq!(el_gamal.public().to_bytes(), public_key);
+
+    let signature =
+        sign_with_nonce(el_gamal.private_key(), curve, h, &BigUint::from_bytes_be(k)).unwrap();
+    assert_eq!(signature.r(), &BigUint::from_bytes_be(r));
+    assert_eq!(signature.s(), &BigUint::from_bytes_be(s));
+    assert!(verify(el_gamal.public_key(), curve, h, &signature));
+
+    let mut fixed = r.clone();
+    fixed.extend(s);
+    assert_eq!(signature.to_bytes(curve), fixed);
+    assert_eq!(Signature::from_bytes(&fixed, curve).unwrap(), signature);
+    assert_eq!(
+        Signature::from_der(&signature.to_der(), curve).unwrap(),
+        signature
+    );
+}
+
+#[test]
+fn random_nonce_round_trip() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let hash = [7u8; 32];
+    let signature = sign(el_gamal.private_key(), el_gamal.curve(), &hash).unwrap();
+    assert!(verify(
+        el_gamal.public_key(),
+        el_gamal.curve(),
+        &hash,
+        &signature
+    ));
+    let mut wrong_hash = hash;
+    wrong_hash[0] ^= 1;
+    assert!(!verify(
+        el_gamal.public_key(),
+        el_gamal.curve(),
+        &wrong_hash,
+        &signature
+    ));
+    let other = ElGamal::new(Curves::TwoFiveSix);
+    assert!(!verify(
+        other.public_key(),
+        other.curve(),
+        &hash,
+        &signature
+    ));
+}
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
@@ -0,0 +1,119 @@
+use encryption::chacha20_poly1305::{open, seal};
+use encryption::ecies::{decrypt, encrypt, overhead, x963_kdf};
+use encryption::el_gamal::{Curves, ElGamal};
+use encryption::error::Error;
+use encryption::sha2::Hash;
+
+#[test]
+fn round_trip() {
+    for curve in [
+        Curves::TwoFiveSix,
+        Curves::ThreeEightFour,
+        Curves::FiveTwoOne,
+    ] {
+        let el_gamal = ElGamal::new(curve);
+        let curve = el_gamal.curve();
+        for len in [0, 1, 63, 64, 65, 1000] {
+            let plain_text: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let cipher_text = encrypt(el_gamal.public_key(), curve, &plain_text).unwrap();
+            assert_eq!(cipher_text.len(), plain_text.len() + overhead(curve));
+            assert_eq!(
+                decrypt(el_gamal.private_key(), curve, &cipher_text).unwrap(),
+                plain_text
+            );
+        }
+    }
+}
+
+#[test]
+fn tampering_is_caught() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let cipher_text = encrypt(el_gamal.public_key(), curve, b"attack at dawn").unwrap();
+
+    // Flipping a bit of the encrypted message or the tag.
+    for i in [cipher_text.len() - 20, cipher_text.len() - 1] {
+        let mut tampered = cipher_text.clone();
+        tampered[i] ^= 1;
+        assert_eq!(
+            decrypt(el_gamal.private_key(), curve, &tampered),
+            Err(Error::DecryptionFailed)
+        );
+    }
+
+    // Swapping in a different ephemeral key.
+    let other = encrypt(el_gamal.public_key(), curve, b"attack at dawn").unwrap();
+    let mut tampered = other[..33].to_vec();
+    tampered.extend_from_slice(&cipher_text[33..]);
+    assert_eq!(
+        decrypt(el_gamal.private_key(), curve, &tampered),
+        Err(Error::DecryptionFailed)
+    );
+
+    let wrong_key = ElGamal::new(Curves::TwoFiveSix);
+    assert_eq!(
+        decrypt(wrong_key.private_key(), curve, &cipher_text),
+        Err(Error::DecryptionFailed)
+    );
+    assert!(decrypt(el_gamal.private_key(), curve, &cipher_text[..40]).is_err());
+}
+
+// RFC 8439 section 2.8.2.
+#[test]
+fn chacha20_poly1305_rfc8439() {
+    let key: [u8; 32] = core::array::from_fn(|i| 0x80 + i as u8);
+    let nonce: [u8; 12] = hex("070000004041424344454647").try_into().unwrap();
+    let associated_data = hex("50515253c0c1c2c3c4c5c6c7");
+    let plain_text = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+    let sealed = hex(concat!(
+        "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6",
+        "3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36",
+        "92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc",
+        "3ff4def08e4b7a9de576d26586cec64b6116",
+        "1ae10b594f09e26a7e902ecbd0600691",
+    ));
+    assert_eq!(seal(&key, &nonce, &associated_data, plain_text), sealed);
+    assert_eq!(
+        open(&key, &nonce, &associated_data, &sealed).unwrap(),
+        plain_text
+    );
+    assert_eq!(
+        open(&key, &nonce, b"", &sealed),
+        Err(Error::DecryptionFailed)
+    );
+}
+
+// From the NIST CAVS ANSI X9.63 KDF vectors for SHA-256.
+#[test]
+fn x963_kdf_vectors() {
+    assert_eq!(
+        x963_kdf(
+            Hash::Sha256,
+            &hex("96c05619d56c328ab95fe84b18264b08725b85e33fd34f08"),
+            &[],
+            16
+        ),
+        hex("443024c3dae66b95e6f5670601558f71")
+    );
+    assert_eq!(
+        x963_kdf(
+            Hash::Sha256,
+            &hex("22518b10e70f2a3f243810ae3254139efbee04aa57c7af7d"),
+            &hex("75eef81aa3041e33b80971203d2c0c52"),
+            128
+        ),
+        hex(concat!(
+            "c498af77161cc59f2962b9a713e2b215152d139766ce34a776df11866a69bf2e",
+            "52a13d9c7c6fc878c50c5ea0bc7b00e0da2447cfd874f6cf92f30d0097111485",
+            "500c90c3af8b487872d04685d14c8d1dc8d7fa08beb0ce0ababc11f0bd496269",
+            "142d43525a78e5bc79a17f59676a5706dc54d54d4d1f0bd7e386128ec26afc21",
+        ))
+    );
+}
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
@@ -0,0 +1,287 @@
+use encryption::ciphertext::Ciphertext;
+use encryption::el_gamal::{self, Curves, ElGamal};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Inf, Point, ScalarMul};
+use encryption::error::Error;
+use encryption::modular_numbers::ModNum;
+use num_bigint::BigUint;
+
+fn big(value: u32) -> BigUint {
+    BigUint::from(value)
+}
+
+// y^2 = x^3 + 2x + 3 over the given field, with the first point found from x = 0 up as its init_point.
+fn curve(field: u32) -> EllipticCurve {
+    let field = big(field);
+    let (a, b) = (ModNum::new(&big(2), &field), ModNum::new(&big(3), &field));
+    let mut x = ModNum::new(&big(0), &field);
+    loop {
+        if let Some(y) = (&x * &x * &x + &a * &x + &b).sqrt() {
+            let point = EllipticType::Point(Point::new(x, y, &a, &b));
+            return EllipticCurve::new(a, b, point);
+        }
+        x += ModNum::new(&big(1), &field);
+    }
+}
+
+// Fields below 2^16 leave no room for a byte of message next to the W byte, so encrypting or decrypting on
+//  them is an Error rather than a panic. 65_537 is the smallest field that works, one byte per chunk.
+#[test]
+fn field_too_small() {
+    for field in [211, 257, 65_521] {
+        let el_gamal = ElGamal::try_new(Curves::Custom(curve(field))).unwrap();
+        let curve = el_gamal.curve();
+        assert_eq!(
+            el_gamal::try_encrypt_bytes(el_gamal.public_key(), curve, b"small"),
+            Err(Error::FieldTooSmall)
+        );
+        let cipher_text = Ciphertext::new(vec![], 0);
+        assert_eq!(
+            el_gamal::try_decrypt_bytes(el_gamal.private_key(), curve, &cipher_text),
+            Err(Error::FieldTooSmall)
+        );
+        assert_eq!(
+            Ciphertext::from_bytes(&cipher_text.to_bytes(curve).unwrap(), curve),
+            Err(Error::FieldTooSmall)
+        );
+    }
+
+    let el_gamal = ElGamal::new(Curves::Custom(curve(65_537)));
+    let curve = el_gamal.curve();
+    let message = [0, 1, 255, 0, 0];
+    let cipher_text = el_gamal::encrypt_bytes(el_gamal.public_key(), curve, &message);
+    assert_eq!(cipher_text.chunks().len(), message.len());
+    assert_eq!(
+        el_gamal::decrypt_bytes(el_gamal.private_key(), curve, &cipher_text),
+        message
+    );
+}
+
+// The number of message bytes in each chunk, see el_gamal::chunk_size.
+fn chunk_size(curve: &EllipticCurve) -> usize {
+    (curve.field().bits() as usize - 1) / 8 - 1
+}
+
+// Lengths either side of multiples of the chunk size, with tails of zeros, which are lost if the chunk is
+//  only turned into a number and back, and of 0xFF, the largest a chunk can be.
+#[test]
+fn bytes_round_trip() {
+    for curve in [Curves::TwoFiveSix, Curves::Custom(curve(65_537))] {
+        let el_gamal = ElGamal::new(curve);
+        let curve = el_gamal.curve();
+        let n = chunk_size(curve);
+        let mut lengths = vec![0, 1];
+        for chunks in 1..=3 {
+            lengths.extend([chunks * n - 1, chunks * n, chunks * n + 1]);
+        }
+        for length in lengths {
+            for tail in [0x00, 0xFF] {
+                for tail_len in [1, n.min(length), length] {
+                    let mut message: Vec<u8> = (0..length).map(|i| (i * 37 + 1) as u8).collect();
+                    let tail_start = length - tail_len.min(length);
+                    message[tail_start..].fill(tail);
+                    let cipher_text =
+                        el_gamal::encrypt_bytes(el_gamal.public_key(), curve, &message);
+                    assert_eq!(cipher_text.len(), length);
+                    assert_eq!(cipher_text.chunks().len(), length.div_ceil(n));
+                    assert_eq!(
+                        el_gamal::decrypt_bytes(el_gamal.private_key(), curve, &cipher_text),
+                        message,
+                        "length {} tail {:#x} * {}",
+                        length,
+                        tail,
+                        tail_len
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Every byte value, including ones that aren't valid utf8 anywhere.
+#[test]
+fn binary_round_trip() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let message: Vec<u8> = (0..=255).rev().collect();
+    let cipher_text = el_gamal::encrypt_bytes(el_gamal.public_key(), el_gamal.curve(), &message);
+    assert_eq!(
+        el_gamal::decrypt_bytes(el_gamal.private_key(), el_gamal.curve(), &cipher_text),
+        message
+    );
+    assert!(matches!(
+        el_gamal::try_decrypt(el_gamal.private_key(), el_gamal.curve(), &cipher_text),
+        Err(Error::InvalidUtf8(_))
+    ));
+}
+
+// Multi-byte characters placed so they're split between two chunks come back whole.
+#[test]
+fn utf8_across_chunks() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let n = chunk_size(el_gamal.curve());
+    for character in ["é", "€", "🦀"] {
+        for offset in 0..character.len() {
+            let message = "a".repeat(n - offset) + &character.repeat(3);
+            let cipher_text = el_gamal::encrypt(el_gamal.public_key(), el_gamal.curve(), &message);
+            assert_eq!(
+                el_gamal::decrypt(el_gamal.private_key(), el_gamal.curve(), &cipher_text),
+                message
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_round_trip() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let cipher_text = el_gamal::encrypt_bytes(el_gamal.public_key(), curve, &[]);
+    assert!(cipher_text.is_empty());
+    assert!(cipher_text.chunks().is_empty());
+    assert_eq!(
+        el_gamal::decrypt_bytes(el_gamal.private_key(), curve, &cipher_text),
+        Vec::<u8>::new()
+    );
+    let message = String::new();
+    let cipher_text = el_gamal::encrypt(el_gamal.public_key(), curve, &message);
+    assert_eq!(
+        el_gamal::decrypt(el_gamal.private_key(), curve, &cipher_text),
+        message
+    );
+    assert_eq!(
+        Ciphertext::from_bytes(&cipher_text.to_bytes(curve).unwrap(), curve),
+        Ok(cipher_text)
+    );
+}
+
+// A curve whose order is 1 has no scalars in [1, n) to use as a key or ephemeral s.
+#[test]
+fn try_new_errors() {
+    let small = curve(65_537);
+    let order_one = EllipticCurve::new_with_order(
+        small.a().clone(),
+        small.b().clone(),
+        small.init_point().clone(),
+        big(1),
+        big(1),
+    );
+    assert_eq!(
+        ElGamal::try_new(Curves::Custom(order_one.clone())).unwrap_err(),
+        Error::InvalidOrder
+    );
+    let el_gamal = ElGamal::new(Curves::Custom(small));
+    assert_eq!(
+        el_gamal::try_encrypt(el_gamal.public_key(), &order_one, &"one".to_string()),
+        Err(Error::InvalidOrder)
+    );
+    assert_eq!(
+        ElGamal::from_private_key(Curves::TwoFiveSix, big(0)).unwrap_err(),
+        Error::InvalidPrivateKey
+    );
+}
+
+#[test]
+fn try_encrypt_errors() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let message = "errors".to_string();
+    let other = ElGamal::new(Curves::ThreeEightFour);
+    for (public_key, error) in [
+        (other.public_key().clone(), Error::FieldMismatch),
+        (
+            EllipticType::Infinity(Inf::in_field(curve.shared_field())),
+            Error::InvalidPublicKey,
+        ),
+    ] {
+        assert_eq!(
+            el_gamal::try_encrypt(&public_key, curve, &message),
+            Err(error.clone())
+        );
+        assert_eq!(
+            el_gamal::try_encrypt_bytes(&public_key, curve, message.as_bytes()),
+            Err(error)
+        );
+    }
+    assert!(el_gamal::try_encrypt(el_gamal.public_key(), curve, &message).is_ok());
+}
+
+// Cipher texts that were tampered with, are for another curve, or are decrypted with the wrong key give an
+//  Error instead of panicking.
+#[test]
+fn try_decrypt_errors() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let key = el_gamal.private_key();
+    let message = "a message long enough to need two chunks".to_string();
+    let cipher_text = el_gamal::encrypt(el_gamal.public_key(), curve, &message);
+
+    let longer = Ciphertext::new(cipher_text.chunks().clone(), message.len() + 100);
+    assert_eq!(
+        el_gamal::try_decrypt(key, curve, &longer),
+        Err(Error::ChunkCountMismatch)
+    );
+
+    let other = ElGamal::new(Curves::ThreeEightFour);
+    let other_cipher_text = el_gamal::encrypt(other.public_key(), other.curve(), &message);
+    assert_eq!(
+        el_gamal::try_decrypt(key, curve, &other_cipher_text),
+        Err(Error::ChunkCountMismatch)
+    );
+    let other_chunk = Ciphertext::new(vec![other_cipher_text.chunks()[0].clone()], 1);
+    assert_eq!(
+        el_gamal::try_decrypt(key, curve, &other_chunk),
+        Err(Error::FieldMismatch)
+    );
+
+    // c_1 = key * c_0 decrypts to Infinity, and c_1 = key * c_0 + G to G, whose x is far too big for a one
+    //  byte chunk.
+    let c_0 = curve.init_point().clone();
+    let key_c_0 = c_0.pow(key, curve);
+    let to_infinity = Ciphertext::new(vec![(c_0.clone(), key_c_0.clone())], 1);
+    assert_eq!(
+        el_gamal::try_decrypt_bytes(key, curve, &to_infinity),
+        Err(Error::DecodedInfinity)
+    );
+    let to_g = Ciphertext::new(vec![(c_0.clone(), key_c_0.group_op(&c_0, curve))], 1);
+    assert_eq!(
+        el_gamal::try_decrypt_bytes(key, curve, &to_g),
+        Err(Error::ChunkTooLong)
+    );
+
+    let wrong_key = ElGamal::new(Curves::TwoFiveSix);
+    assert!(el_gamal::try_decrypt(wrong_key.private_key(), curve, &cipher_text).is_err());
+    assert_eq!(el_gamal::try_decrypt(key, curve, &cipher_text), Ok(message));
+}
+
+#[test]
+fn try_point_operation_errors() {
+    let p_256 = Curves::TwoFiveSix.into_curve();
+    let p_384 = Curves::ThreeEightFour.into_curve();
+    let point = p_256.init_point();
+    let other = p_384.init_point();
+    assert_eq!(point.try_group_op(other, &p_256), Err(Error::FieldMismatch));
+    assert_eq!(point.try_group_op(point, &p_384), Err(Error::FieldMismatch));
+    assert_eq!(point.try_pow(&big(2), &p_384), Err(Error::FieldMismatch));
+    assert_eq!(
+        point.try_pow_secret(&big(2), &p_384),
+        Err(Error::FieldMismatch)
+    );
+    for method in [
+        ScalarMul::FixedWindow(0),
+        ScalarMul::FixedWindow(9),
+        ScalarMul::Wnaf(1),
+        ScalarMul::Wnaf(9),
+    ] {
+        assert_eq!(
+            point.try_pow_with(&big(2), &p_256, method),
+            Err(Error::InvalidWindowWidth)
+        );
+    }
+    let double = point.group_op(point, &p_256);
+    assert_eq!(point.try_pow(&big(2), &p_256), Ok(double.clone()));
+    assert_eq!(point.try_pow_secret(&big(2), &p_256), Ok(double.clone()));
+    assert_eq!(
+        point.try_pow_with(&big(2), &p_256, ScalarMul::Wnaf(8)),
+        Ok(double)
+    );
+}
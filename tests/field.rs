@@ -0,0 +1,63 @@
+use encryption::el_gamal::Curves;
+use encryption::error::Error;
+use encryption::field::Field;
+use encryption::modular_numbers::ModNum;
+use num_bigint::BigUint;
+use std::sync::Arc;
+
+// There's only ever one Field for a modulus, so everything made from the same modulus shares it.
+#[test]
+fn fields_are_shared() {
+    let modulus = BigUint::from(1_000_003u32);
+    let field = Field::new(&modulus);
+    assert!(Arc::ptr_eq(&field, &Field::new(&modulus)));
+
+    let a = ModNum::new(&BigUint::from(5u32), &modulus);
+    let b = ModNum::in_field(&BigUint::from(7u32), &field);
+    assert!(Arc::ptr_eq(a.shared_field(), &field));
+    assert!(Arc::ptr_eq(a.mul(&b).shared_field(), &field));
+    assert!(Arc::ptr_eq(a.clone().shared_field(), &field));
+    assert_eq!(a.add(&b).value(), &BigUint::from(12u32));
+    assert_eq!(a, ModNum::in_field(&BigUint::from(1_000_008u32), &field));
+
+    let other = Field::new(&BigUint::from(1_000_033u32));
+    assert_ne!(field, other);
+    assert_ne!(a, ModNum::in_field(&BigUint::from(5u32), &other));
+}
+
+#[test]
+fn curve_shares_its_field() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let field = curve.shared_field();
+    assert!(Arc::ptr_eq(curve.b().shared_field(), field));
+    assert!(Arc::ptr_eq(curve.init_point().shared_field(), field));
+    assert!(Arc::ptr_eq(
+        curve.init_point_pow(&BigUint::from(3u32)).shared_field(),
+        field
+    ));
+    assert!(Arc::ptr_eq(&Field::new(curve.field()), field));
+}
+
+// A field that has been dropped is made again from scratch and still works.
+#[test]
+fn dropped_fields_come_back() {
+    let modulus = BigUint::from(65_537u32);
+    let value = ModNum::new(&BigUint::from(3u32), &modulus).pow(&BigUint::from(4u32));
+    assert_eq!(value.value(), &BigUint::from(81u32));
+    drop(value);
+    let again = ModNum::new(&BigUint::from(70_000u32), &modulus);
+    assert_eq!(again.value(), &BigUint::from(70_000u32 - 65_537));
+    assert_eq!(again.field(), &modulus);
+}
+
+#[test]
+fn zero_field() {
+    assert_eq!(
+        Field::try_new(&BigUint::from(0u32)).unwrap_err(),
+        Error::ZeroField
+    );
+    assert_eq!(
+        ModNum::try_new(&BigUint::from(1u32), &BigUint::from(0u32)).unwrap_err(),
+        Error::ZeroField
+    );
+}
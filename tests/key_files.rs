@@ -0,0 +1,297 @@
+use encryption::asn1;
+use encryption::el_gamal::{Curves, ElGamal};
+use encryption::error::Error;
+use encryption::pem;
+use num_bigint::BigUint;
+
+// Keys made with OpenSSL 3.5, see tests/data/openssl.
+//  openssl ecparam -name <curve> -genkey -noout -out sec1.pem
+//  openssl pkcs8 -topk8 -nocrypt -in sec1.pem -out pkcs8.pem
+//  openssl ec -in sec1.pem -pubout -out spki.pem
+// d is the private key from openssl ec -text.
+struct Fixture {
+    curve: Curves,
+    d: &'static str,
+    pkcs8: &'static str,
+    sec1: &'static str,
+    spki: &'static str,
+}
+
+const FIXTURES: [Fixture; 3] = [
+    Fixture {
+        curve: Curves::TwoFiveSix,
+        d: "c968d32d01d6fa314005952cab200e39f499390f3a5f0e206f9edb23f22f57b1",
+        pkcs8: include_str!("data/openssl/p256_pkcs8.pem"),
+        sec1: include_str!("data/openssl/p256_sec1.pem"),
+        spki: include_str!("data/openssl/p256_spki.pem"),
+    },
+    Fixture {
+        curve: Curves::ThreeEightFour,
+        d: "faf834b76f433576d0a6768407722125451ecefea1f850271bb4bd8da844e9c105f5752e8ae3413445a3e126395be9f3",
+        pkcs8: include_str!("data/openssl/p384_pkcs8.pem"),
+        sec1: include_str!("data/openssl/p384_sec1.pem"),
+        spki: include_str!("data/openssl/p384_spki.pem"),
+    },
+    Fixture {
+        curve: Curves::FiveTwoOne,
+        d: "01523b9bb54ea94164ef309554b7687bb94fb915f1fc134902acb31aa1276a0304decf150975e6870481727a297c30d4550a5112d6bd05d7d7de1b5985171158935d",
+        pkcs8: include_str!("data/openssl/p521_pkcs8.pem"),
+        sec1: include_str!("data/openssl/p521_sec1.pem"),
+        spki: include_str!("data/openssl/p521_spki.pem"),
+    },
+];
+
+const P_256: &[u64] = &[1, 2, 840, 10045, 3, 1, 7];
+const P_384: &[u64] = &[1, 3, 132, 0, 34];
+const EC_PUBLIC_KEY: &[u64] = &[1, 2, 840, 10045, 2, 1];
+
+fn hex(value: &str) -> BigUint {
+    BigUint::parse_bytes(value.as_bytes(), 16).unwrap()
+}
+
+fn der(text: &str, label: &str) -> Vec<u8> {
+    pem::decode(label, text).unwrap()
+}
+
+fn invalid_encoding(result: Result<ElGamal, Error>) -> bool {
+    matches!(result, Err(Error::InvalidEncoding(_)))
+}
+
+// OpenSSL's keys read back as the key pair it generated, and writing that key pair out gives the same bytes
+//  OpenSSL wrote.
+#[test]
+fn openssl_keys() {
+    for fixture in &FIXTURES {
+        let name = fixture.curve.name().unwrap();
+        let expected = ElGamal::from_private_key(fixture.curve.clone(), hex(fixture.d)).unwrap();
+        let pkcs8_der = der(fixture.pkcs8, "PRIVATE KEY");
+        let sec1_der = der(fixture.sec1, "EC PRIVATE KEY");
+        let spki_der = der(fixture.spki, "PUBLIC KEY");
+
+        for read in [
+            ElGamal::from_pkcs8_pem(fixture.pkcs8).unwrap(),
+            ElGamal::from_pkcs8_der(&pkcs8_der).unwrap(),
+            ElGamal::from_sec1_pem(fixture.sec1).unwrap(),
+            ElGamal::from_sec1_der(&sec1_der).unwrap(),
+        ] {
+            assert_eq!(read.curve(), expected.curve(), "{}", name);
+            assert_eq!(read.private_key(), expected.private_key(), "{}", name);
+            assert_eq!(read.public_key(), expected.public_key(), "{}", name);
+        }
+        for read in [
+            ElGamal::from_public_key_pem(fixture.spki).unwrap(),
+            ElGamal::from_public_key_der(&spki_der).unwrap(),
+        ] {
+            assert_eq!(read.curve(), expected.curve(), "{}", name);
+            assert_eq!(read.public_key(), expected.public_key(), "{}", name);
+            assert_eq!(read.try_private_key(), Err(Error::MissingPrivateKey));
+        }
+
+        assert_eq!(expected.to_pkcs8_der().unwrap(), pkcs8_der, "{}", name);
+        assert_eq!(expected.to_sec1_der().unwrap(), sec1_der, "{}", name);
+        assert_eq!(expected.to_public_key_der().unwrap(), spki_der, "{}", name);
+        assert_eq!(expected.to_pkcs8_pem().unwrap(), fixture.pkcs8, "{}", name);
+        assert_eq!(expected.to_sec1_pem().unwrap(), fixture.sec1, "{}", name);
+        assert_eq!(
+            expected.to_public_key_pem().unwrap(),
+            fixture.spki,
+            "{}",
+            name
+        );
+    }
+}
+
+// Every named curve survives writing and reading back in each format.
+#[test]
+fn round_trips() {
+    for named in Curves::all() {
+        let el_gamal = ElGamal::new(named);
+        let sec1 = ElGamal::from_sec1_der(&el_gamal.to_sec1_der().unwrap()).unwrap();
+        assert_eq!(sec1.private_key(), el_gamal.private_key());
+        assert_eq!(sec1.curve(), el_gamal.curve());
+        let sec1 = ElGamal::from_sec1_pem(&el_gamal.to_sec1_pem().unwrap()).unwrap();
+        assert_eq!(sec1.private_key(), el_gamal.private_key());
+        let pkcs8 = ElGamal::from_pkcs8_der(&el_gamal.to_pkcs8_der().unwrap()).unwrap();
+        assert_eq!(pkcs8.private_key(), el_gamal.private_key());
+        let public = ElGamal::from_public_key_der(&el_gamal.to_public_key_der().unwrap()).unwrap();
+        assert_eq!(public.public_key(), el_gamal.public_key());
+
+        let encrypt_only =
+            ElGamal::from_public_key_der(&el_gamal.to_public_key_der().unwrap()).unwrap();
+        assert_eq!(encrypt_only.to_sec1_der(), Err(Error::MissingPrivateKey));
+        assert_eq!(encrypt_only.to_pkcs8_der(), Err(Error::MissingPrivateKey));
+    }
+
+    let custom = ElGamal::new(Curves::Custom(Curves::TwoFiveSix.into_curve().clone()));
+    assert!(custom.to_sec1_der().is_ok());
+    let not_named = ElGamal::new(Curves::Custom(
+        encryption::elliptic_curve::EllipticCurve::new(
+            Curves::TwoFiveSix.into_curve().a().clone(),
+            Curves::TwoFiveSix.into_curve().b().clone(),
+            Curves::TwoFiveSix.into_curve().init_point().clone(),
+        ),
+    ));
+    assert_eq!(not_named.to_pkcs8_der(), Err(Error::UnsupportedCurve));
+    assert_eq!(not_named.to_sec1_der(), Err(Error::UnsupportedCurve));
+    assert_eq!(not_named.to_public_key_der(), Err(Error::UnsupportedCurve));
+}
+
+// Lengths have to use the shortest form, OpenSSL's outer PKCS#8 length is 0x81 0x87, and its SubjectPublicKeyInfo
+//  length fits in the short form.
+#[test]
+fn non_minimal_lengths() {
+    let pkcs8 = der(FIXTURES[0].pkcs8, "PRIVATE KEY");
+    assert_eq!(&pkcs8[..3], &[0x30, 0x81, 0x87]);
+    let mut padded = vec![0x30, 0x82, 0x00, 0x87];
+    padded.extend(&pkcs8[3..]);
+    assert!(invalid_encoding(ElGamal::from_pkcs8_der(&padded)));
+
+    let spki = der(FIXTURES[0].spki, "PUBLIC KEY");
+    assert_eq!(&spki[..2], &[0x30, 0x59]);
+    let mut long_form = vec![0x30, 0x81, 0x59];
+    long_form.extend(&spki[2..]);
+    assert!(invalid_encoding(ElGamal::from_public_key_der(&long_form)));
+
+    // The indefinite length form isn't DER.
+    let mut indefinite = vec![0x30, 0x80];
+    indefinite.extend(&spki[2..]);
+    indefinite.extend([0x00, 0x00]);
+    assert!(invalid_encoding(ElGamal::from_public_key_der(&indefinite)));
+
+    // The version written as 0x00 0x00 instead of 0x00.
+    let sec1 = der(FIXTURES[0].sec1, "EC PRIVATE KEY");
+    assert_eq!(&sec1[2..5], &[0x02, 0x01, 0x01]);
+    let mut padded_integer = vec![0x30, sec1[1] + 1, 0x02, 0x02, 0x00, 0x01];
+    padded_integer.extend(&sec1[5..]);
+    assert!(invalid_encoding(ElGamal::from_sec1_der(&padded_integer)));
+}
+
+#[test]
+fn unknown_oids() {
+    let spki = der(FIXTURES[0].spki, "PUBLIC KEY");
+    let public_key = &spki[spki.len() - 66..];
+    let with_oids = |algorithm: &[u64], curve: &[u64]| {
+        asn1::sequence(&[
+            asn1::sequence(&[asn1::oid(algorithm), asn1::oid(curve)]),
+            asn1::encode(asn1::BIT_STRING, public_key),
+        ])
+    };
+    assert_eq!(
+        ElGamal::from_public_key_der(&with_oids(EC_PUBLIC_KEY, P_256))
+            .unwrap()
+            .public_key(),
+        ElGamal::from_public_key_der(&spki).unwrap().public_key()
+    );
+    // secp256r1 with its last arc changed, and secp256k1's neighbour in the SEC 2 arc.
+    for curve in [&[1, 2, 840, 10045, 3, 1, 8][..], &[1, 3, 132, 0, 99]] {
+        assert_eq!(
+            ElGamal::from_public_key_der(&with_oids(EC_PUBLIC_KEY, curve)).unwrap_err(),
+            Error::UnsupportedCurve
+        );
+    }
+    // rsaEncryption instead of id-ecPublicKey.
+    assert!(invalid_encoding(ElGamal::from_public_key_der(&with_oids(
+        &[1, 2, 840, 113549, 1, 1, 1],
+        P_256
+    ))));
+
+    let sec1 = der(FIXTURES[0].sec1, "EC PRIVATE KEY");
+    let p_256_oid = asn1::oid(P_256);
+    let position = sec1
+        .windows(p_256_oid.len())
+        .position(|window| window == p_256_oid)
+        .unwrap();
+    let mut unknown_curve = sec1.clone();
+    unknown_curve[position + p_256_oid.len() - 1] = 8;
+    assert_eq!(
+        ElGamal::from_sec1_der(&unknown_curve).unwrap_err(),
+        Error::UnsupportedCurve
+    );
+}
+
+// The curve named by the PKCS#8 wrapper, by the ECPrivateKey inside it, and by the stored public key all have
+//  to agree.
+#[test]
+fn curve_mismatch() {
+    let p_256_sec1 = der(FIXTURES[0].sec1, "EC PRIVATE KEY");
+    let wrapped = |curve: &[u64], inner: &[u8]| {
+        asn1::sequence(&[
+            asn1::integer(&BigUint::from(0u32)),
+            asn1::sequence(&[asn1::oid(EC_PUBLIC_KEY), asn1::oid(curve)]),
+            asn1::octet_string(inner),
+        ])
+    };
+    assert_eq!(
+        ElGamal::from_pkcs8_der(&wrapped(P_256, &p_256_sec1))
+            .unwrap()
+            .private_key(),
+        &hex(FIXTURES[0].d)
+    );
+    assert_eq!(
+        ElGamal::from_pkcs8_der(&wrapped(P_384, &p_256_sec1)).unwrap_err(),
+        Error::CurveMismatch
+    );
+
+    // A P-256 private key with a P-384 public key beside it.
+    let p_384 = ElGamal::from_public_key_pem(FIXTURES[1].spki).unwrap();
+    let mismatched = asn1::sequence(&[
+        asn1::integer(&BigUint::from(1u32)),
+        asn1::octet_string(&hex(FIXTURES[0].d).to_bytes_be()),
+        asn1::encode(asn1::context_tag(0), &asn1::oid(P_256)),
+        asn1::encode(
+            asn1::context_tag(1),
+            &asn1::bit_string(&p_384.public().to_bytes()),
+        ),
+    ]);
+    assert!(ElGamal::from_sec1_der(&mismatched).is_err());
+
+    // Another P-256 public key.
+    let other = ElGamal::new(Curves::TwoFiveSix);
+    let wrong_public = asn1::sequence(&[
+        asn1::integer(&BigUint::from(1u32)),
+        asn1::octet_string(&hex(FIXTURES[0].d).to_bytes_be()),
+        asn1::encode(asn1::context_tag(0), &asn1::oid(P_256)),
+        asn1::encode(
+            asn1::context_tag(1),
+            &asn1::bit_string(&other.public().to_bytes()),
+        ),
+    ]);
+    assert!(invalid_encoding(ElGamal::from_sec1_der(&wrong_public)));
+
+    // Without the parameters a bare ECPrivateKey doesn't say which curve it's on.
+    let no_parameters = asn1::sequence(&[
+        asn1::integer(&BigUint::from(1u32)),
+        asn1::octet_string(&hex(FIXTURES[0].d).to_bytes_be()),
+    ]);
+    assert!(invalid_encoding(ElGamal::from_sec1_der(&no_parameters)));
+}
+
+#[test]
+fn trailing_data() {
+    for fixture in &FIXTURES {
+        let mut pkcs8 = der(fixture.pkcs8, "PRIVATE KEY");
+        let mut sec1 = der(fixture.sec1, "EC PRIVATE KEY");
+        let mut spki = der(fixture.spki, "PUBLIC KEY");
+        for bytes in [&mut pkcs8, &mut sec1, &mut spki] {
+            bytes.push(0x00);
+        }
+        assert!(invalid_encoding(ElGamal::from_pkcs8_der(&pkcs8)));
+        assert!(invalid_encoding(ElGamal::from_sec1_der(&sec1)));
+        assert!(invalid_encoding(ElGamal::from_public_key_der(&spki)));
+        for bytes in [&mut pkcs8, &mut sec1, &mut spki] {
+            bytes.pop();
+            bytes.pop();
+        }
+        assert!(invalid_encoding(ElGamal::from_pkcs8_der(&pkcs8)));
+        assert!(invalid_encoding(ElGamal::from_sec1_der(&sec1)));
+        assert!(invalid_encoding(ElGamal::from_public_key_der(&spki)));
+    }
+
+    // Trailing data inside the SubjectPublicKeyInfo SEQUENCE.
+    let spki = der(FIXTURES[0].spki, "PUBLIC KEY");
+    let mut members = spki[2..].to_vec();
+    members.extend(asn1::integer(&BigUint::from(0u32)));
+    assert!(invalid_encoding(ElGamal::from_public_key_der(
+        &asn1::encode(asn1::SEQUENCE, &members)
+    )));
+}
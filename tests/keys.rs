@@ -0,0 +1,173 @@
+use encryption::el_gamal::{self, Curves, ElGamal};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Point};
+use encryption::error::Error;
+use encryption::keys::{PrivateKey, PublicKey};
+use encryption::modular_numbers::ModNum;
+use num_bigint::BigUint;
+
+fn big(value: u32) -> BigUint {
+    BigUint::from(value)
+}
+
+// value as a big-endian number with the given number of bytes.
+fn bytes(value: &BigUint, len: usize) -> Vec<u8> {
+    let mut bytes = value.to_bytes_be();
+    while bytes.len() < len {
+        bytes.insert(0, 0);
+    }
+    bytes
+}
+
+#[test]
+fn private_key_bytes() {
+    for (named, width) in [
+        (Curves::TwoFiveSix, 32),
+        (Curves::ThreeEightFour, 48),
+        (Curves::FiveTwoOne, 66),
+    ] {
+        let el_gamal = ElGamal::new(named.clone());
+        let curve = el_gamal.curve();
+        let n = curve.order().unwrap();
+        let private = el_gamal.private().unwrap();
+        let stored = private.to_bytes(curve);
+        assert_eq!(stored.len(), width);
+        assert_eq!(PrivateKey::from_bytes(&stored, curve).as_ref(), Ok(private));
+
+        // Rebuilt from the scalar it decrypts what the original encrypted.
+        let rebuilt = ElGamal::from_private_key(named, el_gamal.private_key().clone()).unwrap();
+        assert_eq!(rebuilt.public(), el_gamal.public());
+        let message = "stored key".to_string();
+        let cipher_text = el_gamal::encrypt(el_gamal.public_key(), curve, &message);
+        assert_eq!(
+            el_gamal::decrypt(rebuilt.private_key(), rebuilt.curve(), &cipher_text),
+            message
+        );
+
+        assert_eq!(
+            PrivateKey::from_bytes(&bytes(&(n - 1u32), width), curve)
+                .map(|key| key.scalar().clone()),
+            Ok(n - 1u32)
+        );
+        let all_ones = vec![0xFF; width];
+        for scalar in [
+            big(0),
+            n.clone(),
+            n + 1u32,
+            BigUint::from_bytes_be(&all_ones),
+        ] {
+            assert_eq!(
+                PrivateKey::from_bytes(&bytes(&scalar, width), curve),
+                Err(Error::InvalidPrivateKey),
+                "{}",
+                scalar
+            );
+            assert_eq!(
+                PrivateKey::try_new(scalar, curve),
+                Err(Error::InvalidPrivateKey)
+            );
+        }
+
+        let mut longer = stored.clone();
+        longer.insert(0, 0);
+        for wrong_width in [vec![], stored[1..].to_vec(), longer] {
+            assert!(matches!(
+                PrivateKey::from_bytes(&wrong_width, curve),
+                Err(Error::InvalidEncoding(_))
+            ));
+        }
+    }
+}
+
+#[test]
+fn public_key_bytes() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let public = el_gamal.public();
+    assert_eq!(public.to_bytes().len(), 65);
+    assert_eq!(public.to_compressed_bytes().len(), 33);
+    for stored in [public.to_bytes(), public.to_compressed_bytes()] {
+        assert_eq!(PublicKey::from_bytes(&stored, curve).as_ref(), Ok(public));
+    }
+
+    // Rebuilt from the point it can only encrypt.
+    let encrypt_only =
+        ElGamal::from_public_key(Curves::TwoFiveSix, public.point().clone()).unwrap();
+    assert_eq!(
+        encrypt_only.try_private_key(),
+        Err(Error::MissingPrivateKey)
+    );
+    assert!(encrypt_only.private().is_none());
+    let message = "encrypt only".to_string();
+    let cipher_text = el_gamal::encrypt(encrypt_only.public_key(), curve, &message);
+    assert_eq!(
+        el_gamal::decrypt(el_gamal.private_key(), curve, &cipher_text),
+        message
+    );
+
+    // Changing the last byte of y moves the point off the curve.
+    let mut off_curve = public.to_bytes();
+    off_curve[64] ^= 1;
+    assert_eq!(
+        PublicKey::from_bytes(&off_curve, curve),
+        Err(Error::NotOnCurve)
+    );
+    assert_eq!(
+        ElGamal::from_public_key(
+            Curves::TwoFiveSix,
+            EllipticType::from_sec1_bytes(&[0x00], curve).unwrap()
+        )
+        .unwrap_err(),
+        Error::InvalidPublicKey
+    );
+    assert_eq!(
+        PublicKey::from_bytes(&[0x00], curve),
+        Err(Error::InvalidPublicKey)
+    );
+    assert!(matches!(
+        PublicKey::from_bytes(&public.to_bytes()[..64], curve),
+        Err(Error::InvalidEncoding(_))
+    ));
+
+    // A key from another curve.
+    let other = ElGamal::new(Curves::ThreeEightFour);
+    assert!(PublicKey::from_bytes(&other.public().to_bytes(), curve).is_err());
+    assert_eq!(
+        PublicKey::try_new(other.public_key().clone(), curve),
+        Err(Error::FieldMismatch)
+    );
+}
+
+// y^2 = x^3 + 2x + 3 mod 211 has 204 = 4 * 51 points, (145, 50) is on it but has order 4 so isn't generated
+//  by the init_point.
+#[test]
+fn public_key_outside_subgroup() {
+    let field = big(211);
+    let num = |value| ModNum::new(&big(value), &field);
+    let point = |x, y| EllipticType::Point(Point::new(num(x), num(y), &num(2), &num(3)));
+    let curve = EllipticCurve::new_with_order(num(2), num(3), point(3, 6), big(51), big(4));
+    let outside = point(145, 50);
+    assert_eq!(
+        PublicKey::from_bytes(&outside.to_sec1_bytes(true), &curve),
+        Err(Error::InvalidPublicKey)
+    );
+    let inside = curve.init_point_pow(&big(7));
+    assert!(PublicKey::from_bytes(&inside.to_sec1_bytes(false), &curve).is_ok());
+}
+
+// Debug output, of the key or an ElGamal holding it, never includes the scalar.
+#[test]
+fn private_key_debug() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let private_key = el_gamal.private().unwrap();
+    let scalar = private_key.scalar();
+    for printed in [
+        format!("{:?}", private_key),
+        format!("{:?}", el_gamal),
+        format!("{:#?}", el_gamal),
+    ] {
+        assert!(!printed.contains(&scalar.to_string()), "{}", printed);
+        assert!(!printed.contains(&scalar.to_str_radix(16)), "{}", printed);
+    }
+    assert_eq!(format!("{:?}", private_key), "PrivateKey { .. }");
+}
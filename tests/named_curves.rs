@@ -0,0 +1,167 @@
+use encryption::ecdh::shared_secret;
+use encryption::ecdsa;
+use encryption::el_gamal::{self, Curves, ElGamal};
+use encryption::elliptic_point::EllipticType;
+use encryption::sha2::Hash;
+use num_bigint::BigUint;
+
+const NAMES: [&str; 9] = [
+    "secp256r1",
+    "secp384r1",
+    "secp521r1",
+    "secp256k1",
+    "secp224r1",
+    "secp192r1",
+    "brainpoolP256r1",
+    "brainpoolP384r1",
+    "brainpoolP512r1",
+];
+
+fn hex(value: &str) -> BigUint {
+    BigUint::parse_bytes(value.as_bytes(), 16).unwrap()
+}
+
+// Each curve's init_point is on the curve and has the curve's order, which is what a typo in any of the
+//  parameters would break.
+#[test]
+fn registry() {
+    let names: Vec<&str> = Curves::all().map(|curve| curve.name().unwrap()).collect();
+    assert_eq!(names, NAMES);
+    for named in Curves::all() {
+        let name = named.name().unwrap();
+        let oid = named.oid().unwrap();
+        let curve = named.into_curve();
+        curve.validate_public_key(curve.init_point()).unwrap();
+        assert!(matches!(
+            curve.init_point().pow(curve.order().unwrap(), &curve),
+            EllipticType::Infinity(_)
+        ));
+        assert_eq!(Curves::identify(&curve).name(), Some(name));
+        assert_eq!(Curves::by_name(name).unwrap().name(), Some(name));
+        assert_eq!(Curves::by_oid(oid).unwrap().name(), Some(name));
+    }
+}
+
+#[test]
+fn names() {
+    for (name, expected) in [
+        ("P-256", "secp256r1"),
+        ("prime256v1", "secp256r1"),
+        ("p-384", "secp384r1"),
+        ("P-521", "secp521r1"),
+        ("SECP256K1", "secp256k1"),
+        ("P-224", "secp224r1"),
+        ("P-192", "secp192r1"),
+        ("BrainpoolP512R1", "brainpoolP512r1"),
+    ] {
+        assert_eq!(Curves::by_name(name).unwrap().name(), Some(expected));
+    }
+    assert!(Curves::by_name("secp256r2").is_none());
+    assert!(Curves::by_oid(&[1, 3, 132, 0, 99]).is_none());
+    assert!(matches!(
+        Curves::by_name("P-256").unwrap(),
+        Curves::TwoFiveSix
+    ));
+
+    let custom = Curves::Custom(Curves::Secp256k1.into_curve());
+    assert_eq!(custom.name(), None);
+    assert_eq!(custom.oid(), None);
+}
+
+// Runs every key in tests/data/known_keys.txt, checking d gives the published public key. Between these and
+//  the keys in rfc6979.txt every named curve is covered.
+#[test]
+fn known_keys() {
+    let vectors = include_str!("data/known_keys.txt");
+    let mut el_gamal = None;
+    let mut curve = None;
+    let mut qx = BigUint::from(0u32);
+    let mut count = 0;
+    for line in vectors.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            curve = Curves::by_name(name.trim_end_matches(']'));
+            continue;
+        }
+        let (name, value) = line.split_once(" = ").unwrap();
+        match name {
+            "d" => {
+                let curve = curve.clone().unwrap();
+                el_gamal = Some(ElGamal::from_private_key(curve, hex(value)).unwrap());
+            }
+            "Qx" => qx = hex(value),
+            "Qy" => match el_gamal.as_ref().unwrap().public_key() {
+                EllipticType::Point(point) => {
+                    assert_eq!(point.x().value(), &qx);
+                    assert_eq!(point.y().value(), &hex(value));
+                    count += 1;
+                }
+                EllipticType::Infinity(_) => panic!("public key is Infinity"),
+            },
+            other => panic!("unknown field {}", other),
+        }
+    }
+    assert_eq!(count, 9);
+}
+
+// 2 * G on secp256k1, a well known point.
+#[test]
+fn secp256k1_double() {
+    let curve = Curves::Secp256k1.into_curve();
+    match curve.init_point_pow(&BigUint::from(2u32)) {
+        EllipticType::Point(point) => {
+            assert_eq!(
+                point.x().value(),
+                &hex("C6047F9441ED7D6D3045406E95C07CD85C778E4B8CEF3CA7ABAC09B95C709EE5")
+            );
+            assert_eq!(
+                point.y().value(),
+                &hex("1AE168FEA63DC339A3C58419466CEAEEF7F632653266D0E1236431A950CFE52A")
+            );
+        }
+        EllipticType::Infinity(_) => panic!("2G is Infinity"),
+    }
+}
+
+// Everything the crate does works on every curve, and key files record which curve they're for.
+#[test]
+fn round_trips() {
+    let message = "every named curve".to_string();
+    for named in Curves::all() {
+        let name = named.name().unwrap();
+        let alice = ElGamal::new(named.clone());
+        let bob = ElGamal::new(named);
+        let curve = alice.curve();
+
+        let cipher_text = el_gamal::encrypt(alice.public_key(), curve, &message);
+        assert_eq!(
+            el_gamal::decrypt(alice.private_key(), curve, &cipher_text),
+            message,
+            "{}",
+            name
+        );
+        let bytes = cipher_text.to_bytes(curve).unwrap();
+        assert_eq!(
+            el_gamal::Ciphertext::from_bytes(&bytes, curve).unwrap(),
+            cipher_text
+        );
+
+        let hash = Hash::Sha256.digest(message.as_bytes());
+        let signature = ecdsa::sign(alice.private_key(), curve, &hash).unwrap();
+        assert!(ecdsa::verify(alice.public_key(), curve, &hash, &signature));
+
+        assert_eq!(
+            shared_secret(alice.private_key(), bob.public_key(), curve),
+            shared_secret(bob.private_key(), alice.public_key(), curve)
+        );
+
+        let pem = alice.to_pkcs8_pem().unwrap();
+        let read = ElGamal::from_pkcs8_pem(&pem).unwrap();
+        assert_eq!(read.curve(), curve);
+        assert_eq!(read.private_key(), alice.private_key());
+        let public = ElGamal::from_public_key_pem(&alice.to_public_key_pem().unwrap()).unwrap();
+        assert_eq!(public.public_key(), alice.public_key());
+    }
+}
@@ -0,0 +1,104 @@
+use encryption::el_gamal::Curves;
+use encryption::modular_numbers::ModNum;
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+
+// ModNums on the fields of the built in curves use the fast reductions from FIPS 186-4 D.2 instead of %.
+//  These check they agree with plain BigUint % on lots of random numbers, and on numbers made mostly of
+//   0 and 0xffffffff words, which push the sums and differences of words to their limits.
+
+const ROUNDS: usize = 2000;
+
+fn fields() -> Vec<BigUint> {
+    [
+        Curves::TwoFiveSix,
+        Curves::ThreeEightFour,
+        Curves::FiveTwoOne,
+    ]
+    .into_iter()
+    .map(|curve| curve.into_curve().field().clone())
+    .collect()
+}
+
+// A number below limit whose 32 bit words are mostly 0, 1, 0xfffffffe, or 0xffffffff.
+fn extreme_below(limit: &BigUint, rng: &mut impl Rng) -> BigUint {
+    let words = limit.bits().div_ceil(32) as usize;
+    let digits = (0..words)
+        .map(|_| match rng.gen_range(0..5) {
+            0 => 0,
+            1 => 1,
+            2 => 0xfffffffe,
+            3 => 0xffffffff,
+            _ => rng.gen(),
+        })
+        .collect();
+    BigUint::new(digits) % limit
+}
+
+#[test]
+fn mul_matches_generic() {
+    let mut rng = rand::thread_rng();
+    for field in fields() {
+        for i in 0..ROUNDS {
+            let (a, b) = if i % 2 == 0 {
+                (rng.gen_biguint_below(&field), rng.gen_biguint_below(&field))
+            } else {
+                (
+                    extreme_below(&field, &mut rng),
+                    extreme_below(&field, &mut rng),
+                )
+            };
+            let product = ModNum::new(&a, &field).mul(&ModNum::new(&b, &field));
+            assert_eq!(product.value(), &((&a * &b) % &field), "{} * {}", a, b);
+        }
+    }
+}
+
+// new reduces whatever it is given, including numbers bigger than the square of the field which the fast
+//  reductions hand back to %.
+#[test]
+fn new_matches_generic() {
+    let mut rng = rand::thread_rng();
+    for field in fields() {
+        let square = &field * &field;
+        let mut values = vec![
+            BigUint::from(0u32),
+            BigUint::from(1u32),
+            &field - 1u32,
+            field.clone(),
+            &field + 1u32,
+            (&field - 1u32) * (&field - 1u32),
+            &square - 1u32,
+            square.clone(),
+            &square * 7u32 + 3u32,
+            (BigUint::from(1u32) << (2 * field.bits())) - 1u32,
+        ];
+        for _ in 0..ROUNDS {
+            values.push(rng.gen_biguint_below(&square));
+            values.push(extreme_below(&square, &mut rng));
+        }
+        for value in values {
+            assert_eq!(
+                ModNum::new(&value, &field).value(),
+                &(&value % &field),
+                "{}",
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn pow_matches_generic() {
+    let mut rng = rand::thread_rng();
+    for field in fields() {
+        for _ in 0..20 {
+            let base = rng.gen_biguint_below(&field);
+            let exp = rng.gen_biguint(field.bits());
+            assert_eq!(
+                ModNum::new(&base, &field).pow(&exp).value(),
+                &base.modpow(&exp, &field)
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use encryption::curve_point::CurvePoint;
+use encryption::el_gamal::Curves;
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Inf, Point};
+use encryption::error::Error;
+use encryption::modular_numbers::ModNum;
+use num_bigint::{BigUint, RandBigInt};
+
+fn num(value: u32, field: &BigUint) -> ModNum {
+    ModNum::new(&BigUint::from(value), field)
+}
+
+// The operators give the same answers as the methods, whichever operands are owned or borrowed.
+#[test]
+fn mod_num_operators() {
+    let mut rng = rand::thread_rng();
+    let field = Curves::TwoFiveSix.into_curve().field().clone();
+    for _ in 0..100 {
+        let a = ModNum::new(&rng.gen_biguint_below(&field), &field);
+        let b = ModNum::new(&(rng.gen_biguint_below(&(&field - 1u32)) + 1u32), &field);
+
+        assert_eq!(&a + &b, a.add(&b));
+        assert_eq!(a.clone() + &b, a.add(&b));
+        assert_eq!(&a + b.clone(), a.add(&b));
+        assert_eq!(a.clone() + b.clone(), a.add(&b));
+        assert_eq!(&a * &b, a.mul(&b));
+        assert_eq!(a.clone() * b.clone(), a.mul(&b));
+        assert_eq!(-&a, a.add_inv());
+        assert_eq!(-a.clone(), a.add_inv());
+
+        assert_eq!(&(&a - &b) + &b, a);
+        assert_eq!(&a - &a, num(0, &field));
+        assert_eq!(&(&a / &b) * &b, a);
+        assert_eq!(&b / &b, num(1, &field));
+
+        let mut c = a.clone();
+        c += &b;
+        c -= b.clone();
+        c *= &b;
+        c /= b.clone();
+        assert_eq!(c, a);
+    }
+}
+
+#[test]
+fn mod_num_sub_wraps() {
+    let field = BigUint::from(211u32);
+    assert_eq!(num(3, &field) - num(5, &field), num(209, &field));
+    assert_eq!(num(5, &field) - num(3, &field), num(2, &field));
+    assert_eq!(-num(0, &field), num(0, &field));
+    assert_eq!(num(1, &field) / num(2, &field), num(106, &field));
+}
+
+#[test]
+#[should_panic]
+fn mod_num_divide_by_zero() {
+    let field = BigUint::from(211u32);
+    let _ = num(1, &field) / num(0, &field);
+}
+
+#[test]
+#[should_panic]
+fn mod_num_different_fields() {
+    let _ = num(1, &BigUint::from(211u32)) - num(1, &BigUint::from(223u32));
+}
+
+#[test]
+fn curve_point_operators() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let order = curve.order().unwrap().clone();
+    let g = CurvePoint::init_point(&curve);
+    let infinity = CurvePoint::infinity(&curve);
+
+    assert_eq!(&g + &g, &g * &BigUint::from(2u32));
+    assert_eq!(&g + &g + &g, &BigUint::from(3u32) * &g);
+    assert_eq!(&g - &g, infinity);
+    assert_eq!(-&g + &g, infinity);
+    assert_eq!(&g + &infinity, g);
+    assert_eq!(&g * &order, infinity);
+    assert_eq!(
+        (&g * &BigUint::from(12345u32)).point(),
+        &curve.init_point_pow(&BigUint::from(12345u32))
+    );
+
+    let mut rng = rand::thread_rng();
+    let (j, k) = (rng.gen_biguint_below(&order), rng.gen_biguint_below(&order));
+    assert_eq!(&g * &j + &g * &k, &g * &((&j + &k) % &order));
+
+    let mut p = g.clone();
+    p += &g;
+    p *= &BigUint::from(5u32);
+    p -= g.clone();
+    assert_eq!(p, &g * &BigUint::from(9u32));
+    assert_eq!(p.into_point(), curve.init_point_pow(&BigUint::from(9u32)));
+}
+
+// A point made for y^2 = x^3 + 2x + 3 mod 211 isn't on y^2 = x^3 + 2x + 5 mod 211 even though the field is
+//  the same.
+#[test]
+fn curve_point_checks() {
+    let field = BigUint::from(211u32);
+    let a = num(2, &field);
+    let point = Point::new(num(1, &field), num(46, &field), &a, &num(3, &field));
+    let curve = EllipticCurve::new(
+        a.clone(),
+        num(3, &field),
+        EllipticType::Point(point.clone()),
+    );
+    let other_point = Point::new(num(0, &field), num(65, &field), &a, &num(5, &field));
+    let other = EllipticCurve::new(a, num(5, &field), EllipticType::Point(other_point));
+
+    assert!(CurvePoint::try_new(EllipticType::Point(point.clone()), &curve).is_ok());
+    assert_eq!(
+        CurvePoint::try_new(EllipticType::Point(point.clone()), &other).unwrap_err(),
+        Error::NotOnCurve
+    );
+    assert_eq!(
+        CurvePoint::try_new(
+            EllipticType::Infinity(Inf::new(&BigUint::from(223u32))),
+            &curve
+        )
+        .unwrap_err(),
+        Error::FieldMismatch
+    );
+}
+
+#[test]
+#[should_panic]
+fn curve_point_different_curves() {
+    let p_256 = Curves::TwoFiveSix.into_curve();
+    let p_384 = Curves::ThreeEightFour.into_curve();
+    let _ = CurvePoint::init_point(&p_256) + CurvePoint::init_point(&p_384);
+}
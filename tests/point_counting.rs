@@ -0,0 +1,217 @@
+use encryption::el_gamal::Curves;
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Point};
+use encryption::error::Error;
+use encryption::modular_numbers::ModNum;
+use encryption::point_counting::{PointCounting, SCHOOF_BITS};
+use num_bigint::BigUint;
+
+const METHODS: [PointCounting; 3] = [
+    PointCounting::Naive,
+    PointCounting::Mestre,
+    PointCounting::Schoof,
+];
+
+fn big(value: u64) -> BigUint {
+    BigUint::from(value)
+}
+
+fn hex(value: &str) -> BigUint {
+    BigUint::parse_bytes(value.as_bytes(), 16).unwrap()
+}
+
+// y^2 = x^3 + a*x + b with the first point found from x = 0 up as its init_point.
+fn curve(field: &BigUint, a: u64, b: u64) -> EllipticCurve {
+    let (a, b) = (ModNum::new(&big(a), field), ModNum::new(&big(b), field));
+    let mut x = ModNum::new(&big(0), field);
+    loop {
+        if let Some(y) = (&x * &x * &x + &a * &x + &b).sqrt() {
+            let point = EllipticType::Point(Point::new(x, y, &a, &b));
+            return EllipticCurve::new(a, b, point);
+        }
+        x += ModNum::new(&big(1), field);
+    }
+}
+
+fn point(curve: &EllipticCurve, x: u64, y: u64) -> EllipticType {
+    let field = curve.field();
+    EllipticType::Point(Point::new(
+        ModNum::new(&big(x), field),
+        ModNum::new(&big(y), field),
+        curve.a(),
+        curve.b(),
+    ))
+}
+
+// Curves from the curve builder tests, with their counts worked out by hand. Their 17 bit fields are over
+//  NAIVE_BITS so only Mestre's method and Schoof's count them.
+#[test]
+fn known_counts() {
+    for (field, a, b, count) in [
+        (65_539, 2, 1, 65_439),
+        (65_543, 10, 47, 65_543),
+        (66_523, 1, 0, 66_524),
+    ] {
+        let curve = curve(&big(field), a, b);
+        for method in [PointCounting::Mestre, PointCounting::Schoof] {
+            assert_eq!(
+                curve.count_points_with(method),
+                Ok(big(count)),
+                "{:?}",
+                method
+            );
+        }
+        assert_eq!(curve.count_points(), Ok(big(count)));
+    }
+
+    // Every method counts the points on a field of NAIVE_BITS the same way.
+    let curve = curve(&big(65_521), 2, 3);
+    let count = curve.count_points_with(PointCounting::Naive).unwrap();
+    for method in METHODS {
+        assert_eq!(curve.count_points_with(method), Ok(count.clone()));
+    }
+}
+
+// Mestre's method and Schoof's agree, and are right, on random curves over an 18 bit field. The counts are
+//  from adding up the square roots of x^3 + a*x + b for every x.
+#[test]
+fn methods_agree() {
+    let field = big(134_489);
+    for (a, b, count) in [
+        (1, 1, 134_012),
+        (0, 7, 134_490),
+        (134_486, 5, 135_046),
+        (12_345, 67_890, 134_865),
+        (2, 0, 133_780),
+    ] {
+        let curve = curve(&field, a, b);
+        let count = big(count);
+        assert_eq!(
+            curve.count_points_with(PointCounting::Mestre),
+            Ok(count.clone())
+        );
+        assert_eq!(
+            curve.count_points_with(PointCounting::Schoof),
+            Ok(count.clone())
+        );
+        assert!(matches!(
+            curve.init_point().pow(&count, &curve),
+            EllipticType::Infinity(_)
+        ));
+    }
+}
+
+// Fields too big to count naively, where Schoof's algorithm works through the primes up to 11 and 17 before
+//  Mestre's method takes over. The 56 bit field is over MESTRE_BITS, its count was found with Mestre's method
+//   before it was limited.
+#[test]
+fn schoof_and_mestre_agree() {
+    let curve_40 = curve(&big(898_001_579_693), 3, 11);
+    let count = curve_40.count_points_with(PointCounting::Schoof).unwrap();
+    assert_eq!(
+        curve_40.count_points_with(PointCounting::Mestre),
+        Ok(count.clone())
+    );
+    assert!(matches!(
+        curve_40.init_point().pow(&count, &curve_40),
+        EllipticType::Infinity(_)
+    ));
+
+    let curve_56 = curve(&big(62_102_991_880_599_959), 62_102_991_880_599_956, 1_000);
+    assert_eq!(
+        curve_56.count_points_with(PointCounting::Schoof),
+        Ok(big(62_102_992_271_974_200))
+    );
+    assert_eq!(curve_56.count_points(), Ok(big(62_102_992_271_974_200)));
+}
+
+// Each method refuses fields over its limit up front rather than running for hours or filling memory.
+#[test]
+fn field_too_large() {
+    let curve_17 = curve(&big(65_539), 2, 1);
+    assert_eq!(
+        curve_17.count_points_with(PointCounting::Naive),
+        Err(Error::FieldTooLargeToCount)
+    );
+    let curve_56 = curve(&big(62_102_991_880_599_959), 1, 1);
+    assert_eq!(
+        curve_56.count_points_with(PointCounting::Mestre),
+        Err(Error::FieldTooLargeToCount)
+    );
+    for named in [Curves::Secp192r1, Curves::TwoFiveSix, Curves::FiveTwoOne] {
+        let curve = named.into_curve();
+        assert!(curve.field().bits() > SCHOOF_BITS);
+        assert_eq!(curve.count_points(), Err(Error::FieldTooLargeToCount));
+        for method in METHODS {
+            assert_eq!(
+                curve.count_points_with(method),
+                Err(Error::FieldTooLargeToCount)
+            );
+        }
+    }
+}
+
+// secp112r1 and secp128r1 from SEC 2 version 1, which both have a cofactor of 1. These take tens of seconds
+//  in a release build.
+#[test]
+#[ignore]
+fn published_counts() {
+    let secp112r1 = [
+        "DB7C2ABF62E35E668076BEAD208B",
+        "DB7C2ABF62E35E668076BEAD2088",
+        "659EF8BA043916EEDE8911702B22",
+        "09487239995A5EE76B55F9C2F098",
+        "A89CE5AF8724C0A23E0E0FF77500",
+        "DB7C2ABF62E35E7628DFAC6561C5",
+    ];
+    let secp128r1 = [
+        "FFFFFFFDFFFFFFFFFFFFFFFFFFFFFFFF",
+        "FFFFFFFDFFFFFFFFFFFFFFFFFFFFFFFC",
+        "E87579C11079F43DD824993C2CEE5ED3",
+        "161FF7528B899B2D0C28607CA52C5B86",
+        "CF5AC8395BAFEB13C02DA292DDED7A83",
+        "FFFFFFFE0000000075A30D1B9038A115",
+    ];
+    for [field, a, b, x, y, count] in [secp112r1, secp128r1] {
+        let field = hex(field);
+        let (a, b) = (ModNum::new(&hex(a), &field), ModNum::new(&hex(b), &field));
+        let (x, y) = (ModNum::new(&hex(x), &field), ModNum::new(&hex(y), &field));
+        let init_point = EllipticType::Point(Point::new(x, y, &a, &b));
+        let curve = EllipticCurve::new(a, b, init_point);
+        assert_eq!(curve.count_points(), Ok(hex(count)));
+    }
+}
+
+// y^2 = x^3 + 2x + 1 mod 65_539 has 65_439 = 3^2 * 11 * 661 points.
+#[test]
+fn point_order() {
+    let curve = curve(&big(65_539), 2, 1);
+    let group_order = [(big(3), 2), (big(11), 1), (big(661), 1)];
+    for (x, y, order) in [(9_998, 621, 661), (37_776, 43_641, 11)] {
+        assert_eq!(
+            curve.point_order(&point(&curve, x, y), &group_order),
+            big(order)
+        );
+    }
+    let order = curve.point_order(curve.init_point(), &group_order);
+    assert_eq!(big(65_439) % &order, big(0));
+    assert!(matches!(
+        curve.init_point().pow(&order, &curve),
+        EllipticType::Infinity(_)
+    ));
+
+    assert_eq!(
+        curve.try_point_order(&point(&curve, 9_998, 621), &[(big(3), 2), (big(11), 1)]),
+        Err(Error::WrongGroupOrder)
+    );
+    let off_curve = EllipticType::Point(Point::new(
+        ModNum::new(&big(1), &big(65_539)),
+        ModNum::new(&big(1), &big(65_539)),
+        &ModNum::new(&big(0), &big(65_539)),
+        &ModNum::new(&big(0), &big(65_539)),
+    ));
+    assert_eq!(
+        curve.try_point_order(&off_curve, &group_order),
+        Err(Error::NotOnCurve)
+    );
+}
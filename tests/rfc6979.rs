@@ -0,0 +1,173 @@
+use encryption::ecdsa::{sign_deterministic, verify};
+use encryption::el_gamal::{decrypt_bytes, encrypt_bytes_deterministic, Curves, ElGamal};
+use encryption::rfc6979::NonceGenerator;
+use encryption::sha2::Hash;
+use num_bigint::BigUint;
+
+// Runs every vector in tests/data/rfc6979.txt. Each one checks that x gives the public key (Ux, Uy), that
+//  signing the hash of Msg gives (R, S), and that the signature verifies.
+#[test]
+fn rfc6979_appendix_a2() {
+    let vectors = include_str!("data/rfc6979.txt");
+    let mut curve = None;
+    let mut el_gamal = None;
+    let mut public_key = Vec::new();
+    let mut hash = Hash::Sha256;
+    let mut msg = "";
+    let mut r = Vec::new();
+    let mut count = 0;
+    for line in vectors.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            curve = Some(match &line[1..6] {
+                "P-256" => Curves::TwoFiveSix,
+                "P-384" => Curves::ThreeEightFour,
+                "P-521" => Curves::FiveTwoOne,
+                other => panic!("unknown curve {}", other),
+            });
+            public_key = vec![0x04];
+            continue;
+        }
+        let (name, value) = line.split_once(" = ").unwrap();
+        match name {
+            "x" => {
+                // The P-521 key is written with an odd number of digits so it can't go through hex.
+                let x = BigUint::parse_bytes(value.as_bytes(), 16).unwrap();
+                el_gamal = Some(ElGamal::from_private_key(curve.clone().unwrap(), x).unwrap());
+            }
+            "Ux" => public_key.extend(hex(value)),
+            "Uy" => {
+                public_key.extend(hex(value));
+                assert_eq!(el_gamal.as_ref().unwrap().public().to_bytes(), public_key);
+            }
+            "Hash" => {
+                hash = match value {
+                    "SHA-224" => Hash::Sha224,
+                    "SHA-256" => Hash::Sha256,
+                    "SHA-384" => Hash::Sha384,
+                    "SHA-512" => Hash::Sha512,
+                    other => panic!("unknown hash {}", other),
+                }
+            }
+            "Msg" => msg = value,
+            "R" => r = hex(value),
+            "S" => {
+                let el_gamal = el_gamal.as_ref().unwrap();
+                let msg_hash = hash.digest(msg.as_bytes());
+                let signature =
+                    sign_deterministic(el_gamal.private_key(), el_gamal.curve(), &msg_hash, hash)
+                        .unwrap();
+                assert_eq!(signature.r(), &BigUint::from_bytes_be(&r));
+                assert_eq!(signature.s(), &BigUint::from_bytes_be(&hex(value)));
+                assert!(verify(
+                    el_gamal.public_key(),
+                    el_gamal.curve(),
+                    &msg_hash,
+                    &signature
+                ));
+                count += 1;
+            }
+            other => panic!("unknown field {}", other),
+        }
+    }
+    assert_eq!(count, 24);
+}
+
+// The nonce itself for the P-256, SHA-256, "sample" vector in A.2.5.
+#[test]
+fn rfc6979_nonce() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let x = BigUint::parse_bytes(
+        b"C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+        16,
+    )
+    .unwrap();
+    let msg_hash = Hash::Sha256.digest(b"sample");
+    let mut nonces = NonceGenerator::new(Hash::Sha256, &x, curve.order().unwrap(), &msg_hash);
+    let k = BigUint::parse_bytes(
+        b"A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60",
+        16,
+    )
+    .unwrap();
+    assert_eq!(nonces.next(), k);
+    assert_ne!(nonces.next(), k);
+}
+
+// FIPS 180-4 examples and RFC 4231 test cases 2 and 6, the second of which has a key longer than a block.
+#[test]
+fn sha2_and_hmac() {
+    let two_blocks = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+    let long_key = [0xaa; 131];
+    let long_key_msg = b"Test Using Larger Than Block-Size Key - Hash Key First";
+    let cases = [
+        (
+            Hash::Sha224,
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+            "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+            "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e",
+        ),
+        (
+            Hash::Sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+        ),
+        (
+            Hash::Sha384,
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+            "3391fdddfc8dc7393707a65b1b4709397cf8b1d162af05abfe8f450de5f36bc6b0455a8520bc4e6f5fe95b1fe3c8452b",
+            "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+            "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+        ),
+        (
+            Hash::Sha512,
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+        ),
+    ];
+    for (hash, abc, two_block, hmac, long_key_hmac) in cases {
+        assert_eq!(hash.digest(b"abc"), hex(abc));
+        assert_eq!(hash.digest(two_blocks), hex(two_block));
+        assert_eq!(
+            hash.hmac(b"Jefe", b"what do ya want for nothing?"),
+            hex(hmac)
+        );
+        assert_eq!(hash.hmac(&long_key, long_key_msg), hex(long_key_hmac));
+    }
+}
+
+#[test]
+fn deterministic_encryption() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let sender = ElGamal::new(Curves::TwoFiveSix);
+    let plain_text = b"the same every time";
+    let encrypt = |plain_text: &[u8]| {
+        encrypt_bytes_deterministic(
+            el_gamal.public_key(),
+            el_gamal.curve(),
+            plain_text,
+            sender.private_key(),
+            Hash::Sha256,
+        )
+    };
+    let cipher_text = encrypt(plain_text);
+    assert_eq!(cipher_text, encrypt(plain_text));
+    assert_ne!(cipher_text, encrypt(b"different every time"));
+    assert_eq!(
+        decrypt_bytes(el_gamal.private_key(), el_gamal.curve(), &cipher_text),
+        plain_text
+    );
+}
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
@@ -0,0 +1,221 @@
+use encryption::el_gamal::Curves;
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Inf, Point, ScalarMul};
+use encryption::modular_numbers::ModNum;
+use num_bigint::{BigUint, RandBigInt};
+
+const METHODS: [ScalarMul; 8] = [
+    ScalarMul::DoubleAndAdd,
+    ScalarMul::FixedWindow(1),
+    ScalarMul::FixedWindow(4),
+    ScalarMul::FixedWindow(5),
+    ScalarMul::Wnaf(2),
+    ScalarMul::Wnaf(4),
+    ScalarMul::Wnaf(5),
+    ScalarMul::Wnaf(8),
+];
+
+// Every method, and the init_point table, gives the same answer as adding the point to itself with group_op.
+#[test]
+fn methods_agree() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    // The same curve without its order, so nothing is reduced and init_point_pow can't use the table.
+    let no_order = EllipticCurve::new(
+        curve.a().clone(),
+        curve.b().clone(),
+        curve.init_point().clone(),
+    );
+    let n = curve.order().unwrap();
+    let mut rng = rand::thread_rng();
+    let mut exponents: Vec<BigUint> = (0u32..40).map(BigUint::from).collect();
+    exponents.extend([n - 1u32, n.clone(), n + 1u32, BigUint::from(1u32) << 255]);
+    exponents.extend((0..5).map(|_| rng.gen_biguint_below(n)));
+
+    for curve in [&curve, &no_order] {
+        let point = curve.init_point();
+        for exp in &exponents {
+            let expected = if exp < &BigUint::from(40u32) {
+                repeated_group_op(point, exp, curve)
+            } else {
+                point.pow_with(exp, curve, ScalarMul::DoubleAndAdd)
+            };
+            for method in METHODS {
+                assert_eq!(point.pow_with(exp, curve, method), expected, "{:?}", method);
+            }
+            assert_eq!(curve.init_point_pow(exp), expected);
+            assert_eq!(point.pow_secret(exp, curve), expected);
+            assert_eq!(curve.init_point_pow_secret(exp), expected);
+        }
+    }
+}
+
+#[test]
+fn order_edge_cases() {
+    let curve = Curves::ThreeEightFour.into_curve();
+    let n = curve.order().unwrap();
+    let point = curve.init_point();
+    assert_eq!(
+        curve.init_point_pow(n),
+        EllipticType::Infinity(Inf::new(curve.field()))
+    );
+    assert_eq!(curve.init_point_pow(&(n - 1u32)), point.group_inv());
+    assert_eq!(
+        curve.init_point_pow_secret(n),
+        EllipticType::Infinity(Inf::new(curve.field()))
+    );
+    assert_eq!(curve.init_point_pow_secret(&(n - 1u32)), point.group_inv());
+    assert_eq!(
+        point.pow(&BigUint::from(0u32), &curve),
+        EllipticType::Infinity(Inf::new(curve.field()))
+    );
+}
+
+// y^2 = x^3 + 2x + 3 mod 211 has 204 points, including (210, 0) which has order 2. The complete formulas can't
+//  add two points that differ by a point of order 2 so this checks pow_secret still gets every point right.
+#[test]
+fn pow_secret_small_curve() {
+    let field = BigUint::from(211u32);
+    let a = ModNum::new(&BigUint::from(2u32), &field);
+    let b = ModNum::new(&BigUint::from(3u32), &field);
+    let mut points = vec![EllipticType::Infinity(Inf::new(&field))];
+    for x in 0u32..211 {
+        for y in 0u32..211 {
+            let x = ModNum::new(&BigUint::from(x), &field);
+            let y = ModNum::new(&BigUint::from(y), &field);
+            if let Ok(point) = Point::try_new(x, y, &a, &b) {
+                points.push(EllipticType::Point(point));
+            }
+        }
+    }
+    assert_eq!(points.len(), 204);
+
+    let curve = EllipticCurve::new(a, b, points[1].clone());
+    for point in &points {
+        for exp in (0u32..12).chain([203, 204, 205, 1000]) {
+            let exp = BigUint::from(exp);
+            assert_eq!(point.pow_secret(&exp, &curve), point.pow(&exp, &curve));
+        }
+    }
+}
+
+// Jacobian add and double against affine group_op on a point of each order on y^2 = x^3 + 2x + 3 mod 211. The group has
+//  order 204 = 4 * 3 * 17 so there are points of every order dividing 204, and the tables the windowed methods
+//   build run into each special case, adding a point to itself, adding P to -P, doubling a point with y = 0, and
+//  adding Infinity on either side.
+#[test]
+fn jacobian_special_cases() {
+    let field = BigUint::from(211u32);
+    let a = ModNum::new(&BigUint::from(2u32), &field);
+    let b = ModNum::new(&BigUint::from(3u32), &field);
+    let mut points = vec![EllipticType::Infinity(Inf::new(&field))];
+    for x in 0u32..211 {
+        for y in 0u32..211 {
+            let x = ModNum::new(&BigUint::from(x), &field);
+            let y = ModNum::new(&BigUint::from(y), &field);
+            if let Ok(point) = Point::try_new(x, y, &a, &b) {
+                points.push(EllipticType::Point(point));
+            }
+        }
+    }
+    let curve = EllipticCurve::new(a, b, points[1].clone());
+    let infinity = points[0].clone();
+
+    // One point of each order.
+    let mut orders = Vec::new();
+    let mut representatives = Vec::new();
+    for point in &points {
+        let mut order = 1;
+        let mut multiple = point.clone();
+        while multiple != infinity {
+            multiple = multiple.group_op(point, &curve);
+            order += 1;
+        }
+        if !orders.contains(&order) {
+            orders.push(order);
+            representatives.push(point);
+        }
+    }
+    assert_eq!(orders.len(), 12);
+
+    for point in representatives {
+        let inverse = point.group_inv();
+        assert_eq!(point.group_op(&inverse, &curve), infinity);
+        assert_eq!(inverse.group_op(point, &curve), infinity);
+        assert_eq!(point.group_op(&infinity, &curve), *point);
+        assert_eq!(infinity.group_op(point, &curve), *point);
+        assert_eq!(
+            point.pow_with(&BigUint::from(2u32), &curve, ScalarMul::DoubleAndAdd),
+            point.group_op(point, &curve)
+        );
+
+        let mut expected = infinity.clone();
+        for exp in 0u32..70 {
+            let exp_big = BigUint::from(exp);
+            for method in METHODS {
+                assert_eq!(
+                    point.pow_with(&exp_big, &curve, method),
+                    expected,
+                    "{:?} {:?}^{}",
+                    method,
+                    point,
+                    exp
+                );
+            }
+            assert_eq!(inverse.pow(&exp_big, &curve), expected.group_inv());
+            expected = expected.group_op(point, &curve);
+        }
+    }
+}
+
+// Fields over 576 bits are too big for the Montgomery backend and use plain BigUint arithmetic instead. The
+//  field is the Mersenne prime 2^607 - 1, a is 1, and b is whatever puts (3, 5) on the curve.
+#[test]
+fn big_field_fallback() {
+    let field = (BigUint::from(1u32) << 607) - 1u32;
+    let x = ModNum::new(&BigUint::from(3u32), &field);
+    let y = ModNum::new(&BigUint::from(5u32), &field);
+    let a = ModNum::new(&BigUint::from(1u32), &field);
+    let b = y
+        .mul(&y)
+        .add(&x.pow(&BigUint::from(3u32)).add(&a.mul(&x)).add_inv());
+    let point = EllipticType::Point(Point::new(x, y, &a, &b));
+    let curve = EllipticCurve::new(a, b, point.clone());
+    for exp in 0u32..20 {
+        let exp = BigUint::from(exp);
+        let expected = repeated_group_op(&point, &exp, &curve);
+        assert_eq!(point.pow(&exp, &curve), expected);
+        assert_eq!(point.pow_secret(&exp, &curve), expected);
+    }
+    let exp = BigUint::from(0xdead_beefu32);
+    assert_eq!(
+        point.pow_secret(&exp, &curve),
+        point.pow_with(&exp, &curve, ScalarMul::DoubleAndAdd)
+    );
+}
+
+#[test]
+fn mod_num_pow() {
+    let curve = Curves::FiveTwoOne.into_curve();
+    let field = curve.field();
+    let mut rng = rand::thread_rng();
+    for _ in 0..10 {
+        let base = rng.gen_biguint_below(field);
+        let exp = rng.gen_biguint(600);
+        assert_eq!(
+            ModNum::new(&base, field).pow(&exp).value(),
+            &base.modpow(&exp, field)
+        );
+    }
+    let base = ModNum::new(&BigUint::from(5u32), field);
+    assert_eq!(base.pow(&BigUint::from(0u32)).value(), &BigUint::from(1u32));
+}
+
+fn repeated_group_op(point: &EllipticType, exp: &BigUint, curve: &EllipticCurve) -> EllipticType {
+    let mut result = EllipticType::Infinity(Inf::new(curve.field()));
+    let mut i = BigUint::from(0u32);
+    while &i < exp {
+        result = result.group_op(point, curve);
+        i += 1u32;
+    }
+    result
+}
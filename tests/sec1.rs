@@ -0,0 +1,226 @@
+use encryption::el_gamal::Curves;
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Inf, Point};
+use encryption::error::Error;
+use encryption::modular_numbers::ModNum;
+use num_bigint::BigUint;
+
+const P_256_G_X: &str = "6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296";
+const P_256_G_Y: &str = "4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5";
+
+fn hex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn invalid_encoding(result: Result<EllipticType, Error>) -> bool {
+    matches!(result, Err(Error::InvalidEncoding(_)))
+}
+
+// The P-256 generator from SEC 2, with an odd y.
+#[test]
+fn known_point() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let g = curve.init_point();
+    let uncompressed = hex(&format!("04{}{}", P_256_G_X, P_256_G_Y));
+    let compressed = hex(&format!("03{}", P_256_G_X));
+    assert_eq!(g.to_sec1_bytes(false), uncompressed);
+    assert_eq!(g.to_sec1_bytes(true), compressed);
+    assert_eq!(
+        EllipticType::from_sec1_bytes(&uncompressed, &curve).as_ref(),
+        Ok(g)
+    );
+    assert_eq!(
+        EllipticType::from_sec1_bytes(&compressed, &curve).as_ref(),
+        Ok(g)
+    );
+}
+
+#[test]
+fn infinity() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let infinity = EllipticType::Infinity(Inf::in_field(curve.shared_field()));
+    assert_eq!(infinity.to_sec1_bytes(true), vec![0x00]);
+    assert_eq!(infinity.to_sec1_bytes(false), vec![0x00]);
+    assert_eq!(EllipticType::from_sec1_bytes(&[0x00], &curve), Ok(infinity));
+    assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+        &[0x00, 0x00],
+        &curve
+    )));
+    assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+        &[0x00; 33],
+        &curve
+    )));
+}
+
+// A point and its inverse have the same x and y's of opposite parity, so between them both compressed
+//  prefixes are covered.
+#[test]
+fn both_parities() {
+    for named in [Curves::TwoFiveSix, Curves::Secp256k1, Curves::FiveTwoOne] {
+        let curve = named.into_curve();
+        for exp in 1u32..6 {
+            let point = curve.init_point_pow(&BigUint::from(exp));
+            for point in [point.group_inv(), point] {
+                let y_is_odd = match &point {
+                    EllipticType::Point(point) => point.y().value().bit(0),
+                    EllipticType::Infinity(_) => unreachable!(),
+                };
+                let compressed = point.to_sec1_bytes(true);
+                assert_eq!(compressed[0], if y_is_odd { 0x03 } else { 0x02 });
+                assert_eq!(
+                    EllipticType::from_sec1_bytes(&compressed, &curve).as_ref(),
+                    Ok(&point)
+                );
+                let uncompressed = point.to_sec1_bytes(false);
+                assert_eq!(uncompressed[0], 0x04);
+                assert_eq!(
+                    EllipticType::from_sec1_bytes(&uncompressed, &curve),
+                    Ok(point)
+                );
+            }
+        }
+    }
+}
+
+// y^2 = x^3 + 2x + 3 mod 211, (210, 0) has y = 0, which has no odd version.
+#[test]
+fn zero_y() {
+    let field = BigUint::from(211u32);
+    let num = |value: u32| ModNum::new(&BigUint::from(value), &field);
+    let point = EllipticType::Point(Point::new(num(210), num(0), &num(2), &num(3)));
+    let curve = EllipticCurve::new(num(2), num(3), point.clone());
+    assert_eq!(point.to_sec1_bytes(true), vec![0x02, 210]);
+    assert_eq!(
+        EllipticType::from_sec1_bytes(&[0x02, 210], &curve),
+        Ok(point)
+    );
+    assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+        &[0x03, 210],
+        &curve
+    )));
+}
+
+#[test]
+fn malformed() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let g = curve.init_point();
+    let compressed = g.to_sec1_bytes(true);
+    let uncompressed = g.to_sec1_bytes(false);
+
+    // Hybrid points, 0x06 and 0x07, aren't supported, and the other prefixes don't exist.
+    for prefix in [0x01, 0x05, 0x06, 0x07, 0xFF] {
+        let mut bytes = uncompressed.clone();
+        bytes[0] = prefix;
+        assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+            &bytes, &curve
+        )));
+        let mut bytes = compressed.clone();
+        bytes[0] = prefix;
+        assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+            &bytes, &curve
+        )));
+    }
+
+    // Compressed and uncompressed lengths swapped, a byte short, and a byte over.
+    let mut compressed_long = compressed.clone();
+    compressed_long.push(0);
+    let mut uncompressed_long = uncompressed.clone();
+    uncompressed_long.push(0);
+    let mut swapped = uncompressed.clone();
+    swapped[0] = 0x02;
+    let mut unswapped = compressed.clone();
+    unswapped[0] = 0x04;
+    for bytes in [
+        vec![],
+        compressed[..32].to_vec(),
+        compressed_long,
+        uncompressed[..64].to_vec(),
+        uncompressed_long,
+        swapped,
+        unswapped,
+    ] {
+        assert!(
+            invalid_encoding(EllipticType::from_sec1_bytes(&bytes, &curve)),
+            "{:?}",
+            bytes
+        );
+    }
+}
+
+#[test]
+fn not_on_curve() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let mut uncompressed = curve.init_point().to_sec1_bytes(false);
+    uncompressed[64] ^= 1;
+    assert_eq!(
+        EllipticType::from_sec1_bytes(&uncompressed, &curve),
+        Err(Error::NotOnCurve)
+    );
+
+    // The first x where x^3 + a*x + b has no square root, so no point has it.
+    let mut x = 0u32;
+    loop {
+        let x_num = ModNum::in_field(&BigUint::from(x), curve.shared_field());
+        if (&x_num * &x_num * &x_num + curve.a() * &x_num + curve.b())
+            .sqrt()
+            .is_none()
+        {
+            break;
+        }
+        x += 1;
+    }
+    let mut compressed = vec![0; 33];
+    compressed[29..].copy_from_slice(&x.to_be_bytes());
+    for prefix in [0x02, 0x03] {
+        compressed[0] = prefix;
+        assert_eq!(
+            EllipticType::from_sec1_bytes(&compressed, &curve),
+            Err(Error::NotOnCurve)
+        );
+    }
+}
+
+// Coordinates of p or more would be reduced into the field and turn into a different, valid, point. On
+//  y^2 = x^3 + 2x + 3 mod 211 (3, 6) could be written as (214, 6) or (3, 217) without the check.
+#[test]
+fn coordinates_outside_field() {
+    let field = BigUint::from(211u32);
+    let num = |value: u32| ModNum::new(&BigUint::from(value), &field);
+    let point = EllipticType::Point(Point::new(num(3), num(6), &num(2), &num(3)));
+    let curve = EllipticCurve::new(num(2), num(3), point.clone());
+    assert_eq!(point.to_sec1_bytes(false), vec![0x04, 3, 6]);
+    assert_eq!(
+        EllipticType::from_sec1_bytes(&[0x04, 3, 6], &curve),
+        Ok(point)
+    );
+    for bytes in [
+        [0x04, 214, 6],
+        [0x04, 3, 217],
+        [0x04, 211, 6],
+        [0x04, 255, 255],
+    ] {
+        assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+            &bytes, &curve
+        )));
+    }
+    for bytes in [[0x02, 214], [0x03, 214], [0x02, 211], [0x03, 255]] {
+        assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+            &bytes, &curve
+        )));
+    }
+
+    let curve = Curves::TwoFiveSix.into_curve();
+    let mut x_is_p = vec![0x02];
+    x_is_p.extend(curve.field().to_bytes_be());
+    assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+        &x_is_p, &curve
+    )));
+    let mut y_is_p = curve.init_point().to_sec1_bytes(false)[..33].to_vec();
+    y_is_p.extend(curve.field().to_bytes_be());
+    assert!(invalid_encoding(EllipticType::from_sec1_bytes(
+        &y_is_p, &curve
+    )));
+}
@@ -0,0 +1,128 @@
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Point};
+use encryption::modular_numbers::{ModNum, SqrtMethod};
+use num_bigint::{BigUint, RandBigInt};
+use std::collections::HashSet;
+
+const METHODS: [SqrtMethod; 2] = [SqrtMethod::TonelliShanks, SqrtMethod::Cipolla];
+
+fn primes_below(limit: u32) -> Vec<u32> {
+    (2..limit)
+        .filter(|n| (2..*n).take_while(|d| d * d <= *n).all(|d| n % d != 0))
+        .collect()
+}
+
+// Every number in every prime field below 700, which covers both 1 and 3 mod 4 and fields like 257 and 641
+//  where field - 1 is divisible by a large power of two.
+#[test]
+fn every_small_field() {
+    let primes = primes_below(700);
+    assert!(primes.iter().any(|p| p % 4 == 1) && primes.iter().any(|p| p % 4 == 3));
+    for p in primes {
+        let field = BigUint::from(p);
+        let squares: HashSet<u32> = (0..p).map(|x| x * x % p).collect();
+        for v in 0..p {
+            let value = ModNum::new(&BigUint::from(v), &field);
+            let is_square = squares.contains(&v);
+            assert_eq!(value.is_square(), is_square, "{}", value);
+            for method in METHODS {
+                match value.sqrt_with(method) {
+                    Some(root) => {
+                        assert!(is_square, "{} has no root but got {}", value, root);
+                        assert_eq!(&root * &root, value, "{:?}", method);
+                    }
+                    None => assert!(!is_square, "{:?} missed the root of {}", method, value),
+                }
+            }
+        }
+    }
+}
+
+// P-224's field is 2^224 - 2^96 + 1 so field - 1 is divisible by 2^96, the worst case for Tonelli-Shanks.
+//  2^255 - 19 is 5 mod 8 and P-256's field is 3 mod 4.
+#[test]
+fn large_fields() {
+    let one = BigUint::from(1u32);
+    let fields = [
+        (&one << 224) - (&one << 96) + 1u32,
+        (&one << 255) - 19u32,
+        BigUint::parse_bytes(
+            b"ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+            16,
+        )
+        .unwrap(),
+    ];
+    let mut rng = rand::thread_rng();
+    for field in fields {
+        let mut non_squares = 0;
+        for _ in 0..20 {
+            let x = ModNum::new(&rng.gen_biguint_below(&field), &field);
+            let square = &x * &x;
+            let maybe = ModNum::new(&rng.gen_biguint_below(&field), &field);
+            for method in METHODS {
+                let root = square.sqrt_with(method).unwrap();
+                assert!(root == x || root == -&x);
+                match maybe.sqrt_with(method) {
+                    Some(root) => assert_eq!(&root * &root, maybe),
+                    None => assert!(!maybe.is_square()),
+                }
+            }
+            if !maybe.is_square() {
+                non_squares += 1;
+            }
+        }
+        assert!(non_squares > 0);
+    }
+}
+
+#[test]
+fn special_cases() {
+    for p in [2u32, 3, 5, 13, 17] {
+        let field = BigUint::from(p);
+        let zero = ModNum::new(&BigUint::from(0u32), &field);
+        for method in METHODS {
+            assert_eq!(zero.sqrt_with(method), Some(zero.clone()));
+        }
+        assert_eq!(zero.legendre_symbol(), 0);
+    }
+    let one = ModNum::new(&BigUint::from(1u32), &BigUint::from(2u32));
+    assert_eq!(one.sqrt(), Some(one.clone()));
+}
+
+// The algorithms assume a prime field. Any other modulus must give a right answer or None, never a wrong root.
+#[test]
+fn composite_fields() {
+    for n in [9u32, 15, 21, 25, 33, 45, 65, 105, 221] {
+        let field = BigUint::from(n);
+        for value in 0..n {
+            let value = ModNum::new(&BigUint::from(value), &field);
+            for method in METHODS {
+                if let Some(root) = value.sqrt_with(method) {
+                    assert_eq!(&root * &root, value);
+                }
+            }
+        }
+    }
+}
+
+// y^2 = x^3 + 2x + 3 over 257, which is 1 mod 4 so compressed points need the general square root.
+#[test]
+fn compressed_points_on_a_1_mod_4_field() {
+    let field = BigUint::from(257u32);
+    let num = |v: u32| ModNum::new(&BigUint::from(v), &field);
+    let (a, b) = (num(2), num(3));
+    let points: Vec<Point> = (0..257)
+        .flat_map(|x| (0..257).map(move |y| (x, y)))
+        .filter_map(|(x, y)| Point::try_new(num(x), num(y), &a, &b).ok())
+        .collect();
+    assert!(points.len() > 200);
+    let curve = EllipticCurve::new(a, b, EllipticType::Point(points[0].clone()));
+    for point in points {
+        let point = EllipticType::Point(point);
+        let bytes = point.to_sec1_bytes(true);
+        assert_eq!(
+            EllipticType::from_sec1_bytes(&bytes, &curve).unwrap(),
+            point
+        );
+    }
+}
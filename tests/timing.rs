@@ -0,0 +1,112 @@
+use encryption::el_gamal::Curves;
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::EllipticType;
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+use std::time::Instant;
+
+// A statistical timing test in the style of dudect, Reparaz, Balasch, and Verbauwhede,
+//  https://eprint.iacr.org/2016/1123
+//   The operation is timed over and over with the exponent drawn from one of two classes, a single fixed
+//  exponent or a fresh random one, picked at random for every measurement so drift in the machine hits both
+//   the same. If the time doesn't depend on the exponent the two sets of times have the same mean, and
+//  Welch's t-test says how sure we can be that they don't. |t| over 4.5 is taken as a leak.
+// Timing is far too noisy in a debug build and on a busy machine to run with everything else, so these are
+//  ignored by default. Run them with
+//   cargo test --release --test timing -- --ignored --nocapture
+
+const SAMPLES: usize = 20_000;
+const THRESHOLD: f64 = 4.5;
+
+// pow_secret shouldn't be distinguishable. The fixed exponent has a single bit set, which is about as far
+//  from a random exponent as it gets, pow would do one addition for it instead of one for every other bit.
+#[test]
+#[ignore]
+fn pow_secret_is_constant_time() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let point = curve.init_point_pow(&BigUint::from(12345u32));
+    let fixed = BigUint::from(1u32) << (curve.order().unwrap().bits() - 1);
+    let t = welch_t(&curve, &fixed, |exp| point.pow_secret(exp, &curve));
+    println!("pow_secret: t = {:.2}", t);
+    assert!(t.abs() < THRESHOLD, "pow_secret leaks, t = {:.2}", t);
+
+    let t = welch_t(&curve, &fixed, |exp| curve.init_point_pow_secret(exp));
+    println!("init_point_pow_secret: t = {:.2}", t);
+    assert!(
+        t.abs() < THRESHOLD,
+        "init_point_pow_secret leaks, t = {:.2}",
+        t
+    );
+}
+
+// Shows the harness can actually see a leak, pow should fail the same test by a wide margin.
+#[test]
+#[ignore]
+fn pow_is_not_constant_time() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let point = curve.init_point_pow(&BigUint::from(12345u32));
+    let fixed = BigUint::from(1u32) << (curve.order().unwrap().bits() - 1);
+    let t = welch_t(&curve, &fixed, |exp| point.pow(exp, &curve));
+    println!("pow: t = {:.2}", t);
+    assert!(t.abs() > THRESHOLD, "pow didn't leak, t = {:.2}", t);
+}
+
+// Times operation with fixed and with random exponents and returns Welch's t statistic for the two sets.
+//  The exponents are all made before any timing starts so making them isn't measured.
+fn welch_t(
+    curve: &EllipticCurve,
+    fixed: &BigUint,
+    operation: impl Fn(&BigUint) -> EllipticType,
+) -> f64 {
+    let mut rng = rand::thread_rng();
+    let n = curve.order().unwrap();
+    let inputs: Vec<(bool, BigUint)> = (0..SAMPLES)
+        .map(|_| {
+            if rng.gen() {
+                (true, fixed.clone())
+            } else {
+                (false, rng.gen_biguint_below(n))
+            }
+        })
+        .collect();
+
+    // Warm up the caches and the init_point table.
+    for (_, exp) in inputs.iter().take(100) {
+        operation(exp);
+    }
+    let mut fixed_times = Vec::new();
+    let mut random_times = Vec::new();
+    for (is_fixed, exp) in &inputs {
+        let start = Instant::now();
+        std::hint::black_box(operation(std::hint::black_box(exp)));
+        let elapsed = start.elapsed().as_nanos() as f64;
+        if *is_fixed {
+            fixed_times.push(elapsed);
+        } else {
+            random_times.push(elapsed);
+        }
+    }
+
+    // dudect throws away the slowest measurements, they are mostly the process being interrupted.
+    let cutoff = percentile(&[fixed_times.clone(), random_times.clone()].concat(), 0.9);
+    fixed_times.retain(|&time| time < cutoff);
+    random_times.retain(|&time| time < cutoff);
+
+    let (fixed_mean, fixed_var) = mean_var(&fixed_times);
+    let (random_mean, random_var) = mean_var(&random_times);
+    (fixed_mean - random_mean)
+        / (fixed_var / fixed_times.len() as f64 + random_var / random_times.len() as f64).sqrt()
+}
+
+fn percentile(times: &[f64], fraction: f64) -> f64 {
+    let mut sorted = times.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted[(sorted.len() as f64 * fraction) as usize]
+}
+
+fn mean_var(times: &[f64]) -> (f64, f64) {
+    let mean = times.iter().sum::<f64>() / times.len() as f64;
+    let var =
+        times.iter().map(|time| (time - mean).powi(2)).sum::<f64>() / (times.len() - 1) as f64;
+    (mean, var)
+}
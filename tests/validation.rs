@@ -0,0 +1,140 @@
+use encryption::ciphertext::Ciphertext;
+use encryption::el_gamal::{self, Curves, ElGamal};
+use encryption::elliptic_curve::EllipticCurve;
+use encryption::elliptic_point::{EllipticType, Inf, Point};
+use encryption::error::Error;
+use encryption::keys::PublicKey;
+use encryption::modular_numbers::ModNum;
+use num_bigint::BigUint;
+
+// y^2 = x^3 + 2x + 3 mod 211 has 204 = 4 * 51 points. (3, 6) generates the 51 of them in the subgroup of
+//  order 51, (145, 50) has order 4 and (210, 0) has order 2 so they are on the curve but not in that
+//   subgroup.
+fn small_curve() -> EllipticCurve {
+    let field = BigUint::from(211u32);
+    EllipticCurve::new_with_order(
+        num(2, &field),
+        num(3, &field),
+        point(3, 6, 3),
+        BigUint::from(51u32),
+        BigUint::from(4u32),
+    )
+}
+
+fn num(value: u32, field: &BigUint) -> ModNum {
+    ModNum::new(&BigUint::from(value), field)
+}
+
+fn point(x: u32, y: u32, b: u32) -> EllipticType {
+    let field = BigUint::from(211u32);
+    EllipticType::Point(Point::new(
+        num(x, &field),
+        num(y, &field),
+        &num(2, &field),
+        &num(b, &field),
+    ))
+}
+
+// A point on y^2 = x^3 - 3x + b + 1, the same curve as P-256 except for b.
+fn invalid_curve_point(curve: &EllipticCurve) -> EllipticType {
+    let field = curve.shared_field();
+    let b = curve.b() + ModNum::in_field(&BigUint::from(1u32), field);
+    let mut x = 1u32;
+    loop {
+        let x_num = ModNum::in_field(&BigUint::from(x), field);
+        if let Some(y) = (&x_num * &x_num * &x_num + curve.a() * &x_num + &b).sqrt() {
+            return EllipticType::Point(Point::new(x_num, y, curve.a(), &b));
+        }
+        x += 1;
+    }
+}
+
+#[test]
+fn validate_small_curve() {
+    let curve = small_curve();
+    let infinity = EllipticType::Infinity(Inf::new(curve.field()));
+    for good in [
+        point(3, 6, 3),
+        point(20, 5, 3),
+        curve.init_point_pow(&BigUint::from(7u32)),
+    ] {
+        assert_eq!(curve.validate_point(&good), Ok(()));
+        assert_eq!(curve.validate_public_key(&good), Ok(()));
+    }
+
+    // On the curve but outside the subgroup.
+    for outside in [point(145, 50, 3), point(210, 0, 3)] {
+        assert_eq!(curve.validate_point(&outside), Ok(()));
+        assert_eq!(
+            curve.validate_public_key(&outside),
+            Err(Error::InvalidPublicKey)
+        );
+        assert_eq!(
+            PublicKey::try_new(outside, &curve).unwrap_err(),
+            Error::InvalidPublicKey
+        );
+    }
+
+    assert_eq!(curve.validate_point(&infinity), Ok(()));
+    assert_eq!(
+        curve.validate_public_key(&infinity),
+        Err(Error::InvalidPublicKey)
+    );
+
+    let other_curve = point(0, 65, 5);
+    assert_eq!(curve.validate_point(&other_curve), Err(Error::NotOnCurve));
+    assert_eq!(
+        curve.validate_public_key(&other_curve),
+        Err(Error::NotOnCurve)
+    );
+
+    let other_field = EllipticType::Infinity(Inf::new(&BigUint::from(223u32)));
+    assert_eq!(
+        curve.validate_point(&other_field),
+        Err(Error::FieldMismatch)
+    );
+}
+
+#[test]
+fn encrypt_rejects_invalid_public_keys() {
+    let curve = Curves::TwoFiveSix.into_curve();
+    let message = "invalid curve".to_string();
+    let bad = invalid_curve_point(&curve);
+    assert_eq!(curve.validate_point(&bad), Err(Error::NotOnCurve));
+    assert_eq!(
+        el_gamal::try_encrypt(&bad, &curve, &message).unwrap_err(),
+        Error::NotOnCurve
+    );
+    let infinity = EllipticType::Infinity(Inf::new(curve.field()));
+    assert_eq!(
+        el_gamal::try_encrypt(&infinity, &curve, &message).unwrap_err(),
+        Error::InvalidPublicKey
+    );
+}
+
+// A cipher text with its c_0 or c_1 swapped for a point that isn't valid is refused before the private key
+//  touches it.
+#[test]
+fn decrypt_rejects_invalid_points() {
+    let el_gamal = ElGamal::new(Curves::TwoFiveSix);
+    let curve = el_gamal.curve();
+    let key = el_gamal.private_key();
+    let message = "invalid curve".to_string();
+    let cipher_text = el_gamal::encrypt(el_gamal.public_key(), curve, &message);
+    assert_eq!(
+        el_gamal::try_decrypt(key, curve, &cipher_text),
+        Ok(message.clone())
+    );
+
+    let bad = invalid_curve_point(curve);
+    let infinity = EllipticType::Infinity(Inf::new(curve.field()));
+    let (c_0, c_1) = cipher_text.chunks()[0].clone();
+    for (chunk, error) in [
+        ((bad.clone(), c_1.clone()), Error::NotOnCurve),
+        ((c_0.clone(), bad), Error::NotOnCurve),
+        ((infinity, c_1), Error::InvalidPublicKey),
+    ] {
+        let tampered = Ciphertext::new(vec![chunk], cipher_text.len());
+        assert_eq!(el_gamal::try_decrypt(key, curve, &tampered), Err(error));
+    }
+}